@@ -1,8 +1,10 @@
+use futures::stream::BoxStream;
 use std::{collections::VecDeque, fmt::Debug};
+use tokio_util::sync::CancellationToken;
 use tracing::Level;
 
-use crate::domain::repositories::FetchBasicDataTrait;
-use crate::{metadata::BasicData, url::Url};
+use crate::domain::repositories::{CheckExistenceTrait, FetchBasicDataTrait};
+use crate::{metadata::BasicData, url::Url, FetchedItem, FetchedResults, UrlExistence};
 
 #[derive(Debug)]
 pub struct YouTubeService<T: FetchBasicDataTrait> {
@@ -17,19 +19,126 @@ where
         Self { api }
     }
 
-    #[tracing::instrument(level = Level::DEBUG)]
+    /// 取得に使用するクライアント, 消費したquotaなどの確認に使用する
+    pub fn api(&self) -> &T {
+        &self.api
+    }
+
+    /// `cancel`は呼び出しごとに渡す, `cancel()`を呼ぶと送信中のリクエストを破棄し新たなリクエストを送らない
+    #[tracing::instrument(level = Level::DEBUG, skip(cancel))]
     pub async fn using_urls(
         &self,
         urls: VecDeque<Url>,
-    ) -> Result<VecDeque<Result<BasicData, Url>>, crate::Error> {
-        self.api.fetch_basic_data_with_urls(urls).await
+        cancel: CancellationToken,
+    ) -> Result<FetchedResults<BasicData, Url>, crate::Error> {
+        self.api.fetch_basic_data_with_urls(urls, cancel).await
+    }
+
+    /// 取得できたものから順に返す, 詳しくは`FetchBasicDataTrait`を参照
+    pub fn using_urls_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<BasicData, Url>, crate::Error>> {
+        self.api.fetch_basic_data_stream(urls, cancel)
     }
 
-    #[tracing::instrument(level = Level::DEBUG)]
+    #[tracing::instrument(level = Level::DEBUG, skip(cancel))]
     pub async fn using_url(
         &self,
         url: Url,
+        cancel: CancellationToken,
     ) -> Result<Result<BasicData, Url>, crate::Error> {
-        self.api.fetch_basic_data_with_url(url).await
+        self.api.fetch_basic_data_with_url(url, cancel).await
+    }
+}
+
+impl<T> YouTubeService<T>
+where
+    T: FetchBasicDataTrait + CheckExistenceTrait + Debug,
+{
+    /// 動画の情報を取得せずに存在のみを確認する, 詳しくは`CheckExistenceTrait`を参照
+    #[tracing::instrument(level = Level::DEBUG, skip(cancel))]
+    pub async fn check_existence(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> Result<FetchedResults<UrlExistence, Url>, crate::Error> {
+        FetchedResults::try_collect(self.check_existence_stream(urls, cancel)).await
+    }
+
+    /// 確認できたものから順に返す
+    pub fn check_existence_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<UrlExistence, Url>, crate::Error>> {
+        self.api.check_existence_stream(urls, cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{id::VideoId, url::UrlVideo};
+    use futures::{stream, StreamExt};
+
+    /// `cancel`されていれば中断し, そうでなければ全て`NotFound`を返す
+    #[derive(Debug)]
+    struct NotFoundApi;
+
+    #[async_trait::async_trait]
+    impl FetchBasicDataTrait for NotFoundApi {
+        async fn fetch_basic_data_with_urls(
+            &self,
+            urls: VecDeque<Url>,
+            cancel: CancellationToken,
+        ) -> Result<FetchedResults<BasicData, Url>, crate::Error> {
+            FetchedResults::try_collect(self.fetch_basic_data_stream(urls, cancel))
+                .await
+        }
+
+        fn fetch_basic_data_stream(
+            &self,
+            urls: VecDeque<Url>,
+            cancel: CancellationToken,
+        ) -> BoxStream<'_, Result<FetchedItem<BasicData, Url>, crate::Error>> {
+            stream::iter(urls.into_iter().map(move |url| {
+                Ok(match cancel.is_cancelled() {
+                    true => FetchedItem::Unattempted(url),
+                    false => FetchedItem::NotFound(url),
+                })
+            }))
+            .boxed()
+        }
+
+        async fn fetch_basic_data_with_url(
+            &self,
+            url: Url,
+            cancel: CancellationToken,
+        ) -> Result<Result<BasicData, Url>, crate::Error> {
+            match cancel.is_cancelled() {
+                true => Err(crate::Error::Interrupted),
+                false => Ok(Err(url)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_does_not_affect_next_job() {
+        let service = YouTubeService::new(NotFoundApi);
+        let url = Url::from(UrlVideo::from(VideoId::all_1()));
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(matches!(
+            service.using_url(url.clone(), cancel).await,
+            Err(crate::Error::Interrupted)
+        ));
+
+        assert_eq!(
+            service.using_url(url.clone(), CancellationToken::new()).await.unwrap(),
+            Err(url)
+        );
     }
 }
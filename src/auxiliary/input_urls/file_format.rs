@@ -0,0 +1,750 @@
+use serde_json::Value;
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use crate::{
+    id::{PlaylistId, VideoId},
+    url::{ExtractedUrl, IdStrictness, PlaylistPolicy, Url, UrlPlaylist, UrlVideo},
+    ParseError,
+};
+
+/// 入力ファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    /// 拡張子と内容から判定する
+    #[default]
+    Auto,
+    /// 1行に1つ以上の`url`, 空白文字とカンマで区切る, `#`以降はコメント
+    Lines,
+    /// ヘッダー付きのカンマ区切り
+    Csv,
+    /// ヘッダー付きのタブ区切り
+    Tsv,
+    /// 文字列の配列, またはこのプログラムが出力した`json`
+    Json,
+    /// 文字列の配列, またはこのプログラムが出力した`yaml`
+    Yaml,
+    /// チャットの記録, Markdown, HTMLなどの文章, 含まれる`url`を全て探す
+    Text,
+}
+
+impl InputFormat {
+    /// `Auto`のとき, 拡張子から, 分からなければ内容から判定する
+    fn resolve(self, path: Option<&Path>, contents: &str) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        let ext = path
+            .and_then(|p| p.extension())
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        match ext.as_deref() {
+            Some("csv") => Self::Csv,
+            Some("tsv") => Self::Tsv,
+            Some("json") => Self::Json,
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("html" | "htm" | "md" | "markdown") => Self::Text,
+            _ if contents.trim_start().starts_with(['[', '{']) => Self::Json,
+            _ => Self::Lines,
+        }
+    }
+}
+
+/// `csv`, `tsv`で`url`を読み込む列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputColumn {
+    /// ヘッダーの名前, 大文字と小文字を区別しない
+    Name(String),
+    /// 1から始まる列の番号
+    Index(usize),
+}
+
+impl Default for InputColumn {
+    fn default() -> Self {
+        Self::Name("url".into())
+    }
+}
+
+impl FromStr for InputColumn {
+    type Err = String;
+    /// 数字のみのときは列の番号, それ以外はヘッダーの名前
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("column must not be empty".into());
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err("column index starts from 1".into()),
+            Ok(index) => Ok(Self::Index(index)),
+            Err(_) => Ok(Self::Name(s.into())),
+        }
+    }
+}
+
+impl Display for InputColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// ファイルを読み込むときの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputOptions {
+    pub format: InputFormat,
+    pub column: InputColumn,
+    /// `url`を省略したIDの解釈
+    pub strictness: IdStrictness,
+    /// 動画と再生リストの両方を指す`url`の扱い, 入力ごとに接頭語で変えられる
+    pub playlist_policy: PlaylistPolicy,
+}
+
+impl InputOptions {
+    /// 入力を1つ解釈し, `playlist_policy`に従って取得する`url`に分ける
+    ///
+    /// 先頭に`video:`, `playlist:`, `both:`があれば, この入力のみその方針を使う
+    pub(super) fn parse_entry(&self, entry: &str) -> Result<Vec<Url>, ParseError> {
+        let (policy, entry) = PlaylistPolicy::strip_prefix(entry);
+        let policy = policy.unwrap_or(self.playlist_policy);
+        Url::from_input(entry, self.strictness).map(|url| policy.apply(url))
+    }
+}
+
+/// 読み込めなかった入力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidEntry {
+    /// ファイルのパス, 標準入力のときは`-`
+    pub origin: String,
+    /// 1から始まる行番号, 特定できないときは`None`
+    pub line: Option<usize>,
+    pub entry: String,
+    pub reason: String,
+}
+
+impl Display for InvalidEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.origin)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, ": `{}`: {}", self.entry, self.reason)
+    }
+}
+
+/// 入力ファイルを解析した結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ParsedContents {
+    /// 読み込めた`url`, 記述された順
+    pub urls: Vec<Url>,
+    pub invalid: Vec<InvalidEntry>,
+    /// `Text`のとき見つけた`url`と行番号, `playlist_policy`を適用する前
+    pub extracted: Vec<ExtractedUrl>,
+    /// 各入力を`url`として解釈するときの設定
+    options: InputOptions,
+}
+
+impl ParsedContents {
+    fn push_str(&mut self, origin: &str, line: Option<usize>, entry: &str) {
+        match self.options.parse_entry(entry) {
+            Ok(urls) => self.urls.extend(urls),
+            Err(e) => self.reject(origin, line, entry, e.to_string()),
+        }
+    }
+
+    fn reject(
+        &mut self,
+        origin: &str,
+        line: Option<usize>,
+        entry: &str,
+        reason: impl Into<String>,
+    ) {
+        self.invalid.push(InvalidEntry {
+            origin: origin.into(),
+            line,
+            entry: entry.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+/// `contents`を`options`の形式で解析する
+///
+/// `origin`はエラーの表示に使用し, `path`は形式の判定に使用する
+pub(super) fn parse_contents(
+    contents: &str,
+    origin: &str,
+    path: Option<&Path>,
+    options: &InputOptions,
+) -> ParsedContents {
+    let mut parsed = ParsedContents {
+        options: options.clone(),
+        ..Default::default()
+    };
+    match options.format.resolve(path, contents) {
+        InputFormat::Auto | InputFormat::Lines => {
+            parse_lines(contents, origin, &mut parsed)
+        }
+        InputFormat::Csv => {
+            parse_table(contents, origin, ',', &options.column, &mut parsed)
+        }
+        InputFormat::Tsv => {
+            parse_table(contents, origin, '\t', &options.column, &mut parsed)
+        }
+        InputFormat::Json => match serde_json::from_str::<Value>(contents) {
+            Ok(value) => {
+                let mut lines = LineFinder::new(contents, true);
+                parse_value(&value, &mut lines, origin, &mut parsed)
+            }
+            Err(e) => parsed.reject(
+                origin,
+                Some(e.line()),
+                "",
+                format!("invalid json: {}", e),
+            ),
+        },
+        InputFormat::Yaml => match serde_yaml::from_str::<Value>(contents) {
+            Ok(value) => {
+                let mut lines = LineFinder::new(contents, false);
+                parse_value(&value, &mut lines, origin, &mut parsed)
+            }
+            Err(e) => {
+                let line = e.location().map(|l| l.line());
+                parsed.reject(origin, line, "", format!("invalid yaml: {}", e))
+            }
+        },
+        InputFormat::Text => {
+            parsed.extracted = Url::extract(contents);
+            let policy = options.playlist_policy;
+            parsed.urls = parsed
+                .extracted
+                .iter()
+                .flat_map(|e| policy.apply(e.url.clone()))
+                .collect();
+        }
+    }
+    parsed
+}
+
+/// `#`が行頭または空白文字の直後にあるとき, それ以降をコメントとして扱う
+///
+/// `url`に含まれる`#`(フラグメント)はコメントとしない
+fn strip_comment(line: &str) -> &str {
+    let mut prev_is_space = true;
+    for (i, c) in line.char_indices() {
+        if c == '#' && prev_is_space {
+            return &line[..i];
+        }
+        prev_is_space = c.is_whitespace();
+    }
+    line
+}
+
+fn parse_lines(contents: &str, origin: &str, parsed: &mut ParsedContents) {
+    for (i, line) in contents.lines().enumerate() {
+        for entry in strip_comment(line)
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+        {
+            parsed.push_str(origin, Some(i + 1), entry);
+        }
+    }
+}
+
+fn parse_table(
+    contents: &str,
+    origin: &str,
+    delimiter: char,
+    column: &InputColumn,
+    parsed: &mut ParsedContents,
+) {
+    let records = match split_records(contents, delimiter) {
+        Ok(records) => records,
+        Err((line, reason)) => return parsed.reject(origin, Some(line), "", reason),
+    };
+    let mut records = records.into_iter();
+    let Some((header_line, header)) = records.next() else {
+        return;
+    };
+    let index = match column {
+        InputColumn::Name(name) => {
+            header.iter().position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+        }
+        InputColumn::Index(index) => (*index <= header.len()).then(|| index - 1),
+    };
+    let Some(index) = index else {
+        let reason = format!(
+            "no column `{}` in the header, choose one with `--input-column`",
+            column
+        );
+        return parsed.reject(origin, Some(header_line), &header.join(","), reason);
+    };
+    for (line, record) in records {
+        match record.get(index).map(|field| field.trim()) {
+            Some(field) if !field.is_empty() => {
+                parsed.push_str(origin, Some(line), field)
+            }
+            _ => {
+                parsed.reject(origin, Some(line), &record.join(","), "empty url column")
+            }
+        }
+    }
+}
+
+/// 1から始まる行番号とフィールド
+pub(super) type Record = (usize, Vec<String>);
+
+/// `(行番号, フィールド)`のレコードに分割する, 空行は取り除く
+///
+/// `"`で囲まれたフィールドは区切り文字と改行を含められ, `""`は`"`を表す
+pub(super) fn split_records(
+    contents: &str,
+    delimiter: char,
+) -> Result<Vec<Record>, (usize, String)> {
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut quote_line = 1;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut record);
+                if !(record.len() == 1 && record[0].trim().is_empty()) {
+                    records.push((record_line, record));
+                }
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err((quote_line, "unterminated quoted field".into()));
+    }
+    record.push(field);
+    if !(record.len() == 1 && record[0].trim().is_empty()) {
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/// `json`, `yaml`の値から`url`を読み込む
+///
+/// - 配列: 各要素を`url`の文字列, あるいは以前のこのプログラムの出力の`data`の要素として読み込む
+/// - オブジェクト: このプログラムの出力として`data`, `unattempted_urls`, `malformed_urls`から読み込む
+fn parse_value(
+    value: &Value,
+    lines: &mut LineFinder,
+    origin: &str,
+    parsed: &mut ParsedContents,
+) {
+    match value {
+        Value::Array(entries) => {
+            for entry in entries {
+                match entry {
+                    // 以前は`data`の配列のみを出力していた
+                    Value::Object(_) => parse_output_item(entry, lines, origin, parsed),
+                    _ => parse_url_array(
+                        std::slice::from_ref(entry),
+                        lines,
+                        origin,
+                        parsed,
+                    ),
+                }
+            }
+        }
+        Value::Object(output) if output.contains_key("data") => {
+            if let Some(Value::Array(data)) = output.get("data") {
+                for item in data {
+                    parse_output_item(item, lines, origin, parsed);
+                }
+            }
+            if let Some(Value::Array(urls)) = output.get("unattempted_urls") {
+                parse_url_array(urls, lines, origin, parsed);
+            }
+            if let Some(Value::Array(malformed)) = output.get("malformed_urls") {
+                for item in malformed {
+                    match item.get("url") {
+                        Some(Value::String(url)) => {
+                            parsed.push_str(origin, lines.line_of(url), url)
+                        }
+                        _ => {
+                            reject_value(item, lines, origin, "expected `url`", parsed)
+                        }
+                    }
+                }
+            }
+        }
+        _ => reject_value(
+            value,
+            lines,
+            origin,
+            "expected an array of urls or an output of this program",
+            parsed,
+        ),
+    }
+}
+
+fn parse_url_array(
+    entries: &[Value],
+    lines: &mut LineFinder,
+    origin: &str,
+    parsed: &mut ParsedContents,
+) {
+    for entry in entries {
+        match entry {
+            Value::String(s) => parsed.push_str(origin, lines.line_of(s), s),
+            _ => reject_value(entry, lines, origin, "expected a string", parsed),
+        }
+    }
+}
+
+/// `data`の要素, 動画(`Video.id`), 再生リスト(`Playlist.playlist_data_itself.id`),
+/// 存在の確認結果(`url`)のいずれか
+fn parse_output_item(
+    item: &Value,
+    lines: &mut LineFinder,
+    origin: &str,
+    parsed: &mut ParsedContents,
+) {
+    let video_id = item.pointer("/Video/id").and_then(Value::as_str);
+    let playlist_id =
+        item.pointer("/Playlist/playlist_data_itself/id").and_then(Value::as_str);
+    let url = item.get("url").and_then(Value::as_str);
+
+    let (id, res): (&str, Result<Url, ParseError>) = match (video_id, playlist_id, url)
+    {
+        (Some(id), _, _) => (
+            id,
+            VideoId::new(id.into()).map(|id| UrlVideo::from(id).into()),
+        ),
+        (_, Some(id), _) => (
+            id,
+            PlaylistId::new(id.into()).map(|id| UrlPlaylist::from((None, id)).into()),
+        ),
+        (_, _, Some(url)) => (url, Url::new(url.into())),
+        _ => {
+            let reason = "no video id, playlist id or url (`id` may be excluded by `--output-fields`)";
+            return reject_value(item, lines, origin, reason, parsed);
+        }
+    };
+    let line = lines.line_of(id);
+    match res {
+        Ok(url) => parsed.urls.push(url),
+        Err(e) => parsed.reject(origin, line, id, e.to_string()),
+    }
+}
+
+fn reject_value(
+    value: &Value,
+    lines: &mut LineFinder,
+    origin: &str,
+    reason: &str,
+    parsed: &mut ParsedContents,
+) {
+    let entry = match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    };
+    let line = match value {
+        Value::String(s) => lines.line_of(s),
+        _ => None,
+    };
+    parsed.reject(origin, line, &entry, reason);
+}
+
+/// `json`と`yaml`は位置を保持しないので, 値が現れる行を文字列から探す
+///
+/// 前回見つけた位置より後ろから探し, 見つからなければ先頭から探す
+struct LineFinder<'a> {
+    contents: &'a str,
+    /// `json`の文字列はエスケープを解除して比較する
+    json: bool,
+    pos: usize,
+}
+
+impl<'a> LineFinder<'a> {
+    fn new(contents: &'a str, json: bool) -> Self {
+        Self {
+            contents,
+            json,
+            pos: 0,
+        }
+    }
+
+    fn line_of(&mut self, needle: &str) -> Option<usize> {
+        if needle.is_empty() {
+            return None;
+        }
+        let (start, end) =
+            self.find_from(self.pos, needle).or_else(|| self.find_from(0, needle))?;
+        self.pos = end;
+        Some(self.contents[..start].matches('\n').count() + 1)
+    }
+
+    /// `needle`が現れる範囲, `json`のときは文字列リテラルの範囲
+    fn find_from(&self, from: usize, needle: &str) -> Option<(usize, usize)> {
+        if !self.json {
+            let start = from + self.contents[from..].find(needle)?;
+            return Some((start, start + needle.len()));
+        }
+        let bytes = self.contents.as_bytes();
+        let mut i = from;
+        while let Some(offset) = self.contents[i..].find('"') {
+            let start = i + offset;
+            let mut j = start + 1;
+            loop {
+                match bytes.get(j)? {
+                    b'\\' => j += 2,
+                    b'"' => break,
+                    _ => j += 1,
+                }
+            }
+            let end = j + 1;
+            let literal = &self.contents[start..end];
+            if serde_json::from_str::<String>(literal).is_ok_and(|s| s == needle) {
+                return Some((start, end));
+            }
+            i = end;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V: &str = "https://youtu.be/12345678901";
+    const V_PL: &str = "https://www.youtube.com/watch?v=12345678901&list=1234567890123456789012345678901234";
+
+    fn parse(contents: &str, path: &str, options: &InputOptions) -> ParsedContents {
+        parse_contents(contents, path, Some(Path::new(path)), options)
+    }
+
+    #[test]
+    fn test_parse_lines_with_comments() {
+        let contents = format!(
+            "# urls\n\n{}  # first\n{},not-a-url\nhttps://youtu.be/12345678901#t=10\n",
+            V, V_PL
+        );
+        let parsed = parse(&contents, "urls.txt", &InputOptions::default());
+        assert_eq!(
+            parsed.urls,
+            vec![
+                Url::v_inc_from_1(),
+                Url::v_pl_inc_from_1(),
+                Url::v_inc_from_1()
+            ]
+        );
+        assert_eq!(parsed.invalid.len(), 1);
+        assert_eq!(parsed.invalid[0].line, Some(4));
+        assert_eq!(parsed.invalid[0].entry, "not-a-url");
+        assert!(parsed.invalid[0].to_string().starts_with("urls.txt:4: `not-a-url`: "));
+    }
+
+    #[test]
+    fn test_parse_bare_ids() {
+        let contents =
+            "12345678901\n1234567890123456789012345678901234\n@handle\nfoo\n";
+        let parsed = parse(contents, "ids.txt", &InputOptions::default());
+        assert_eq!(parsed.urls, vec![Url::v_inc_from_1(), Url::pl_inc_from_1()]);
+        // ハンドルは取得できないので無視する
+        assert_eq!(parsed.invalid.len(), 2);
+        assert!(parsed.invalid[0].reason.starts_with("not supported yet"));
+
+        let options = InputOptions {
+            strictness: IdStrictness::Strict,
+            ..Default::default()
+        };
+        let parsed = parse(contents, "ids.txt", &options);
+        assert!(parsed.invalid[1].reason.starts_with("ambiguous input"));
+    }
+
+    #[test]
+    fn test_parse_playlist_policy() {
+        let contents = format!("{V_PL}\nplaylist:{V_PL}\nvideo:{V_PL}\n");
+        let options = InputOptions {
+            playlist_policy: PlaylistPolicy::Both,
+            ..Default::default()
+        };
+        let parsed = parse(&contents, "urls.txt", &options);
+        // 接頭語のある行はその方針, 無い行は`options`の方針
+        assert_eq!(
+            parsed.urls,
+            vec![
+                Url::v_inc_from_1(),
+                Url::v_pl_inc_from_1(),
+                Url::v_pl_inc_from_1(),
+                Url::v_inc_from_1(),
+            ]
+        );
+        assert!(parsed.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv_and_tsv() {
+        let contents = format!(
+            "title,URL\n\"a, \"\"quoted\"\" title\",{}\n\nb,\nc,{}\n",
+            V, V_PL
+        );
+        let parsed = parse(&contents, "list.csv", &InputOptions::default());
+        assert_eq!(
+            parsed.urls,
+            vec![Url::v_inc_from_1(), Url::v_pl_inc_from_1()]
+        );
+        assert_eq!(parsed.invalid.len(), 1);
+        assert_eq!(parsed.invalid[0].line, Some(4));
+
+        let contents = format!("id\tlink\n1\t{}\n", V);
+        let options = InputOptions {
+            format: InputFormat::Tsv,
+            column: "2".parse().unwrap(),
+            ..Default::default()
+        };
+        let parsed = parse_contents(&contents, "-", None, &options);
+        assert_eq!(parsed.urls, vec![Url::v_inc_from_1()]);
+
+        // 指定した列が無いとき
+        let parsed = parse(&contents, "list.tsv", &InputOptions::default());
+        assert!(parsed.urls.is_empty());
+        assert_eq!(parsed.invalid[0].line, Some(1));
+        assert!(parsed.invalid[0].reason.contains("--input-column"));
+    }
+
+    #[test]
+    fn test_split_records() {
+        assert_eq!(
+            split_records("a,\"b\nc\"\r\nd,e", ','),
+            Ok(vec![
+                (1, vec!["a".to_string(), "b\nc".to_string()]),
+                (3, vec!["d".to_string(), "e".to_string()]),
+            ])
+        );
+        assert_eq!(
+            split_records("a\n\"b", ','),
+            Err((2, "unterminated quoted field".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_json_array() {
+        let contents = format!("[\n  \"{}\",\n  1,\n  \"{}\"\n]", V, V_PL);
+        let parsed = parse_contents(&contents, "-", None, &InputOptions::default());
+        assert_eq!(
+            parsed.urls,
+            vec![Url::v_inc_from_1(), Url::v_pl_inc_from_1()]
+        );
+        assert_eq!(parsed.invalid.len(), 1);
+        assert_eq!(parsed.invalid[0].reason, "expected a string");
+    }
+
+    #[test]
+    fn test_parse_repeated_invalid_lines() {
+        // 同じ無効な項目はそれぞれの行を, エスケープされた文字列も行を表示する
+        let contents = format!(
+            "[\n  \"not a url!\",\n  \"{}\",\n  \"not a url!\",\n  \"https:\\/\\/youtu.be\\/1234567890\"\n]",
+            V
+        );
+        let parsed = parse(&contents, "urls.json", &InputOptions::default());
+        assert_eq!(parsed.urls, vec![Url::v_inc_from_1()]);
+        let lines: Vec<_> = parsed.invalid.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![Some(2), Some(4), Some(5)]);
+
+        let contents = format!("- not a url!\n- {}\n- not a url!\n", V);
+        let parsed = parse(&contents, "urls.yaml", &InputOptions::default());
+        let lines: Vec<_> = parsed.invalid.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn test_parse_previous_output() {
+        let output = serde_json::json!({
+            "data": [
+                {"Video": {"id": "12345678901", "title": "a"}},
+                {"Playlist": {
+                    "videos": [],
+                    "playlist_data_itself": {"id": "1234567890123456789012345678901234", "total": 0}
+                }},
+                {"Video": {"title": "no id"}},
+                {"url": V_PL, "existence": "existing"}
+            ],
+            "interrupted": true,
+            "unattempted_urls": [V],
+            "malformed_urls": [{"url": "https://www.youtube.com/watch?v=22222222222", "reason": "x"}]
+        });
+        let expected = vec![
+            Url::v_inc_from_1(),
+            Url::pl_inc_from_1(),
+            Url::v_pl_inc_from_1(),
+            Url::v_inc_from_1(),
+            UrlVideo::from(VideoId::all_2()).into(),
+        ];
+
+        let json = serde_json::to_string_pretty(&output).unwrap();
+        let parsed = parse(&json, "out.json", &InputOptions::default());
+        assert_eq!(parsed.urls, expected);
+        assert_eq!(parsed.invalid.len(), 1);
+        assert!(parsed.invalid[0].reason.contains("--output-fields"));
+
+        let yaml = serde_yaml::to_string(&output).unwrap();
+        let parsed = parse(&yaml, "out.yaml", &InputOptions::default());
+        assert_eq!(parsed.urls, expected);
+        assert_eq!(parsed.invalid.len(), 1);
+
+        // 以前の`data`の配列のみの出力
+        let json = serde_json::to_string_pretty(&output["data"]).unwrap();
+        let parsed = parse(&json, "old.json", &InputOptions::default());
+        assert_eq!(parsed.urls, expected[..3]);
+        assert_eq!(parsed.invalid.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let contents =
+            format!("see [this]({})\n\n<a href=\"{}\">also</a> {}\n", V, V_PL, V);
+        let parsed = parse(&contents, "notes.md", &InputOptions::default());
+        assert_eq!(
+            parsed.urls,
+            vec![Url::v_inc_from_1(), Url::v_pl_inc_from_1()]
+        );
+        let lines: Vec<usize> = parsed.extracted.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert!(parsed.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_input_column_from_str() {
+        assert_eq!("url".parse(), Ok(InputColumn::Name("url".into())));
+        assert_eq!("3".parse(), Ok(InputColumn::Index(3)));
+        assert!("0".parse::<InputColumn>().is_err());
+        assert!("".parse::<InputColumn>().is_err());
+    }
+}
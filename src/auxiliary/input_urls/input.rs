@@ -1,69 +1,198 @@
+use rustyline::{error::ReadlineError, DefaultEditor};
 use std::{
     collections::{HashSet, VecDeque},
     fs,
-    io::{self, Write},
+    path::{Path, PathBuf},
 };
 
+use super::{
+    file_format::{parse_contents, InputFormat, InputOptions},
+    queue::UrlQueue,
+};
 use crate::url::Url;
 
+/// 入力履歴を保存するファイル名, ホームディレクトリに作成する
+const HISTORY_FILE_NAME: &str = ".fetch_yt_data_tools_history";
+
 /// urlを入力させる
 ///
+/// 矢印キーで行を編集でき, 入力履歴はホームディレクトリに保存する
+///
+/// `url`を省略したIDと`f:<path>`のファイルは`options`で読み込み,
+/// `x:<path>`のファイルは文章として含まれる`url`を全て探す
+///
 /// 戻り値の長さが0の時もある
-pub fn input() -> VecDeque<Url> {
-    println!("Input urls. Type `help`, show help.");
-    let mut urls: VecDeque<Url> = VecDeque::new();
+pub fn input(options: &InputOptions) -> VecDeque<Url> {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("Cannot start url input: {}", e);
+            return VecDeque::new();
+        }
+    };
+    let history = history_path();
+    if let Some(path) = &history {
+        // 初回は履歴ファイルが存在しないので失敗しても無視する
+        let _ = editor.load_history(path);
+    }
 
+    println!("Input urls. Type `help`, show help.");
+    let mut queue = UrlQueue::new();
     loop {
-        let mut buffer = String::new();
-        print!("  > ");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut buffer).unwrap();
-        if parse_input(buffer.trim(), &mut urls) {
-            // parse_inputで重複削除する場合もあるが,必ず呼び出されるようここで呼ぶ
-            remove_duplicates(&mut urls);
+        let (videos, playlists) = queue.counts();
+        let prompt = format!("  [videos: {}, playlists: {}] > ", videos, playlists);
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            // Ctrl-Cは入力中の行のみを取り消す
+            Err(ReadlineError::Interrupted) => continue,
+            // Ctrl-Dは`exit-f`と同じ
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("Cannot read input: {}", e);
+                break;
+            }
+        };
+        let line = line.trim();
+        if !line.is_empty() {
+            let _ = editor.add_history_entry(line);
+        }
+        if run_command(Command::parse(line), &mut queue, &mut editor, options) {
             break;
         }
     }
-    urls
+
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            tracing::warn!("failed to save input history: {}", e);
+        }
+    }
+    queue.into_urls()
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
+}
+
+/// 入力された1行
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    /// Enterのみを押したとき
+    Empty,
+    Help,
+    /// 確認してから入力を終了
+    Exit,
+    /// 確認せずに入力を終了
+    ExitForce,
+    /// ファイルからurlを取得
+    File(String),
+    /// 文章のファイルから含まれるurlを全て取得
+    Extract(String),
+    List,
+    /// 1から始まる番号のurlを取り除く
+    Remove(usize),
+    Clear,
+    Undo,
+    Count,
+    /// 入力したurlをファイルに保存
+    Save(String),
+    /// 引数が正しくないコマンド
+    Invalid(String),
+    Url(String),
+}
+
+impl Command {
+    fn parse(s: &str) -> Self {
+        let (name, arg) = match s.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s, ""),
+        };
+        let name = name.to_lowercase();
+
+        if s.is_empty() {
+            return Self::Empty;
+        }
+        if let Some(path) = s.strip_prefix("f:").or_else(|| s.strip_prefix("F:")) {
+            return Self::File(path.trim().into());
+        }
+        if let Some(path) = s.strip_prefix("x:").or_else(|| s.strip_prefix("X:")) {
+            return Self::Extract(path.trim().into());
+        }
+        match (name.as_str(), arg) {
+            ("help", "") => Self::Help,
+            ("exit", "") => Self::Exit,
+            ("exit-f", "") => Self::ExitForce,
+            ("list", "") => Self::List,
+            ("clear", "") => Self::Clear,
+            ("undo", "") => Self::Undo,
+            ("count", "") => Self::Count,
+            ("remove", n) => match n.parse() {
+                Ok(n) => Self::Remove(n),
+                Err(_) => Self::Invalid(format!(
+                    "invalid number:`{}`, usage: `remove <n>` (see `list`)",
+                    n
+                )),
+            },
+            ("save", "") => Self::Invalid("usage: `save <path/to/file>`".into()),
+            ("save", path) => Self::Save(path.into()),
+            _ => Self::Url(s.into()),
+        }
+    }
 }
 
 /// 戻り値:
 /// - `true`: urlの入力を終了する
 /// - `false`: urlの入力を終了しない
-fn parse_input(s: &str, urls: &mut VecDeque<Url>) -> bool {
-    let s_lowercase = s.to_lowercase();
-
-    // Enterのみを押したとき
-    if s.is_empty() {
-        return false;
-
-    // ヘルプメッセージを表示
-    } else if s_lowercase == "help" {
-        show_help_message();
-
-    // ファイルからurlを取得
-    } else if s.starts_with("f:") {
-        read_from_file(s.trim_start_matches("f:"), urls);
-
-    // ファイルからurlを取得
-    } else if s.starts_with("F:") {
-        read_from_file(s.trim_start_matches("F:"), urls);
-
-    // 入力を終了
-    } else if s_lowercase == "exit" {
-        remove_duplicates(urls);
-        return confirm_exit(urls);
-
-    // 入力を強制的に終了
-    } else if s_lowercase == "exit-f" {
-        return true;
-
-    // urlに入力をパース
-    } else {
-        match Url::new(s.to_string()) {
-            Ok(url) => urls.push_back(url),
-            Err(e) => println!("{}", e),
+fn run_command(
+    command: Command,
+    queue: &mut UrlQueue,
+    editor: &mut DefaultEditor,
+    options: &InputOptions,
+) -> bool {
+    match command {
+        Command::Empty => {}
+        Command::Help => show_help_message(),
+        Command::Exit => return confirm_exit(queue.urls(), editor),
+        Command::ExitForce => return true,
+        Command::File(path) => add_from_file(&path, options, queue),
+        Command::Extract(path) => {
+            let options = InputOptions {
+                format: InputFormat::Text,
+                ..options.clone()
+            };
+            add_from_file(&path, &options, queue)
+        }
+        Command::List => show_urls(queue.urls()),
+        Command::Remove(n) => match queue.remove(n) {
+            Some(url) => println!("removed:`{}`", url),
+            None => {
+                println!("no url at {}, there are {} url(s)", n, queue.urls().len())
+            }
+        },
+        Command::Clear => println!("{} url(s) are removed.", queue.clear()),
+        Command::Undo => {
+            if !queue.undo() {
+                println!("nothing to undo");
+            }
         }
+        Command::Count => {
+            let (videos, playlists) = queue.counts();
+            println!("videos: {}, playlists: {}", videos, playlists);
+        }
+        Command::Save(path) => match queue.save(Path::new(&path)) {
+            Ok(n) => println!("{} url(s) are saved to `{}`", n, path),
+            Err(e) => println!("Cannot save file:`{}`", e),
+        },
+        Command::Invalid(message) => println!("{}", message),
+        Command::Url(s) => match options.parse_entry(&s) {
+            Ok(urls) => {
+                if queue.extend(urls) == 0 {
+                    println!("already inputted");
+                }
+            }
+            Err(e) => println!("{}\n{}", e, e.annotate()),
+        },
     }
     false
 }
@@ -71,23 +200,61 @@ fn parse_input(s: &str, urls: &mut VecDeque<Url>) -> bool {
 fn show_help_message() {
     let message = r#"
 # input urls
-Please enter urls in stdin without abbreviation.
+Please enter urls, video ids (11 chars) or playlist ids (34 chars).
 ==== cmd ====
   - help            : show help message
   - exit            : exit url input(with confirm)
-  - exit-f          : exit url input(without confirm)
+  - exit-f          : exit url input(without confirm), or Ctrl-D
   - f:<path/to/file>: read urls from file
-    required csv file with url separated by commas
+    lines (`#` comments), csv/tsv (`url` column), json array or previous output
+  - x:<path/to/file>: find every youtube link in free text, markdown or html
+  - video:<url>     : fetch only the video of a `watch?v=...&list=...` url
+    (also `playlist:<url>` for the whole playlist, `both:<url>` for both)
+  - list            : show inputted urls with numbers
+  - remove <n>      : remove the n-th url
+  - clear           : remove all urls
+  - undo            : undo the last change
+  - count           : show the number of videos and playlists
+  - save <path>     : save inputted urls to file, one per line
 =============
 "#;
     println!("{}", message.trim());
 }
 
+fn show_urls(urls: &VecDeque<Url>) {
+    for (i, url) in urls.iter().enumerate() {
+        println!("{:>4}: {}", i + 1, url);
+    }
+}
+
 // ファイルからの読み込み関連 begin
-fn read_from_file(path: &str, urls: &mut VecDeque<Url>) {
+fn add_from_file(path: &str, options: &InputOptions, queue: &mut UrlQueue) {
+    if let Some(urls) = read_from_file(path, options) {
+        let parsed = urls.len();
+        let added = queue.extend(urls);
+        if added != parsed {
+            println!("{} url(s) are already inputted.", parsed - added);
+        }
+    }
+}
+
+fn read_from_file(path: &str, options: &InputOptions) -> Option<VecDeque<Url>> {
     match read_file_contents(path) {
-        Ok(contents) => parse_read_file_contents(contents, urls),
-        Err(e) => println!("{}", e),
+        Ok(contents) => {
+            let mut urls = VecDeque::new();
+            parse_read_file_contents(
+                &contents,
+                path,
+                Some(Path::new(path)),
+                options,
+                &mut urls,
+            );
+            Some(urls)
+        }
+        Err(e) => {
+            println!("{}", e);
+            None
+        }
     }
 }
 
@@ -95,46 +262,47 @@ fn read_file_contents(path: &str) -> Result<String, String> {
     fs::read_to_string(path).map_err(|e| format!("Cannot open file:`{}`", e))
 }
 
-fn parse_read_file_contents(contents: String, urls: &mut VecDeque<Url>) {
-    let mut added_urls_num: usize = 0;
-    let mut invalid_urls_num: usize = 0;
-
-    for c in contents
-        .split_whitespace()
-        .flat_map(|s| s.split(','))
-        .skip_while(|s| s.is_empty())
-    {
-        match Url::new(c.to_string()) {
-            Ok(url) => {
-                urls.push_back(url);
-                added_urls_num += 1;
-            }
-            Err(_) => invalid_urls_num += 1,
-        }
+/// 読み込めなかった入力はファイル, 行番号と理由を表示する
+///
+/// 文章から探した`url`は見つけた行とともに表示する
+///
+/// 戻り値は無視した項目の数
+pub(super) fn parse_read_file_contents(
+    contents: &str,
+    origin: &str,
+    path: Option<&Path>,
+    options: &InputOptions,
+    urls: &mut VecDeque<Url>,
+) -> usize {
+    let parsed = parse_contents(contents, origin, path, options);
+    for extracted in &parsed.extracted {
+        println!("Found {}:{}: {}", origin, extracted.line, extracted.url);
     }
-
-    if invalid_urls_num != 0 {
+    for invalid in &parsed.invalid {
+        eprintln!("Ignored {}", invalid);
+    }
+    if !parsed.invalid.is_empty() {
         println!(
             "{} url(s) are ignored due to invalid format.",
-            invalid_urls_num
+            parsed.invalid.len()
         );
     }
-    println!("{} url(s) are added.", added_urls_num);
+    println!("{} url(s) are added.", parsed.urls.len());
+    urls.extend(parsed.urls);
+    parsed.invalid.len()
 }
 // ファイルからの読み込み関連 end
 
-fn confirm_exit(urls: &mut VecDeque<Url>) -> bool {
+fn confirm_exit(urls: &VecDeque<Url>, editor: &mut DefaultEditor) -> bool {
     println!("Inputted urls are following:");
-    for url in urls {
-        println!(" > {}", url.build_url());
-    }
+    show_urls(urls);
 
     loop {
-        print!("Exit input urls? [y/n] ");
-        let mut buffer = String::new();
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut buffer).unwrap();
-        buffer = buffer.to_lowercase().trim().to_string();
+        let buffer = match editor.readline("Exit input urls? [y/n] ") {
+            Ok(buffer) => buffer.to_lowercase().trim().to_string(),
+            Err(ReadlineError::Interrupted) => return false,
+            Err(_) => return true,
+        };
 
         if buffer == "y" {
             return true;
@@ -146,7 +314,7 @@ fn confirm_exit(urls: &mut VecDeque<Url>) -> bool {
     }
 }
 
-fn remove_duplicates(urls: &mut VecDeque<Url>) {
+pub(super) fn remove_duplicates(urls: &mut VecDeque<Url>) {
     let mut seen = HashSet::new();
     urls.retain(|url| seen.insert(url.clone()));
 }
@@ -155,6 +323,31 @@ fn remove_duplicates(urls: &mut VecDeque<Url>) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_command_parse() {
+        assert_eq!(Command::parse(""), Command::Empty);
+        assert_eq!(Command::parse("EXIT"), Command::Exit);
+        assert_eq!(Command::parse("remove 2"), Command::Remove(2));
+        assert!(matches!(Command::parse("remove x"), Command::Invalid(_)));
+        assert!(matches!(Command::parse("save"), Command::Invalid(_)));
+        assert_eq!(
+            Command::parse("save ./urls.txt"),
+            Command::Save("./urls.txt".into())
+        );
+        assert_eq!(
+            Command::parse("f: ./urls.csv"),
+            Command::File("./urls.csv".into())
+        );
+        assert_eq!(
+            Command::parse("x:./chat.html"),
+            Command::Extract("./chat.html".into())
+        );
+        assert_eq!(
+            Command::parse("https://youtu.be/12345678901"),
+            Command::Url("https://youtu.be/12345678901".into())
+        );
+    }
+
     #[test]
     fn test_input_urls_remove_duplicates() {
         let mut input_urls: VecDeque<Url> = vec![
@@ -181,7 +374,13 @@ https://youtu.be/12345678901,https://www.youtube.com/watch?v=12345678901&list=12
         "#
         .to_string();
         let mut urls: VecDeque<Url> = VecDeque::new();
-        parse_read_file_contents(contents, &mut urls);
+        parse_read_file_contents(
+            &contents,
+            "-",
+            None,
+            &InputOptions::default(),
+            &mut urls,
+        );
         assert_eq!(urls, expected_urls);
     }
 
@@ -197,7 +396,13 @@ https://youtu.be/12345678901,https://www.youtube.com/watch?v=12345678901&list=12
             );
 
         let mut urls: VecDeque<Url> = VecDeque::new();
-        parse_read_file_contents(contents, &mut urls);
+        parse_read_file_contents(
+            &contents,
+            "-",
+            None,
+            &InputOptions::default(),
+            &mut urls,
+        );
         assert_eq!(urls, expected_urls);
     }
 }
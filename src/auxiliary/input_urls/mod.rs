@@ -1,3 +1,13 @@
+mod file_format;
 mod input;
+mod queue;
+mod source;
+mod takeout;
 
+pub use file_format::{InputColumn, InputFormat, InputOptions, InvalidEntry};
 pub use input::input;
+pub use source::{load, LoadedUrls, UrlSource};
+pub use takeout::{
+    import_takeout, load_takeout, TakeoutContext, TakeoutFile, TakeoutImport,
+    TakeoutTime,
+};
@@ -0,0 +1,183 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fs, io,
+    path::Path,
+};
+
+use crate::url::Url;
+
+/// `UrlQueue`への変更, 取り消すのに必要な分のみ保持する
+#[derive(Debug)]
+enum Change {
+    /// 末尾に追加した`url`の数
+    Extended(usize),
+    /// 取り除いた位置と`url`
+    Removed(usize, Url),
+    /// 取り除いた全ての`url`
+    Cleared(VecDeque<Url>),
+}
+
+/// 対話的な入力で追加された`url`, 重複は追加しない
+///
+/// 変更の履歴を保持し, `undo`で元に戻せる
+#[derive(Debug, Default)]
+pub(super) struct UrlQueue {
+    urls: VecDeque<Url>,
+    /// `urls`に含まれる`url`, 重複の確認に使用する
+    seen: HashSet<Url>,
+    history: Vec<Change>,
+}
+
+impl UrlQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn urls(&self) -> &VecDeque<Url> {
+        &self.urls
+    }
+
+    pub fn into_urls(self) -> VecDeque<Url> {
+        self.urls
+    }
+
+    /// `(動画の数, 再生リストの数)`
+    pub fn counts(&self) -> (usize, usize) {
+        let videos =
+            self.urls.iter().filter(|url| matches!(url, Url::Video(_))).count();
+        (videos, self.urls.len() - videos)
+    }
+
+    /// 追加した`url`の数を返す, 既に追加されている`url`は追加しない
+    ///
+    /// 一度の`undo`でまとめて取り消せる
+    pub fn extend(&mut self, urls: impl IntoIterator<Item = Url>) -> usize {
+        let before = self.urls.len();
+        for url in urls {
+            if self.seen.insert(url.clone()) {
+                self.urls.push_back(url);
+            }
+        }
+        let added = self.urls.len() - before;
+        if added != 0 {
+            self.history.push(Change::Extended(added));
+        }
+        added
+    }
+
+    /// 1から始まる`n`番目の`url`を取り除く
+    pub fn remove(&mut self, n: usize) -> Option<Url> {
+        let index = n.checked_sub(1).filter(|&i| i < self.urls.len())?;
+        let url = self.urls.remove(index)?;
+        self.seen.remove(&url);
+        self.history.push(Change::Removed(index, url.clone()));
+        Some(url)
+    }
+
+    /// 取り除いた`url`の数を返す
+    pub fn clear(&mut self) -> usize {
+        let len = self.urls.len();
+        if len != 0 {
+            self.seen.clear();
+            self.history.push(Change::Cleared(std::mem::take(&mut self.urls)));
+        }
+        len
+    }
+
+    /// 直前の変更を取り消す, 取り消す変更が無いときは`false`
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(Change::Extended(added)) => {
+                let at = self.urls.len() - added;
+                for url in self.urls.drain(at..) {
+                    self.seen.remove(&url);
+                }
+            }
+            Some(Change::Removed(index, url)) => {
+                self.seen.insert(url.clone());
+                self.urls.insert(index, url);
+            }
+            Some(Change::Cleared(urls)) => {
+                self.seen = urls.iter().cloned().collect();
+                self.urls = urls;
+            }
+            None => return false,
+        }
+        true
+    }
+
+    /// 1行に1つの`url`を書き込む, 書き込んだ`url`の数を返す
+    ///
+    /// 書き込んだファイルは`f:<path>`や`--input`で再度読み込める
+    pub fn save(&self, path: &Path) -> io::Result<usize> {
+        let contents: String =
+            self.urls.iter().map(|url| format!("{}\n", url)).collect();
+        fs::write(path, contents)?;
+        Ok(self.urls.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_queue_edit_and_undo() {
+        let mut queue = UrlQueue::new();
+        assert_eq!(queue.extend([Url::v_inc_from_1(), Url::pl_inc_from_1()]), 2);
+        // 重複は追加しない
+        assert_eq!(queue.extend([Url::v_inc_from_1()]), 0);
+        assert_eq!(queue.extend([Url::v_pl_inc_from_1()]), 1);
+        assert_eq!(queue.counts(), (1, 2));
+
+        assert_eq!(queue.remove(0), None);
+        assert_eq!(queue.remove(4), None);
+        assert_eq!(queue.remove(2), Some(Url::pl_inc_from_1()));
+        assert_eq!(queue.clear(), 2);
+        assert!(queue.urls().is_empty());
+
+        assert!(queue.undo());
+        assert_eq!(
+            queue.urls(),
+            &VecDeque::from(vec![Url::v_inc_from_1(), Url::v_pl_inc_from_1()])
+        );
+        assert!(queue.undo());
+        assert_eq!(queue.urls().len(), 3);
+        assert_eq!(
+            queue.urls(),
+            &VecDeque::from(vec![
+                Url::v_inc_from_1(),
+                Url::pl_inc_from_1(),
+                Url::v_pl_inc_from_1()
+            ])
+        );
+        assert!(queue.undo());
+        assert!(queue.undo());
+        assert!(queue.urls().is_empty());
+        assert!(!queue.undo());
+
+        // 取り消した`url`は再び追加できる
+        assert_eq!(queue.extend([Url::v_inc_from_1()]), 1);
+    }
+
+    #[test]
+    fn test_url_queue_save() {
+        let path = std::env::temp_dir().join(format!(
+            "fetch_yt_data_tools_test_queue_save_{}.txt",
+            std::process::id()
+        ));
+        let mut queue = UrlQueue::new();
+        queue.extend([Url::v_inc_from_1(), Url::pl_inc_from_1()]);
+        assert_eq!(queue.save(&path).unwrap(), 2);
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(
+            contents,
+            format!(
+                "{}\n{}\n",
+                Url::v_inc_from_1().build_url(),
+                Url::pl_inc_from_1().build_url()
+            )
+        );
+    }
+}
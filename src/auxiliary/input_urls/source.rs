@@ -0,0 +1,225 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, IsTerminal, Read},
+    path::PathBuf,
+};
+
+use super::{
+    file_format::{InputFormat, InputOptions},
+    input::{input, parse_read_file_contents, remove_duplicates},
+};
+use crate::url::Url;
+
+/// 対話せずに`url`を読み込む入力元
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlSource {
+    /// コマンドライン引数で直接指定された`url`
+    Url(String),
+    /// `url`を記述したファイル
+    File(PathBuf),
+    /// 文章から`url`を全て探すファイル, `-`は標準入力
+    Extract(PathBuf),
+    /// 標準入力
+    Stdin,
+}
+
+impl UrlSource {
+    /// `-`は標準入力
+    pub fn from_arg(arg: String) -> Self {
+        if arg == "-" {
+            Self::Stdin
+        } else {
+            Self::Url(arg)
+        }
+    }
+
+    /// `-`は標準入力
+    pub fn from_path(path: PathBuf) -> Self {
+        if path.as_os_str() == "-" {
+            Self::Stdin
+        } else {
+            Self::File(path)
+        }
+    }
+}
+
+/// `load`で読み込んだ`url`と, 読み込めなかったもの
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LoadedUrls {
+    pub urls: VecDeque<Url>,
+    /// ファイルと標準入力のうち, 無効な形式のため無視した項目の数
+    pub ignored: usize,
+    /// 無効な`url`や開けなかったファイルなど, 読み込めなかった入力元とその理由
+    pub failed: Vec<(String, String)>,
+}
+
+impl From<VecDeque<Url>> for LoadedUrls {
+    fn from(urls: VecDeque<Url>) -> Self {
+        Self {
+            urls,
+            ..Default::default()
+        }
+    }
+}
+
+/// `sources`から`url`を読み込む, 重複は取り除く
+///
+/// `sources`が空のとき, 標準入力が端末でなければ標準入力から読み込み,
+/// 端末であれば`prompt`のときのみ対話的に入力させる(`input`)
+///
+/// 端末の標準入力はEOFまで待ち続けるため, `-`で明示されたときのみ読み込む
+///
+/// ファイルと標準入力は`options`の形式で, `Extract`は文章(`InputFormat::Text`)として読み込む
+///
+/// 読み込めなかった入力元は表示して`failed`に含め, 残りの入力元は読み込む
+///
+/// `urls`の長さが0の時もある
+pub fn load(sources: &[UrlSource], options: &InputOptions, prompt: bool) -> LoadedUrls {
+    if sources.is_empty() {
+        if !io::stdin().is_terminal() {
+            return load(&[UrlSource::Stdin], options, prompt);
+        }
+        if prompt {
+            return input(options).into();
+        }
+        eprintln!(
+            "No url source is given, pass urls, `--input <FILE>` or `-` to read standard input."
+        );
+        return LoadedUrls::default();
+    }
+
+    let mut loaded = LoadedUrls::default();
+    let urls = &mut loaded.urls;
+    let mut stdin_read = false;
+    let text_options = InputOptions {
+        format: InputFormat::Text,
+        ..options.clone()
+    };
+    for source in sources {
+        let (path, options) = match source {
+            UrlSource::Url(s) => {
+                match options.parse_entry(s) {
+                    Ok(parsed) => urls.extend(parsed),
+                    Err(e) => {
+                        eprintln!("Ignored `{}`: {}", s, e);
+                        loaded.failed.push((s.clone(), e.to_string()));
+                    }
+                }
+                continue;
+            }
+            UrlSource::File(path) => (Some(path), options),
+            UrlSource::Extract(path) if path.as_os_str() == "-" => {
+                (None, &text_options)
+            }
+            UrlSource::Extract(path) => (Some(path), &text_options),
+            UrlSource::Stdin => (None, options),
+        };
+        match path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => {
+                    loaded.ignored += parse_read_file_contents(
+                        &contents,
+                        &path.display().to_string(),
+                        Some(path),
+                        options,
+                        urls,
+                    )
+                }
+                Err(e) => {
+                    eprintln!("Cannot open file `{}`: {}", path.display(), e);
+                    loaded.failed.push((path.display().to_string(), e.to_string()));
+                }
+            },
+            // 標準入力は一度しか読み込めない
+            None if stdin_read => {
+                eprintln!("Ignored `-`: standard input is already read.");
+            }
+            None => {
+                stdin_read = true;
+                let mut contents = String::new();
+                match io::stdin().read_to_string(&mut contents) {
+                    Ok(_) => {
+                        loaded.ignored += parse_read_file_contents(
+                            &contents, "-", None, options, urls,
+                        )
+                    }
+                    Err(e) => {
+                        eprintln!("Cannot read standard input: {}", e);
+                        loaded.failed.push(("-".to_string(), e.to_string()));
+                    }
+                }
+            }
+        }
+    }
+    remove_duplicates(urls);
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_source_from_arg() {
+        assert_eq!(UrlSource::from_arg("-".into()), UrlSource::Stdin);
+        assert_eq!(UrlSource::from_path(PathBuf::from("-")), UrlSource::Stdin);
+        assert_eq!(
+            UrlSource::from_path(PathBuf::from("urls.txt")),
+            UrlSource::File(PathBuf::from("urls.txt"))
+        );
+    }
+
+    #[test]
+    fn test_load_urls_extract() {
+        let path = std::env::temp_dir().join(format!(
+            "fetch_yt_data_tools_test_extract_{}.txt",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            "[a](https://youtu.be/12345678901) and https://youtu.be/12345678901\n",
+        )
+        .unwrap();
+
+        // 拡張子によらず文章として読み込む
+        let sources = vec![
+            UrlSource::Extract(path.clone()),
+            UrlSource::from_arg("https://www.youtube.com/watch?v=12345678901".into()),
+        ];
+        let loaded = load(&sources, &InputOptions::default(), false);
+        fs::remove_file(path).unwrap();
+        assert_eq!(loaded, VecDeque::from(vec![Url::v_inc_from_1()]).into());
+    }
+
+    #[test]
+    fn test_load_urls_from_args_and_file() {
+        let path = std::env::temp_dir().join(format!(
+            "fetch_yt_data_tools_test_load_urls_{}.txt",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            "https://youtu.be/12345678901\nhttps://www.youtube.com/watch?v=12345678901&list=1234567890123456789012345678901234\n",
+        )
+        .unwrap();
+
+        let sources = vec![
+            UrlSource::from_arg("https://www.youtube.com/watch?v=12345678901".into()),
+            UrlSource::from_arg("not a url".into()),
+            UrlSource::from_path(path.clone()),
+            UrlSource::from_path(PathBuf::from("does/not/exist.txt")),
+        ];
+        let loaded = load(&sources, &InputOptions::default(), false);
+        fs::remove_file(path).unwrap();
+
+        // 無効な`url`と読み込めないファイルは残りを読み込んだ上で`failed`に含め, 重複は取り除く
+        assert_eq!(
+            loaded.urls,
+            VecDeque::from(vec![Url::v_inc_from_1(), Url::v_pl_inc_from_1()])
+        );
+        let failed: Vec<&str> = loaded.failed.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(failed, vec!["not a url", "does/not/exist.txt"]);
+        assert_eq!(loaded.ignored, 0);
+    }
+}
@@ -0,0 +1,490 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use super::file_format::{split_records, InvalidEntry};
+use crate::{
+    id::VideoId,
+    url::{Url, UrlVideo},
+};
+
+/// 視聴履歴の`Google広告`の項目, 視聴した動画ではないので読み込まない
+const ADS_MARKER: &str = "From Google Ads";
+
+/// Google Takeoutのファイルの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TakeoutFile {
+    /// `playlists/<name>-videos.csv`など, 再生リストの動画のIDと追加した日時
+    PlaylistCsv,
+    /// `history/watch-history.json`
+    WatchHistoryJson,
+    /// `history/watch-history.html`
+    WatchHistoryHtml,
+}
+
+impl TakeoutFile {
+    /// 拡張子から, 分からなければ内容から判定する
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        let ext =
+            path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase());
+        match ext.as_deref() {
+            Some("csv") => Self::PlaylistCsv,
+            Some("json") => Self::WatchHistoryJson,
+            Some("html" | "htm") => Self::WatchHistoryHtml,
+            _ if contents.trim_start().starts_with('[') => Self::WatchHistoryJson,
+            _ if contents.contains("content-cell") => Self::WatchHistoryHtml,
+            _ => Self::PlaylistCsv,
+        }
+    }
+}
+
+/// Takeoutの各項目の日時, 読み取れないときは`None`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TakeoutTime {
+    /// 再生リストに追加した日時
+    AddedAt(Option<DateTime<Utc>>),
+    /// 視聴した日時
+    WatchedAt(Option<DateTime<Utc>>),
+}
+
+/// Takeoutから読み込んだ`url`の文脈
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TakeoutContext {
+    /// 読み込んだファイル, 再生リストのファイル名はその名前を含む
+    pub source: String,
+    #[serde(flatten)]
+    pub time: TakeoutTime,
+}
+
+/// Takeoutのファイルを読み込んだ結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TakeoutImport {
+    /// ファイルに記述された順, 同じ動画を複数回視聴したときはそれぞれ含める
+    pub entries: Vec<(Url, TakeoutContext)>,
+    pub invalid: Vec<InvalidEntry>,
+}
+
+impl TakeoutImport {
+    fn push(&mut self, url: Url, source: &str, time: TakeoutTime) {
+        let context = TakeoutContext {
+            source: source.into(),
+            time,
+        };
+        self.entries.push((url, context));
+    }
+
+    fn reject(
+        &mut self,
+        origin: &str,
+        line: Option<usize>,
+        entry: &str,
+        reason: impl Into<String>,
+    ) {
+        self.invalid.push(InvalidEntry {
+            origin: origin.into(),
+            line,
+            entry: entry.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+/// `contents`を`kind`の形式で読み込む, `origin`は出力の`source`とエラーの表示に使用する
+pub fn import_takeout(
+    contents: &str,
+    origin: &str,
+    kind: TakeoutFile,
+) -> TakeoutImport {
+    let mut import = TakeoutImport::default();
+    match kind {
+        TakeoutFile::PlaylistCsv => import_playlist_csv(contents, origin, &mut import),
+        TakeoutFile::WatchHistoryJson => {
+            import_history_json(contents, origin, &mut import)
+        }
+        TakeoutFile::WatchHistoryHtml => {
+            import_history_html(contents, origin, &mut import)
+        }
+    }
+    import
+}
+
+/// `paths`のファイルを順に読み込む, `-`は標準入力
+///
+/// 読み込めなかった項目はファイル, 行番号と理由を表示する
+pub fn load_takeout(paths: &[PathBuf]) -> Vec<(Url, TakeoutContext)> {
+    let mut entries = Vec::new();
+    for path in paths {
+        let contents = if path.as_os_str() == "-" {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents).map(|_| contents)
+        } else {
+            fs::read_to_string(path)
+        };
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Cannot open file `{}`: {}", path.display(), e);
+                continue;
+            }
+        };
+        let origin = path.display().to_string();
+        let import =
+            import_takeout(&contents, &origin, TakeoutFile::detect(path, &contents));
+        for invalid in &import.invalid {
+            eprintln!("Ignored {}", invalid);
+        }
+        println!(
+            "{} entries are added from `{}`.",
+            import.entries.len(),
+            origin
+        );
+        entries.extend(import.entries);
+    }
+    entries
+}
+
+/// 以下の2つの形式に対応する
+///
+/// - `Video ID,Playlist Video Creation Timestamp`: 日時はRFC 3339
+/// - `Video Id,Time Added`: 以前の形式, 再生リスト自体の情報の後に続き, 日時は`2019-01-01 00:00:00 UTC`
+fn import_playlist_csv(contents: &str, origin: &str, import: &mut TakeoutImport) {
+    let records = match split_records(contents, ',') {
+        Ok(records) => records,
+        Err((line, reason)) => return import.reject(origin, Some(line), "", reason),
+    };
+    let is_video_column = |h: &String| h.trim().eq_ignore_ascii_case("video id");
+    let Some(header_pos) =
+        records.iter().position(|(_, record)| record.iter().any(is_video_column))
+    else {
+        let reason = "no `Video ID` column, expected a playlist csv of google takeout";
+        return import.reject(origin, None, "", reason);
+    };
+    let header = &records[header_pos].1;
+    let video_col = header.iter().position(is_video_column).unwrap();
+    let time_col = header.iter().position(|h| {
+        let h = h.to_lowercase();
+        h.contains("timestamp") || h.contains("time added")
+    });
+
+    for (line, record) in &records[header_pos + 1..] {
+        let Some(id) = record.get(video_col).map(|id| id.trim()) else {
+            import.reject(origin, Some(*line), &record.join(","), "no video id");
+            continue;
+        };
+        let added_at = time_col
+            .and_then(|col| record.get(col))
+            .and_then(|time| parse_csv_time(time.trim()));
+        match VideoId::new(id.into()) {
+            Ok(id) => import.push(
+                UrlVideo::from(id).into(),
+                origin,
+                TakeoutTime::AddedAt(added_at),
+            ),
+            Err(e) => import.reject(origin, Some(*line), id, e.to_string()),
+        }
+    }
+}
+
+fn parse_csv_time(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = s.strip_suffix(" UTC")?;
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// 各項目の`titleUrl`を`url`, `time`を視聴した日時とする
+///
+/// `titleUrl`の無い削除された動画と広告は読み込まない
+fn import_history_json(contents: &str, origin: &str, import: &mut TakeoutImport) {
+    let items = match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Array(items)) => items,
+        Ok(_) => {
+            let reason = "expected an array of watch history items of google takeout";
+            return import.reject(origin, None, "", reason);
+        }
+        Err(e) => {
+            let reason = format!("invalid json: {}", e);
+            return import.reject(origin, Some(e.line()), "", reason);
+        }
+    };
+    for item in &items {
+        let is_ad = item["details"].as_array().is_some_and(|details| {
+            details.iter().any(|detail| detail["name"] == ADS_MARKER)
+        });
+        if is_ad {
+            continue;
+        }
+        let Some(url) = item["titleUrl"].as_str() else {
+            continue;
+        };
+        let watched_at = item["time"]
+            .as_str()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc));
+        match Url::new(url.into()) {
+            Ok(parsed) => {
+                import.push(parsed, origin, TakeoutTime::WatchedAt(watched_at))
+            }
+            Err(e) => import.reject(origin, None, url, e.to_string()),
+        }
+    }
+}
+
+/// `outer-cell`ごとに, 最初の`content-cell`の`url`とその後の日時を読み込む
+///
+/// 日時は言語の設定によって形式が異なるので, 読み取れないときは`None`とする
+fn import_history_html(contents: &str, origin: &str, import: &mut TakeoutImport) {
+    for cell in contents.split("<div class=\"outer-cell").skip(1) {
+        if cell.contains(ADS_MARKER) {
+            continue;
+        }
+        let Some(body) = content_cell(cell) else {
+            continue;
+        };
+        let Some(url) = Url::extract(body).into_iter().next() else {
+            continue;
+        };
+        let watched_at = body
+            .split("<br>")
+            .map(html_text)
+            .filter(|text| !text.is_empty())
+            .last()
+            .and_then(|text| parse_html_time(&text));
+        import.push(url.url, origin, TakeoutTime::WatchedAt(watched_at));
+    }
+}
+
+/// 最初の`content-cell`の`div`の中身
+fn content_cell(cell: &str) -> Option<&str> {
+    let start = cell.find("content-cell")?;
+    let start = start + cell[start..].find('>')? + 1;
+    let end = start + cell[start..].find("</div>")?;
+    Some(&cell[start..end])
+}
+
+/// タグを取り除き, 空白文字をまとめる
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .split(|c: char| c.is_whitespace() || c == '\u{202f}')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 日時の形式, 末尾のタイムゾーンの略称は取り除いてから解析する
+const HTML_TIME_FORMATS: [&str; 4] = [
+    // Jan 1, 2023, 12:34:56 PM
+    "%b %d, %Y, %I:%M:%S %p",
+    // 1 Jan 2023, 12:34:56
+    "%d %b %Y, %H:%M:%S",
+    // 2023/01/01 12:34:56
+    "%Y/%m/%d %H:%M:%S",
+    // 2023-01-01 12:34:56
+    "%Y-%m-%d %H:%M:%S",
+];
+
+/// タイムゾーンの略称と協定世界時との差(分)
+const TIME_ZONES: [(&str, i32); 18] = [
+    ("UTC", 0),
+    ("GMT", 0),
+    ("JST", 9 * 60),
+    ("KST", 9 * 60),
+    ("IST", 5 * 60 + 30),
+    ("CET", 60),
+    ("CEST", 2 * 60),
+    ("BST", 60),
+    ("EET", 2 * 60),
+    ("EEST", 3 * 60),
+    ("EST", -5 * 60),
+    ("EDT", -4 * 60),
+    ("CST", -6 * 60),
+    ("CDT", -5 * 60),
+    ("MST", -7 * 60),
+    ("MDT", -6 * 60),
+    ("PST", -8 * 60),
+    ("PDT", -7 * 60),
+];
+
+fn parse_html_time(text: &str) -> Option<DateTime<Utc>> {
+    let (naive, zone) = text.rsplit_once(' ')?;
+    let offset = TIME_ZONES
+        .iter()
+        .find(|(name, _)| *name == zone)
+        .and_then(|(_, minutes)| FixedOffset::east_opt(minutes * 60))?;
+    let naive = HTML_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(naive, format).ok())?;
+    offset.from_local_datetime(&naive).single().map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> Option<DateTime<Utc>> {
+        Some(DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc))
+    }
+
+    fn video(id: &str) -> Url {
+        UrlVideo::from(VideoId::new(id.into()).unwrap()).into()
+    }
+
+    fn times(import: &TakeoutImport) -> Vec<TakeoutTime> {
+        import.entries.iter().map(|(_, c)| c.time.clone()).collect()
+    }
+
+    #[test]
+    fn test_takeout_file_detect() {
+        let detect =
+            |path: &str, contents: &str| TakeoutFile::detect(Path::new(path), contents);
+        assert_eq!(detect("a-videos.csv", ""), TakeoutFile::PlaylistCsv);
+        assert_eq!(
+            detect("watch-history.json", ""),
+            TakeoutFile::WatchHistoryJson
+        );
+        assert_eq!(
+            detect("watch-history.html", ""),
+            TakeoutFile::WatchHistoryHtml
+        );
+        assert_eq!(detect("-", " [{}]"), TakeoutFile::WatchHistoryJson);
+    }
+
+    #[test]
+    fn test_import_playlist_csv() {
+        let contents = "Video ID,Playlist Video Creation Timestamp\n\
+                        12345678901,2023-01-02T03:04:05+00:00\n\
+                        123,2023-01-02T03:04:05+00:00\n\
+                        22222222222,not a time\n";
+        let import =
+            import_takeout(contents, "music-videos.csv", TakeoutFile::PlaylistCsv);
+        let urls: Vec<&Url> = import.entries.iter().map(|(url, _)| url).collect();
+        assert_eq!(urls, vec![&Url::v_inc_from_1(), &video("22222222222")]);
+        assert_eq!(
+            times(&import),
+            vec![
+                TakeoutTime::AddedAt(time("2023-01-02T03:04:05Z")),
+                TakeoutTime::AddedAt(None)
+            ]
+        );
+        assert_eq!(import.entries[0].1.source, "music-videos.csv");
+        assert_eq!(import.invalid.len(), 1);
+        assert_eq!(import.invalid[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_import_legacy_playlist_csv() {
+        let contents = "Playlist Id,Channel Id,Time Created,Title\n\
+                        PLxxxx,UCxxxx,2019-01-01 00:00:00 UTC,music\n\
+                        \n\
+                        Video Id,Time Added\n\
+                        12345678901 ,2019-01-02 03:04:05 UTC\n";
+        let import = import_takeout(contents, "music.csv", TakeoutFile::PlaylistCsv);
+        assert_eq!(import.entries[0].0, Url::v_inc_from_1());
+        assert_eq!(
+            times(&import),
+            vec![TakeoutTime::AddedAt(time("2019-01-02T03:04:05Z"))]
+        );
+        assert!(import.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_import_history_json() {
+        let contents = r#"[
+          {"header": "YouTube", "title": "Watched a", "titleUrl": "https://www.youtube.com/watch?v=12345678901", "time": "2024-05-06T07:08:09.123Z"},
+          {"header": "YouTube", "title": "Watched a video that has been removed", "time": "2024-05-05T00:00:00Z"},
+          {"header": "YouTube", "title": "Watched ad", "titleUrl": "https://www.youtube.com/watch?v=22222222222", "time": "2024-05-04T00:00:00Z", "details": [{"name": "From Google Ads"}]},
+          {"header": "YouTube", "title": "Watched a", "titleUrl": "https://www.youtube.com/watch?v=12345678901", "time": "2024-05-03T00:00:00Z"}
+        ]"#;
+        let import = import_takeout(
+            contents,
+            "watch-history.json",
+            TakeoutFile::WatchHistoryJson,
+        );
+        assert_eq!(import.entries.len(), 2);
+        assert!(import.entries.iter().all(|(url, _)| *url == Url::v_inc_from_1()));
+        assert_eq!(
+            times(&import),
+            vec![
+                TakeoutTime::WatchedAt(time("2024-05-06T07:08:09.123Z")),
+                TakeoutTime::WatchedAt(time("2024-05-03T00:00:00Z"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_history_html() {
+        let cell = |url: &str, time: &str, extra: &str| {
+            format!(
+                "<div class=\"outer-cell mdl-cell\"><div class=\"mdl-grid\">\
+                 <div class=\"header-cell\"><p>YouTube<br></p></div>\
+                 <div class=\"content-cell mdl-cell--6-col\">Watched&nbsp;<a href=\"{}\">title</a><br>\
+                 <a href=\"https://www.youtube.com/channel/UCxxxx\">channel</a><br>{}<br></div>\
+                 <div class=\"content-cell\"><b>Products:</b><br>YouTube<br>{}</div></div></div>",
+                url, time, extra
+            )
+        };
+        let contents = format!(
+            "<html><body>{}{}{}</body></html>",
+            cell(
+                "https://www.youtube.com/watch?v=12345678901",
+                "Jan 2, 2024, 3:04:05\u{202f}PM JST",
+                ""
+            ),
+            cell(
+                "https://www.youtube.com/watch?v=22222222222",
+                "Jan 1, 2024, 1:00:00 AM EST",
+                ADS_MARKER
+            ),
+            cell(
+                "https://www.youtube.com/watch?v=22222222222",
+                "2024/01/01 1:00:00 XYZ",
+                ""
+            ),
+        );
+        let import = import_takeout(
+            &contents,
+            "watch-history.html",
+            TakeoutFile::WatchHistoryHtml,
+        );
+        let urls: Vec<&Url> = import.entries.iter().map(|(url, _)| url).collect();
+        assert_eq!(urls, vec![&Url::v_inc_from_1(), &video("22222222222")]);
+        assert_eq!(
+            times(&import),
+            vec![
+                TakeoutTime::WatchedAt(time("2024-01-02T15:04:05+09:00")),
+                TakeoutTime::WatchedAt(None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_takeout_context_serialize() {
+        let context = TakeoutContext {
+            source: "a.csv".into(),
+            time: TakeoutTime::AddedAt(time("2023-01-02T03:04:05Z")),
+        };
+        assert_eq!(
+            serde_json::to_value(context).unwrap(),
+            serde_json::json!({"source": "a.csv", "added_at": "2023-01-02T03:04:05Z"})
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod input_urls;
+pub mod progress;
 pub mod settings_cfg;
@@ -0,0 +1,3 @@
+mod progress_line;
+
+pub use progress_line::ProgressLine;
@@ -0,0 +1,176 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::infrastructure::fetch::{FetchEvent, FetchObserver};
+
+/// 取得処理の進捗を標準エラー出力の1行に表示し続ける
+///
+/// 標準エラー出力が端末でないときは何も表示しない
+#[derive(Debug)]
+pub struct ProgressLine {
+    state: Mutex<State>,
+    enabled: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    started_at: Option<Instant>,
+    /// 取得する`url`の数
+    total: usize,
+    /// 取得し終えた`url`の数
+    done: usize,
+    used_quota: usize,
+    retries: usize,
+    /// レスポンスに含まれていた想定していない値の数
+    schema_drift: usize,
+    /// 前回表示した文字数, 短い行で上書きしたときに残らないように使用
+    last_width: usize,
+}
+
+impl ProgressLine {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            enabled: io::stderr().is_terminal(),
+        }
+    }
+
+    /// レスポンスに含まれていた想定していない値の数
+    pub fn schema_drift(&self) -> usize {
+        self.state.lock().unwrap().schema_drift
+    }
+
+    /// 消費したquotaの合計
+    pub fn used_quota(&self) -> usize {
+        self.state.lock().unwrap().used_quota
+    }
+
+    /// 進捗の表示を終える, 以降の出力が進捗の行に続かないように改行する
+    pub fn finish(&self) {
+        let state = self.state.lock().unwrap();
+        if self.enabled && state.started_at.is_some() {
+            eprintln!();
+        }
+    }
+}
+
+impl Default for ProgressLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FetchObserver for ProgressLine {
+    fn on_event(&self, event: &FetchEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            FetchEvent::Started { videos, playlists } => {
+                state.started_at = Some(Instant::now());
+                state.total = videos + playlists;
+            }
+            FetchEvent::UrlsResolved { urls } => state.done += urls,
+            FetchEvent::QuotaSpent { total, .. } => state.used_quota = *total,
+            FetchEvent::RetryScheduled { .. } => state.retries += 1,
+            FetchEvent::SchemaDrift { .. } => state.schema_drift += 1,
+            _ => {}
+        }
+        if !self.enabled {
+            return;
+        }
+        let Some(started_at) = state.started_at else {
+            return;
+        };
+        let line = render(&state, started_at.elapsed());
+        let width = state.last_width.max(line.chars().count());
+        state.last_width = line.chars().count();
+        eprint!("\r{:<width$}", line, width = width);
+        let _ = io::stderr().flush();
+    }
+}
+
+/// e.g. `  12/40 done, 28 remaining, ETA 01:05, quota used 6`
+fn render(state: &State, elapsed: Duration) -> String {
+    let remaining = state.total.saturating_sub(state.done);
+    let mut line = format!(
+        "  {}/{} done, {} remaining, ETA {}, quota used {}",
+        state.done,
+        state.total,
+        remaining,
+        format_eta(state.done, remaining, elapsed),
+        state.used_quota
+    );
+    if state.retries != 0 {
+        line.push_str(&format!(", {} retries", state.retries));
+    }
+    if state.schema_drift != 0 {
+        line.push_str(&format!(", {} unknown values", state.schema_drift));
+    }
+    line
+}
+
+/// これまでの速さから残りの時間を`mm:ss`で返す, 1件も終わっていないときは`--:--`
+fn format_eta(done: usize, remaining: usize, elapsed: Duration) -> String {
+    if done == 0 {
+        return "--:--".into();
+    }
+    let secs = elapsed.as_secs_f64() / done as f64 * remaining as f64;
+    let secs = secs.round() as u64;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_line_format_eta() {
+        assert_eq!(format_eta(0, 10, Duration::from_secs(5)), "--:--");
+        assert_eq!(format_eta(10, 0, Duration::from_secs(5)), "00:00");
+        assert_eq!(format_eta(1, 13, Duration::from_secs(5)), "01:05");
+    }
+
+    #[test]
+    fn test_progress_line_render() {
+        let state = State {
+            total: 40,
+            done: 12,
+            used_quota: 6,
+            ..Default::default()
+        };
+        assert_eq!(
+            render(&state, Duration::from_secs(12)),
+            "  12/40 done, 28 remaining, ETA 00:28, quota used 6"
+        );
+
+        let state = State {
+            retries: 2,
+            ..state
+        };
+        assert!(render(&state, Duration::from_secs(12)).ends_with(", 2 retries"));
+    }
+
+    #[test]
+    fn test_progress_line_counts_events() {
+        let progress = ProgressLine {
+            state: Mutex::new(State::default()),
+            enabled: false,
+        };
+        progress.on_event(&FetchEvent::Started {
+            videos: 60,
+            playlists: 2,
+        });
+        progress.on_event(&FetchEvent::VideoChunkDone { urls: 50 });
+        progress.on_event(&FetchEvent::UrlsResolved { urls: 51 });
+        progress.on_event(&FetchEvent::QuotaSpent { cost: 2, total: 4 });
+        progress.on_event(&FetchEvent::SchemaDrift {
+            detail: "item kind `youtube#short`".into(),
+        });
+        assert_eq!(progress.schema_drift(), 1);
+
+        let state = progress.state.lock().unwrap();
+        assert_eq!((state.total, state.done, state.used_quota), (62, 51, 4));
+    }
+}
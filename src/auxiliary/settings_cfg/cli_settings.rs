@@ -1,13 +1,52 @@
-use clap::{Parser, ValueEnum};
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
 use std::{path::PathBuf, str::FromStr};
 use tracing::level_filters::LevelFilter;
 
 use super::{output_file::OutputFile, ApiKey, PathBufValidUtf8};
-use crate::infrastructure::output_to_file::OutputFileExt as InfraOutputFileExt;
+use crate::{
+    auxiliary::input_urls::{
+        InputColumn, InputFormat as InputUrlsFormat, InputOptions, UrlSource,
+    },
+    infrastructure::{
+        fetch::{Operation, PlaylistItemPart, QuotaCosts, VideoPart},
+        output_to_file::OutputFileExt as InfraOutputFileExt,
+    },
+    metadata::VideoField,
+    url::PlaylistPolicy as UrlPlaylistPolicy,
+};
 
 /// fetch video data using youtube api
 #[derive(Debug, Parser)]
 pub(super) struct CliSettings {
+    // urls
+    /// urls to fetch, `-` reads urls from standard input (the prompt is skipped when any url source is given)
+    urls: Vec<String>,
+    /// file that lists urls, can be repeated, `-` reads standard input
+    #[arg(long, value_name = "FILE")]
+    input: Vec<PathBuf>,
+    /// text, markdown or html file to find every youtube link in, can be repeated, `-` reads standard input
+    #[arg(long, value_name = "FILE")]
+    extract: Vec<PathBuf>,
+    /// google takeout file to import (playlist csv, `watch-history.json` or `.html`), entries are output in order with their added/watched time, can be repeated
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["urls", "input", "extract", "check_existence"]
+    )]
+    takeout: Vec<PathBuf>,
+    /// format of `--input` files and standard input (default: guessed from the extension and contents)
+    #[arg(long, env)]
+    input_format: Option<InputFormat>,
+    /// csv/tsv column that holds urls, a header name or a 1-based index
+    #[arg(long, env, default_value_t = InputColumn::default())]
+    input_column: InputColumn,
+    /// reject inputs that are not a url or an unambiguous id, instead of reading them as a handle without `@`
+    #[arg(long, env, default_value_t = false)]
+    strict_ids: bool,
+    /// what to fetch for a `watch?v=...&list=...` url (default: playlist), `video:`/`playlist:`/`both:` before an input overrides it
+    #[arg(long, env)]
+    playlist_policy: Option<PlaylistPolicy>,
+
     // how to load settings
     /// path to configuration file
     #[arg(short, long, env, default_value_t = default_settings_path_parser())]
@@ -15,6 +54,9 @@ pub(super) struct CliSettings {
     /// no use settings file
     #[arg(short, long, env, default_value_t = false)]
     no_use_settings_file: bool,
+    /// never prompt, exit with an error if required settings are missing (enabled when stdin is not a terminal)
+    #[arg(long, env, default_value_t = false)]
+    no_prompt: bool,
 
     // api
     /// input api the in the program
@@ -32,6 +74,14 @@ pub(super) struct CliSettings {
     #[arg(long, env)]
     file_log_level: Option<LogLevel>,
 
+    // mode
+    /// only check whether each url exists (`part=id`, no snippet), and output the report
+    #[arg(long, env, default_value_t = false)]
+    check_existence: bool,
+    /// print the planned requests and estimated quota without calling the api
+    #[arg(long, env, default_value_t = false)]
+    dry_run: bool,
+
     // output
     /// path to output fetched data
     #[arg(short, long, env)]
@@ -39,6 +89,29 @@ pub(super) struct CliSettings {
     /// output file extension
     #[arg(long, env)]
     output_file_ext: Option<OutputFileExt>,
+    /// video fields to output, separated by commas (default: all)
+    #[arg(long, env, value_delimiter = ',')]
+    output_fields: Vec<OutputField>,
+
+    // api
+    /// `fields` parameter for `videos.list`, overrides the one derived from output fields
+    #[arg(long, env)]
+    videos_fields: Option<String>,
+    /// `fields` parameter for `playlistItems.list`, overrides the one derived from output fields
+    #[arg(long, env)]
+    playlist_items_fields: Option<String>,
+    /// keep the raw item returned by the api under `raw` in the output
+    #[arg(long, env, default_value_t = false)]
+    keep_raw: bool,
+    /// extra `part`s to request from `videos.list`, separated by commas (only visible in `raw`)
+    #[arg(long, env, value_delimiter = ',')]
+    extra_parts: Vec<VideoPart>,
+    /// extra `part`s to request from `playlistItems.list`, separated by commas (only visible in `raw`)
+    #[arg(long, env, value_delimiter = ',')]
+    playlist_items_extra_parts: Vec<PlaylistItemPart>,
+    /// quota cost of an operation like `videos.list=1`, overrides the official cost (repeatable)
+    #[arg(long, env, value_delimiter = ',', value_parser = QuotaCosts::parse_override)]
+    quota_cost: Vec<(Operation, usize)>,
 }
 
 /// デフォルトの設定ファイルへのパス
@@ -54,6 +127,9 @@ impl CliSettings {
     /// `clap::Parse`よりこれを推奨 (`clap::Parse`は`API_KEY`を環境変数から読み込まない)
     pub(super) fn parse_from_cmd_args() -> Self {
         let mut cli_settings = CliSettings::parse();
+        if let Err(e) = cli_settings.check_stdin() {
+            e.exit();
+        }
 
         if let Ok(key) = std::env::var("YOUTUBE_DATA_API_KEY") {
             if let Ok(key) = ApiKey::new(key) {
@@ -68,6 +144,41 @@ impl CliSettings {
         cli_settings
     }
 
+    /// 標準入力は一度しか読み込めないので, `-`を`--extract`と`urls`または`--input`の両方には指定できない
+    pub(super) fn check_stdin(&self) -> Result<(), clap::Error> {
+        let stdin = self.url_sources().contains(&UrlSource::Stdin);
+        let extract_stdin = self.extract.iter().any(|path| path.as_os_str() == "-");
+        if stdin && extract_stdin {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                "`-` (standard input) cannot be given to both `--extract` and `--input` or the urls",
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) fn url_sources(&self) -> Vec<UrlSource> {
+        let args = self.urls.iter().cloned().map(UrlSource::from_arg);
+        let files = self.input.iter().cloned().map(UrlSource::from_path);
+        let texts = self.extract.iter().cloned().map(UrlSource::Extract);
+        args.chain(files).chain(texts).collect()
+    }
+    pub(super) fn takeout(&self) -> Vec<PathBuf> {
+        self.takeout.clone()
+    }
+    pub(super) fn input_options(&self) -> InputOptions {
+        InputOptions {
+            format: self.input_format.map(Into::into).unwrap_or_default(),
+            column: self.input_column.clone(),
+            ..Default::default()
+        }
+    }
+    pub(super) fn strict_ids(&self) -> bool {
+        self.strict_ids
+    }
+    pub(super) fn playlist_policy(&self) -> Option<UrlPlaylistPolicy> {
+        self.playlist_policy.map(Into::into)
+    }
     pub(super) fn settings_path(&self) -> PathBuf {
         self.settings_path.clone().to_path_buf()
     }
@@ -75,6 +186,9 @@ impl CliSettings {
         // 呼び出し側で使用しやすいようにbool値を反転させる
         !self.no_use_settings_file
     }
+    pub(super) fn no_prompt(&self) -> bool {
+        self.no_prompt
+    }
     pub(super) fn input_api_key(&self) -> bool {
         self.input_api_key
     }
@@ -87,12 +201,51 @@ impl CliSettings {
     pub(super) fn file_log_level(&self) -> Option<LevelFilter> {
         self.file_log_level.map(|level| level.into())
     }
+    pub(super) fn check_existence(&self) -> bool {
+        self.check_existence
+    }
+    pub(super) fn dry_run(&self) -> bool {
+        self.dry_run
+    }
     pub(super) fn output_file(&self) -> Option<PathBufValidUtf8> {
         self.output_file_without_ext.clone().map(|f| f.get_path())
     }
     pub(super) fn output_file_ext(&self) -> Option<InfraOutputFileExt> {
         self.output_file_ext.map(Into::into)
     }
+    pub(super) fn output_fields(&self) -> Option<Vec<VideoField>> {
+        if self.output_fields.is_empty() {
+            None
+        } else {
+            Some(self.output_fields.iter().map(|&f| f.into()).collect())
+        }
+    }
+    pub(super) fn videos_fields(&self) -> Option<String> {
+        self.videos_fields.clone()
+    }
+    pub(super) fn playlist_items_fields(&self) -> Option<String> {
+        self.playlist_items_fields.clone()
+    }
+    pub(super) fn keep_raw(&self) -> bool {
+        self.keep_raw
+    }
+    pub(super) fn extra_parts(&self) -> Option<Vec<VideoPart>> {
+        if self.extra_parts.is_empty() {
+            None
+        } else {
+            Some(self.extra_parts.clone())
+        }
+    }
+    pub(super) fn quota_cost(&self) -> Vec<(Operation, usize)> {
+        self.quota_cost.clone()
+    }
+    pub(super) fn playlist_items_extra_parts(&self) -> Option<Vec<PlaylistItemPart>> {
+        if self.playlist_items_extra_parts.is_empty() {
+            None
+        } else {
+            Some(self.playlist_items_extra_parts.clone())
+        }
+    }
 }
 
 #[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
@@ -116,6 +269,49 @@ impl From<LogLevel> for LevelFilter {
     }
 }
 
+#[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+enum InputFormat {
+    Lines,
+    Csv,
+    Tsv,
+    Json,
+    Yaml,
+    Text,
+}
+
+impl From<InputFormat> for InputUrlsFormat {
+    fn from(value: InputFormat) -> Self {
+        match value {
+            InputFormat::Lines => InputUrlsFormat::Lines,
+            InputFormat::Csv => InputUrlsFormat::Csv,
+            InputFormat::Tsv => InputUrlsFormat::Tsv,
+            InputFormat::Json => InputUrlsFormat::Json,
+            InputFormat::Yaml => InputUrlsFormat::Yaml,
+            InputFormat::Text => InputUrlsFormat::Text,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+enum PlaylistPolicy {
+    /// fetch the whole playlist and mark the video
+    Playlist,
+    /// fetch only the video
+    Video,
+    /// fetch both the video and the whole playlist
+    Both,
+}
+
+impl From<PlaylistPolicy> for UrlPlaylistPolicy {
+    fn from(value: PlaylistPolicy) -> Self {
+        match value {
+            PlaylistPolicy::Playlist => UrlPlaylistPolicy::Playlist,
+            PlaylistPolicy::Video => UrlPlaylistPolicy::Video,
+            PlaylistPolicy::Both => UrlPlaylistPolicy::Both,
+        }
+    }
+}
+
 #[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
 enum OutputFileExt {
     Json,
@@ -130,3 +326,32 @@ impl From<OutputFileExt> for InfraOutputFileExt {
         }
     }
 }
+
+#[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+enum OutputField {
+    #[value(name = "id")]
+    Id,
+    #[value(name = "upload_at")]
+    UploadAt,
+    #[value(name = "title")]
+    Title,
+    #[value(name = "description")]
+    Description,
+    #[value(name = "channel_id")]
+    ChannelId,
+    #[value(name = "channel_title")]
+    ChannelTitle,
+}
+
+impl From<OutputField> for VideoField {
+    fn from(value: OutputField) -> Self {
+        match value {
+            OutputField::Id => VideoField::Id,
+            OutputField::UploadAt => VideoField::UploadAt,
+            OutputField::Title => VideoField::Title,
+            OutputField::Description => VideoField::Description,
+            OutputField::ChannelId => VideoField::ChannelId,
+            OutputField::ChannelTitle => VideoField::ChannelTitle,
+        }
+    }
+}
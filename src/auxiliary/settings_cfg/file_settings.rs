@@ -1,11 +1,16 @@
 use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use std::{collections::HashMap, fs, path::PathBuf};
 use thiserror::Error;
 use tracing::level_filters::LevelFilter;
 
 use super::{ApiKey, PathBufValidUtf8};
 use crate::{
-    infrastructure::output_to_file::{deserialize_option_ext_mode, OutputFileExt},
+    infrastructure::{
+        fetch::{Operation, PlaylistItemPart, VideoPart},
+        output_to_file::{deserialize_option_ext_mode, OutputFileExt},
+    },
+    metadata::VideoField,
+    url::PlaylistPolicy,
     util::serde::deserialize_option_level_filter,
 };
 
@@ -38,6 +43,15 @@ pub(super) struct FileSettings {
     output_path_without_ext: Option<PathBufValidUtf8>,
     #[serde(default, deserialize_with = "deserialize_option_ext_mode")]
     output_file_ext: Option<OutputFileExt>,
+    output_fields: Option<Vec<VideoField>>,
+    videos_fields: Option<String>,
+    playlist_items_fields: Option<String>,
+    keep_raw: Option<bool>,
+    strict_ids: Option<bool>,
+    playlist_policy: Option<PlaylistPolicy>,
+    extra_parts: Option<Vec<VideoPart>>,
+    playlist_items_extra_parts: Option<Vec<PlaylistItemPart>>,
+    quota_costs: Option<HashMap<Operation, usize>>,
 }
 
 #[derive(Debug, Error)]
@@ -77,4 +91,31 @@ impl FileSettings {
     pub(super) fn output_file_ext(&self) -> Option<OutputFileExt> {
         self.output_file_ext
     }
+    pub(super) fn output_fields(&self) -> Option<Vec<VideoField>> {
+        self.output_fields.clone()
+    }
+    pub(super) fn videos_fields(&self) -> Option<String> {
+        self.videos_fields.clone()
+    }
+    pub(super) fn playlist_items_fields(&self) -> Option<String> {
+        self.playlist_items_fields.clone()
+    }
+    pub(super) fn keep_raw(&self) -> Option<bool> {
+        self.keep_raw
+    }
+    pub(super) fn strict_ids(&self) -> Option<bool> {
+        self.strict_ids
+    }
+    pub(super) fn playlist_policy(&self) -> Option<PlaylistPolicy> {
+        self.playlist_policy
+    }
+    pub(super) fn extra_parts(&self) -> Option<Vec<VideoPart>> {
+        self.extra_parts.clone()
+    }
+    pub(super) fn playlist_items_extra_parts(&self) -> Option<Vec<PlaylistItemPart>> {
+        self.playlist_items_extra_parts.clone()
+    }
+    pub(super) fn quota_costs(&self) -> Option<HashMap<Operation, usize>> {
+        self.quota_costs.clone()
+    }
 }
@@ -5,4 +5,4 @@ pub(super) mod output_file;
 mod settings;
 
 pub use collections::{ApiKey, PathBufValidUtf8};
-pub use settings::Settings;
+pub use settings::{MissingSettings, RequiredSetting, Settings};
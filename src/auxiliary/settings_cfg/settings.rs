@@ -1,6 +1,20 @@
+use std::{
+    fmt::Display,
+    io::{self, IsTerminal},
+    path::PathBuf,
+};
+use thiserror::Error;
 use tracing::level_filters::LevelFilter;
 
-use crate::infrastructure::output_to_file::OutputFileExt;
+use crate::{
+    auxiliary::input_urls::{InputOptions, UrlSource},
+    infrastructure::{
+        fetch::{ApiFields, ApiOptionsPart, PlaylistItemPart, QuotaCosts},
+        output_to_file::OutputFileExt,
+    },
+    metadata::VideoProjection,
+    url::IdStrictness,
+};
 
 use super::{
     cli_settings::CliSettings, file_settings::FileSettings, ApiKey, PathBufValidUtf8,
@@ -12,36 +26,164 @@ type File<'a> = &'a Option<FileSettings>;
 /// 設定を保持
 #[derive(Debug, Clone)]
 pub struct Settings {
-    api_key: ApiKey,
+    api_key: Option<ApiKey>,
     stdout_log_level: Option<LevelFilter>,
     file_log_level: Option<LevelFilter>,
-    output_path_without_ext: PathBufValidUtf8,
+    output_path_without_ext: Option<PathBufValidUtf8>,
     output_file_ext: OutputFileExt,
+    output_projection: VideoProjection,
+    api_fields: ApiFields,
+    keep_raw: bool,
+    video_parts: ApiOptionsPart,
+    playlist_items_parts: ApiOptionsPart<PlaylistItemPart>,
+    quota_costs: QuotaCosts,
+    check_existence: bool,
+    dry_run: bool,
+    no_prompt: bool,
+    url_sources: Vec<UrlSource>,
+    takeout_paths: Vec<PathBuf>,
+    input_options: InputOptions,
+}
+
+/// 入力を求めずに実行するとき, 値が与えられなかった必須の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequiredSetting {
+    ApiKey,
+    OutputPath,
+}
+
+impl RequiredSetting {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ApiKey => "api key",
+            Self::OutputPath => "output file path",
+        }
+    }
+
+    /// 値を指定できるコマンドライン引数
+    ///
+    /// APIキーは`--input-api-key`で入力を求める以外に指定できない
+    fn flag(&self) -> Option<&'static str> {
+        match self {
+            Self::ApiKey => None,
+            Self::OutputPath => Some("--output-file-without-ext"),
+        }
+    }
+
+    fn env(&self) -> &'static str {
+        match self {
+            Self::ApiKey => "YOUTUBE_DATA_API_KEY",
+            Self::OutputPath => "OUTPUT_FILE_WITHOUT_EXT",
+        }
+    }
+
+    /// 設定ファイルの`[fetch_yt_data_tools]`のキー
+    fn file_key(&self) -> &'static str {
+        match self {
+            Self::ApiKey => "youtube_data_api_key",
+            Self::OutputPath => "output_path_without_ext",
+        }
+    }
 }
 
+impl Display for RequiredSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: set ", self.name())?;
+        if let Some(flag) = self.flag() {
+            write!(f, "`{}`, ", flag)?;
+        }
+        write!(
+            f,
+            "env `{}` or `{}` in settings.toml",
+            self.env(),
+            self.file_key()
+        )
+    }
+}
+
+/// `--no-prompt`のとき, または標準入力が端末でないときに必須の設定が足りない
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "missing required settings, and prompts are disabled (`--no-prompt` or stdin is not a terminal):{}",
+    .0.iter().map(|s| format!("\n  - {}", s)).collect::<String>()
+)]
+pub struct MissingSettings(pub Vec<RequiredSetting>);
+
 impl Settings {
     /// 設定をcli, file(ops) から読み込み
     ///
     /// 優先順位は `cli` > `file`
-    pub fn load() -> Self {
+    ///
+    /// 標準入力が端末でないときは`--no-prompt`が指定されたものとして扱い,
+    /// 必須の設定が足りないときは入力を求めずに`Err`を返す
+    pub fn load() -> Result<Self, MissingSettings> {
         let cli = CliSettings::parse_from_cmd_args();
         let file = load_file_settings(&cli);
+        let no_prompt = cli.no_prompt() || !io::stdin().is_terminal();
+        Self::from_sources(&cli, &file, no_prompt)
+    }
 
-        let stdout_log_level = stdout_log(&cli, &file);
-        let file_log_level = file_log(&cli, &file);
-        let output_path_without_ext = output_path_without_ext(&cli, &file);
-        let output_file_ext = output_file_ext(&cli, &file);
-        let api_key = api_key(&cli, &file);
-        Self {
+    fn from_sources(
+        cli: Cli,
+        file: File,
+        no_prompt: bool,
+    ) -> Result<Self, MissingSettings> {
+        let stdout_log_level = stdout_log(cli, file);
+        let file_log_level = file_log(cli, file);
+        let output_file_ext = output_file_ext(cli, file);
+        let output_projection = output_projection(cli, file);
+        let keep_raw = keep_raw(cli, file);
+        let api_fields = api_fields(cli, file, &output_projection, keep_raw);
+        let video_parts = video_parts(cli, file);
+        let playlist_items_parts = playlist_items_parts(cli, file);
+        let quota_costs = quota_costs(cli, file);
+        let check_existence = cli.check_existence();
+        let dry_run = cli.dry_run();
+        let url_sources = cli.url_sources();
+        let takeout_paths = cli.takeout();
+        let input_options = input_options(cli, file);
+        // dry runではAPIを呼び出さず出力もしないので入力を求めない
+        let (api_key, output_path_without_ext) = if dry_run {
+            (None, None)
+        } else {
+            (
+                api_key(cli, file, no_prompt),
+                output_path_without_ext(cli, file, no_prompt),
+            )
+        };
+        let mut missing = Vec::new();
+        if !dry_run && api_key.is_none() {
+            missing.push(RequiredSetting::ApiKey);
+        }
+        if !dry_run && output_path_without_ext.is_none() {
+            missing.push(RequiredSetting::OutputPath);
+        }
+        if !missing.is_empty() {
+            return Err(MissingSettings(missing));
+        }
+        Ok(Self {
             api_key,
             stdout_log_level,
             file_log_level,
             output_path_without_ext,
             output_file_ext,
-        }
+            output_projection,
+            api_fields,
+            keep_raw,
+            video_parts,
+            playlist_items_parts,
+            quota_costs,
+            check_existence,
+            dry_run,
+            no_prompt,
+            url_sources,
+            takeout_paths,
+            input_options,
+        })
     }
 
-    pub fn get_api_key(&self) -> ApiKey {
+    /// `get_dry_run()`のときは`None`
+    pub fn get_api_key(&self) -> Option<ApiKey> {
         self.api_key.clone()
     }
     pub fn get_stdout_log_level(&self) -> Option<LevelFilter> {
@@ -50,12 +192,55 @@ impl Settings {
     pub fn get_file_log_level(&self) -> Option<LevelFilter> {
         self.file_log_level
     }
-    pub fn get_output_path_without_ext(&self) -> PathBufValidUtf8 {
+    /// `get_dry_run()`のときは`None`
+    pub fn get_output_path_without_ext(&self) -> Option<PathBufValidUtf8> {
         self.output_path_without_ext.clone()
     }
     pub fn get_output_file_ext(&self) -> OutputFileExt {
         self.output_file_ext
     }
+    pub fn get_output_projection(&self) -> VideoProjection {
+        self.output_projection.clone()
+    }
+    pub fn get_api_fields(&self) -> ApiFields {
+        self.api_fields.clone()
+    }
+    pub fn get_keep_raw(&self) -> bool {
+        self.keep_raw
+    }
+    pub fn get_video_parts(&self) -> ApiOptionsPart {
+        self.video_parts.clone()
+    }
+    pub fn get_playlist_items_parts(&self) -> ApiOptionsPart<PlaylistItemPart> {
+        self.playlist_items_parts.clone()
+    }
+    pub fn get_quota_costs(&self) -> QuotaCosts {
+        self.quota_costs.clone()
+    }
+    /// 動画の情報を取得せずに存在のみを確認するかどうか
+    pub fn get_check_existence(&self) -> bool {
+        self.check_existence
+    }
+    /// APIを呼び出さずに送信するリクエストと消費するquotaを表示するかどうか
+    pub fn get_dry_run(&self) -> bool {
+        self.dry_run
+    }
+    /// 入力を求めないかどうか, 標準入力が端末でないときは常に`true`
+    pub fn get_no_prompt(&self) -> bool {
+        self.no_prompt
+    }
+    /// コマンドライン引数で指定された`url`の入力元, 空のときは対話的に入力させる
+    pub fn get_url_sources(&self) -> Vec<UrlSource> {
+        self.url_sources.clone()
+    }
+    /// Google Takeoutのファイル, 空でないときは`get_url_sources()`の代わりに読み込む
+    pub fn get_takeout_paths(&self) -> Vec<PathBuf> {
+        self.takeout_paths.clone()
+    }
+    /// `url`を記述したファイルと標準入力の形式
+    pub fn get_input_options(&self) -> InputOptions {
+        self.input_options.clone()
+    }
 }
 
 fn load_file_settings(cli: Cli) -> Option<FileSettings> {
@@ -83,10 +268,15 @@ fn file_log(cli: Cli, file: File) -> Option<LevelFilter> {
     cli.file_log_level().or_else(|| file.as_ref().and_then(|f| f.file_log_level()))
 }
 
-fn output_path_without_ext(cli: Cli, file: File) -> PathBufValidUtf8 {
+/// `no_prompt`のときは入力を求めず, 指定されていなければ`None`
+fn output_path_without_ext(
+    cli: Cli,
+    file: File,
+    no_prompt: bool,
+) -> Option<PathBufValidUtf8> {
     cli.output_file()
         .or_else(|| file.as_ref().and_then(|f| f.output_path_without_ext()))
-        .unwrap_or_else(|| PathBufValidUtf8::prompt(false))
+        .or_else(|| (!no_prompt).then(|| PathBufValidUtf8::prompt(false)))
 }
 
 fn output_file_ext(cli: Cli, file: File) -> OutputFileExt {
@@ -95,14 +285,196 @@ fn output_file_ext(cli: Cli, file: File) -> OutputFileExt {
         .unwrap_or_default()
 }
 
-fn api_key(cli: Cli, file: File) -> ApiKey {
-    if cli.input_api_key() {
-        return ApiKey::prompt();
+fn output_projection(cli: Cli, file: File) -> VideoProjection {
+    cli.output_fields()
+        .or_else(|| file.as_ref().and_then(|f| f.output_fields()))
+        .map(VideoProjection::new)
+        .unwrap_or_default()
+}
+
+/// `fields`がどちらのエンドポイントにも指定されていないときは出力する項目から求める
+///
+/// ただし`keep_raw`のときはそのままの`item`を残すため`fields`を指定しない
+fn api_fields(
+    cli: Cli,
+    file: File,
+    projection: &VideoProjection,
+    keep_raw: bool,
+) -> ApiFields {
+    let videos =
+        cli.videos_fields().or_else(|| file.as_ref().and_then(|f| f.videos_fields()));
+    let playlist_items = cli
+        .playlist_items_fields()
+        .or_else(|| file.as_ref().and_then(|f| f.playlist_items_fields()));
+    if videos.is_none() && playlist_items.is_none() {
+        if keep_raw {
+            ApiFields::All
+        } else {
+            ApiFields::Projection(projection.clone())
+        }
+    } else {
+        ApiFields::Explicit {
+            videos,
+            playlist_items,
+        }
+    }
+}
+
+fn keep_raw(cli: Cli, file: File) -> bool {
+    cli.keep_raw() || file.as_ref().and_then(|f| f.keep_raw()).unwrap_or(false)
+}
+
+/// `strict_ids`はどちらかで指定されていれば有効, `playlist_policy`はコマンドライン引数を優先する
+fn input_options(cli: Cli, file: File) -> InputOptions {
+    let strict =
+        cli.strict_ids() || file.as_ref().and_then(|f| f.strict_ids()).unwrap_or(false);
+    let playlist_policy = cli
+        .playlist_policy()
+        .or_else(|| file.as_ref().and_then(|f| f.playlist_policy()))
+        .unwrap_or_default();
+    InputOptions {
+        strictness: if strict {
+            IdStrictness::Strict
+        } else {
+            IdStrictness::Lenient
+        },
+        playlist_policy,
+        ..cli.input_options()
     }
-    if let Some(key) = cli.youtube_data_api_key() {
-        return key;
+}
+
+fn video_parts(cli: Cli, file: File) -> ApiOptionsPart {
+    let extra = cli
+        .extra_parts()
+        .or_else(|| file.as_ref().and_then(|f| f.extra_parts()))
+        .unwrap_or_default();
+    ApiOptionsPart::default().with_extra(extra)
+}
+
+fn playlist_items_parts(cli: Cli, file: File) -> ApiOptionsPart<PlaylistItemPart> {
+    let extra = cli
+        .playlist_items_extra_parts()
+        .or_else(|| file.as_ref().and_then(|f| f.playlist_items_extra_parts()))
+        .unwrap_or_default();
+    ApiOptionsPart::default().with_extra(extra)
+}
+
+/// 操作ごとに`cli`の値を`file`の値より優先する
+fn quota_costs(cli: Cli, file: File) -> QuotaCosts {
+    QuotaCosts::default()
+        .with_overrides(file.as_ref().and_then(|f| f.quota_costs()).unwrap_or_default())
+        .with_overrides(cli.quota_cost())
+}
+
+/// `no_prompt`のときは`--input-api-key`を無視して入力を求めず, 指定されていなければ`None`
+fn api_key(cli: Cli, file: File, no_prompt: bool) -> Option<ApiKey> {
+    if cli.input_api_key() && !no_prompt {
+        return Some(ApiKey::prompt());
+    }
+    cli.youtube_data_api_key()
+        .or_else(|| file.as_ref().and_then(|f| f.youtube_data_api_key()))
+        .or_else(|| (!no_prompt).then(ApiKey::prompt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn cli(args: &[&str]) -> CliSettings {
+        CliSettings::parse_from(["fetch-yt-data-tools"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_load_without_prompt() {
+        let err = Settings::from_sources(&cli(&[]), &None, true).unwrap_err();
+        assert_eq!(
+            err,
+            MissingSettings(vec![RequiredSetting::ApiKey, RequiredSetting::OutputPath])
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing required settings, and prompts are disabled (`--no-prompt` or stdin is not a terminal):\n  \
+             - api key: set env `YOUTUBE_DATA_API_KEY` or `youtube_data_api_key` in settings.toml\n  \
+             - output file path: set `--output-file-without-ext`, env `OUTPUT_FILE_WITHOUT_EXT` or `output_path_without_ext` in settings.toml"
+        );
+
+        // `--input-api-key`は入力を求めないので無視する
+        let err = Settings::from_sources(&cli(&["-i", "-o", "./out"]), &None, true)
+            .unwrap_err();
+        assert_eq!(err, MissingSettings(vec![RequiredSetting::ApiKey]));
+
+        // dry runではAPIキーも出力先も必要ない
+        let settings =
+            Settings::from_sources(&cli(&["--dry-run"]), &None, true).unwrap();
+        assert!(settings.get_api_key().is_none());
+        assert!(settings.get_output_path_without_ext().is_none());
+    }
+
+    #[test]
+    fn test_takeout_conflicts_with_url_sources() {
+        let settings = Settings::from_sources(
+            &cli(&["--dry-run", "--takeout", "a.csv", "--takeout", "b.json"]),
+            &None,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.get_takeout_paths(),
+            vec![PathBuf::from("a.csv"), PathBuf::from("b.json")]
+        );
+        let args = [
+            "fetch-yt-data-tools",
+            "--takeout",
+            "a.csv",
+            "https://youtu.be/x",
+        ];
+        assert!(CliSettings::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_stdin_conflicts_between_input_and_extract() {
+        assert!(cli(&["-", "--extract", "notes.md"]).check_stdin().is_ok());
+        assert!(cli(&["--input", "-", "--input", "a.txt"]).check_stdin().is_ok());
+        assert!(cli(&["-", "--extract", "-"]).check_stdin().is_err());
+        assert!(cli(&["--input", "-", "--extract", "-"]).check_stdin().is_err());
+    }
+
+    #[test]
+    fn test_input_options_strict_ids() {
+        let settings =
+            Settings::from_sources(&cli(&["--dry-run"]), &None, true).unwrap();
+        assert_eq!(
+            settings.get_input_options().strictness,
+            IdStrictness::Lenient
+        );
+        let settings =
+            Settings::from_sources(&cli(&["--dry-run", "--strict-ids"]), &None, true)
+                .unwrap();
+        assert_eq!(
+            settings.get_input_options().strictness,
+            IdStrictness::Strict
+        );
+    }
+
+    #[test]
+    fn test_input_options_playlist_policy() {
+        use crate::url::PlaylistPolicy;
+
+        let policy = |args: &[&str], file: &Option<FileSettings>| {
+            Settings::from_sources(&cli(args), file, true)
+                .unwrap()
+                .get_input_options()
+                .playlist_policy
+        };
+        assert_eq!(policy(&["--dry-run"], &None), PlaylistPolicy::Playlist);
+
+        let file = Some(toml::from_str("playlist_policy = \"both\"").unwrap());
+        assert_eq!(policy(&["--dry-run"], &file), PlaylistPolicy::Both);
+        // コマンドライン引数を優先する
+        assert_eq!(
+            policy(&["--dry-run", "--playlist-policy", "video"], &file),
+            PlaylistPolicy::Video
+        );
     }
-    file.as_ref()
-        .and_then(|f| f.youtube_data_api_key())
-        .unwrap_or_else(ApiKey::prompt)
 }
@@ -1,9 +1,16 @@
 use thiserror::Error;
 
+use super::ParseError;
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum Error {
-    #[error("Invalid input")]
-    InvalidInput,
+    #[error(transparent)]
+    InvalidInput(#[from] ParseError),
     #[error("Network error: `{0}`")]
     NetworkError(String),
+    #[error("Interrupted")]
+    Interrupted,
+    /// APIで取得できない`url`, 理由を保持する
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 }
@@ -1,6 +1,11 @@
 mod error;
 mod models;
+mod parse_error;
 pub mod repositories;
 
 pub use error::Error;
-pub use models::{id, metadata, url, Handle};
+pub use models::{
+    id, metadata, url, Annotated, Annotator, Existence, FetchedItem, FetchedResults,
+    Handle, ItemExistence, MalformedEntries, MalformedEntry, UrlExistence,
+};
+pub use parse_error::{ParseError, ParseErrorKind};
@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{
+    id::{PlaylistId, VideoId},
+    metadata::BasicData,
+    url::Url,
+    FetchedItem,
+};
+
+/// 入力の文脈`C`(追加した日時など)を付けた`url`とその取得結果
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Annotated<C> {
+    pub url: Url,
+    pub context: C,
+    /// 見つからなかった, 取得を試みなかったなどのときは`None`
+    pub data: Option<BasicData>,
+}
+
+/// 取得結果と`url`を対応させるキー
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Video(VideoId),
+    Playlist(PlaylistId),
+}
+
+impl Key {
+    fn of_url(url: &Url) -> Self {
+        match url {
+            Url::Video(v) => Self::Video(<&VideoId>::from(v).clone()),
+            Url::Playlist(pl) => Self::Playlist(pl.get_playlist_id().clone()),
+        }
+    }
+
+    fn of_data(data: &BasicData) -> Self {
+        match data {
+            BasicData::Video(v) => Self::Video(v.id.clone()),
+            BasicData::Playlist(pl) => {
+                Self::Playlist(pl.playlist_data_itself.id.clone())
+            }
+        }
+    }
+}
+
+/// 入力の文脈`C`を付けた`url`を重複を除いて取得し, 取得結果を入力の順に対応させる
+///
+/// 取得できたものから順に記録し, 先頭から結果が確定した項目を取り出す
+#[derive(Debug)]
+pub struct Annotator<C> {
+    /// まだ取り出していない項目, 入力の順
+    entries: VecDeque<(Url, C)>,
+    /// まだ取り出していない項目がそのキーを持つ数
+    remaining: HashMap<Key, usize>,
+    /// 結果が確定したキーとその取得できたデータ
+    results: HashMap<Key, Option<BasicData>>,
+}
+
+impl<C> Annotator<C> {
+    pub fn new(entries: Vec<(Url, C)>) -> Self {
+        let mut remaining: HashMap<Key, usize> = HashMap::new();
+        for (url, _) in &entries {
+            *remaining.entry(Key::of_url(url)).or_default() += 1;
+        }
+        Self {
+            entries: entries.into(),
+            remaining,
+            results: HashMap::new(),
+        }
+    }
+
+    /// 取得する`url`, 重複を除いた入力の順
+    pub fn urls(&self) -> VecDeque<Url> {
+        let mut seen = HashSet::new();
+        self.entries
+            .iter()
+            .map(|(url, _)| url)
+            .filter(|url| seen.insert(*url))
+            .cloned()
+            .collect()
+    }
+
+    /// 取得結果を記録する, `Found`以外はデータ無しとして確定する
+    pub fn record(&mut self, item: FetchedItem<BasicData, Url>) {
+        let (key, data) = match item {
+            FetchedItem::Found(data) => (Key::of_data(&data), Some(data)),
+            FetchedItem::NotFound(url)
+            | FetchedItem::Unattempted(url)
+            | FetchedItem::Malformed(url, _)
+            | FetchedItem::Unsupported(url, _) => (Key::of_url(&url), None),
+        };
+        if self.remaining.contains_key(&key) {
+            self.results.insert(key, data);
+        }
+    }
+
+    /// 先頭から結果が確定した項目を取り出す
+    pub fn take_ready(&mut self) -> Vec<Annotated<C>> {
+        let mut ready = Vec::new();
+        while let Some((url, _)) = self.entries.front() {
+            let key = Key::of_url(url);
+            if !self.results.contains_key(&key) {
+                break;
+            }
+            let (url, context) = self.entries.pop_front().unwrap();
+            let data = self.take_data(key);
+            ready.push(Annotated { url, context, data });
+        }
+        ready
+    }
+
+    /// 残りの全ての項目を取り出す, 結果が確定していないものは`data`を`None`とする
+    pub fn take_rest(&mut self) -> Vec<Annotated<C>> {
+        let mut rest = self.take_ready();
+        while let Some((url, context)) = self.entries.pop_front() {
+            let data = self.take_data(Key::of_url(&url));
+            rest.push(Annotated { url, context, data });
+        }
+        rest
+    }
+
+    /// 同じキーを持つ項目がもう無いときは結果を破棄する
+    fn take_data(&mut self, key: Key) -> Option<BasicData> {
+        let count = self.remaining.get_mut(&key).expect("every entry is counted");
+        *count -= 1;
+        if *count == 0 {
+            self.remaining.remove(&key);
+            self.results.remove(&key).flatten()
+        } else {
+            self.results.get(&key).cloned().flatten()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::BasicVideoData;
+
+    #[test]
+    fn test_annotator_keeps_order_and_duplicates() {
+        let v_1: Url = crate::url::UrlVideo::from(VideoId::all_1()).into();
+        let v_2: Url = crate::url::UrlVideo::from(VideoId::all_2()).into();
+        let entries = vec![(v_1.clone(), 1), (v_2.clone(), 2), (v_1.clone(), 3)];
+        let mut annotator = Annotator::new(entries);
+        assert_eq!(
+            annotator.urls(),
+            VecDeque::from(vec![v_1.clone(), v_2.clone()])
+        );
+
+        // 先頭の結果が確定するまで取り出さない
+        annotator.record(FetchedItem::NotFound(v_2.clone()));
+        assert!(annotator.take_ready().is_empty());
+
+        annotator.record(FetchedItem::Found(BasicVideoData::self_1().into()));
+        let data = Some(BasicData::from(BasicVideoData::self_1()));
+        assert_eq!(
+            annotator.take_ready(),
+            vec![
+                Annotated {
+                    url: v_1.clone(),
+                    context: 1,
+                    data: data.clone()
+                },
+                Annotated {
+                    url: v_2,
+                    context: 2,
+                    data: None
+                },
+                Annotated {
+                    url: v_1,
+                    context: 3,
+                    data
+                },
+            ]
+        );
+        assert!(annotator.results.is_empty());
+    }
+
+    #[test]
+    fn test_annotator_take_rest() {
+        let v_1: Url = crate::url::UrlVideo::from(VideoId::all_1()).into();
+        let v_2: Url = crate::url::UrlVideo::from(VideoId::all_2()).into();
+        let mut annotator = Annotator::new(vec![(v_1, 1), (v_2.clone(), 2)]);
+        annotator.record(FetchedItem::Found(BasicVideoData::self_2().into()));
+
+        // 結果が確定していない項目も`data`を`None`として取り出す
+        let rest = annotator.take_rest();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].data, None);
+        assert_eq!(rest[1].data, Some(BasicVideoData::self_2().into()));
+        assert!(annotator.take_rest().is_empty());
+    }
+}
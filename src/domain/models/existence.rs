@@ -0,0 +1,121 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use super::fetched_results::{MalformedEntries, MalformedEntry};
+use crate::{id::VideoId, url::Url};
+
+/// 動画や再生リストが存在するかどうか
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Existence {
+    Existing,
+    /// 存在しない, あるいは非公開のため確認できない
+    Missing,
+    /// 再生リスト内の非公開の動画
+    Private,
+    /// 再生リスト内の削除済みの動画
+    Deleted,
+}
+
+/// 再生リスト内の動画の存在
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct ItemExistence {
+    pub video_id: VideoId,
+    pub existence: Existence,
+}
+
+/// 入力された`url`の存在
+///
+/// 再生リストのときは`items`に再生リスト内の動画の存在を保持する
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UrlExistence {
+    pub url: Url,
+    pub existence: Existence,
+    pub items: Vec<ItemExistence>,
+    /// 再生リスト内の解釈できなかった項目, 出力には含めない
+    pub malformed: Vec<MalformedEntry>,
+}
+
+impl UrlExistence {
+    pub fn new(url: Url, existence: Existence) -> Self {
+        Self {
+            url,
+            existence,
+            items: Vec::new(),
+            malformed: Vec::new(),
+        }
+    }
+
+    pub fn with_items(mut self, items: Vec<ItemExistence>) -> Self {
+        self.items = items;
+        self
+    }
+
+    pub fn with_malformed(mut self, malformed: Vec<MalformedEntry>) -> Self {
+        self.malformed = malformed;
+        self
+    }
+}
+
+impl MalformedEntries for UrlExistence {
+    fn malformed_entries(&self) -> Vec<(Url, String)> {
+        match &self.url {
+            Url::Playlist(pl) => self
+                .malformed
+                .iter()
+                .map(|entry| entry.to_url_reason(pl.get_playlist_id()))
+                .collect(),
+            Url::Video(_) => Vec::new(),
+        }
+    }
+}
+
+/// `url`は`build_url`の文字列, 動画の`url`のときは`items`を含めない
+impl Serialize for UrlExistence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let is_playlist = matches!(self.url, Url::Playlist(_));
+        let len = if is_playlist { 3 } else { 2 };
+        let mut state = serializer.serialize_struct("UrlExistence", len)?;
+        state.serialize_field("url", &self.url)?;
+        state.serialize_field("existence", &self.existence)?;
+        if is_playlist {
+            state.serialize_field("items", &self.items)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{id::PlaylistId, url::UrlVideo};
+
+    #[test]
+    fn test_url_existence_serialize() {
+        let video = UrlExistence::new(
+            UrlVideo::from(VideoId::all_1()).into(),
+            Existence::Missing,
+        );
+        assert_eq!(
+            serde_json::to_value(&video).unwrap(),
+            serde_json::json!({
+                "url": "https://www.youtube.com/watch?v=11111111111",
+                "existence": "missing"
+            })
+        );
+
+        let playlist = UrlExistence::new(
+            Url::Playlist((None, PlaylistId::all_1()).into()),
+            Existence::Existing,
+        )
+        .with_items(vec![ItemExistence {
+            video_id: VideoId::all_2(),
+            existence: Existence::Private,
+        }]);
+        let value = serde_json::to_value(&playlist).unwrap();
+        assert_eq!(value["existence"], "existing");
+        assert_eq!(
+            value["items"],
+            serde_json::json!([{"video_id": "22222222222", "existence": "private"}])
+        );
+    }
+}
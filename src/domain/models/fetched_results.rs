@@ -0,0 +1,255 @@
+use futures::{Stream, StreamExt};
+use std::collections::VecDeque;
+
+use crate::{
+    id::{PlaylistId, VideoId},
+    url::{Url, UrlPlaylist, UrlVideo},
+};
+
+/// 一つの`url(id)`に対する取得結果
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FetchedItem<T, U> {
+    /// 正常に情報を取得できたとき
+    Found(T),
+    /// `url(id)`が存在せず取得できなかったとき
+    NotFound(U),
+    /// 中断されたため取得を試みなかったとき
+    Unattempted(U),
+    /// レスポンスを解釈できなかったとき, 理由を併せて保持する
+    Malformed(U, String),
+    /// ミックスリストなどAPIで取得できないため取得を試みなかったとき, 理由を併せて保持する
+    Unsupported(U, String),
+}
+
+impl<T, U> FetchedItem<T, U> {
+    /// 内部の値をそれぞれ`Into`で変換する
+    pub fn convert<A, B>(self) -> FetchedItem<A, B>
+    where
+        T: Into<A>,
+        U: Into<B>,
+    {
+        match self {
+            Self::Found(data) => FetchedItem::Found(data.into()),
+            Self::NotFound(url) => FetchedItem::NotFound(url.into()),
+            Self::Unattempted(url) => FetchedItem::Unattempted(url.into()),
+            Self::Malformed(url, reason) => FetchedItem::Malformed(url.into(), reason),
+            Self::Unsupported(url, reason) => {
+                FetchedItem::Unsupported(url.into(), reason)
+            }
+        }
+    }
+}
+
+impl<T, U> From<Result<T, U>> for FetchedItem<T, U> {
+    fn from(value: Result<T, U>) -> Self {
+        match value {
+            Ok(data) => Self::Found(data),
+            Err(url) => Self::NotFound(url),
+        }
+    }
+}
+
+/// 再生リスト内の解釈できなかった項目
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MalformedEntry {
+    /// 項目の動画の`VideoId`, 読み取れなかったときは`None`
+    pub video_id: Option<VideoId>,
+    pub reason: String,
+}
+
+impl MalformedEntry {
+    /// 項目を指す`url`と理由, `VideoId`を読み取れなかったときは再生リストの`url`とする
+    pub fn to_url_reason(&self, playlist: &PlaylistId) -> (Url, String) {
+        let url = match &self.video_id {
+            Some(id) => UrlVideo::from(id.clone()).into(),
+            None => UrlPlaylist::from((None, playlist.clone())).into(),
+        };
+        let reason = format!("item in playlist `{}`: {}", playlist, self.reason);
+        (url, reason)
+    }
+}
+
+/// `Found`の結果に含まれる, 解釈できなかった再生リスト内の項目
+pub trait MalformedEntries {
+    /// 項目を指す`url`と理由
+    fn malformed_entries(&self) -> Vec<(Url, String)>;
+}
+
+/// 複数の`url(id)`に対する取得結果
+///
+/// - `fetched`: 取得を試みた`url(id)`の結果
+///   - `Ok(T)`: 正常に情報を取得できたとき
+///   - `Err(U)`: `url(id)`が存在せず取得できなかったとき
+/// - `unattempted`: 中断されたため取得を試みなかった`url(id)`
+/// - `malformed`: レスポンスを解釈できなかった`url(id)`とその理由
+/// - `unsupported`: APIで取得できないため取得を試みなかった`url(id)`とその理由
+///
+/// 中断されなかったときは`unattempted`は常に空
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FetchedResults<T, U> {
+    pub fetched: VecDeque<Result<T, U>>,
+    pub unattempted: VecDeque<U>,
+    pub malformed: VecDeque<(U, String)>,
+    pub unsupported: VecDeque<(U, String)>,
+}
+
+impl<T, U> FetchedResults<T, U> {
+    pub fn new(fetched: VecDeque<Result<T, U>>, unattempted: VecDeque<U>) -> Self {
+        Self {
+            fetched,
+            unattempted,
+            malformed: VecDeque::new(),
+            unsupported: VecDeque::new(),
+        }
+    }
+
+    /// 途中で中断されたかどうか
+    pub fn is_interrupted(&self) -> bool {
+        !self.unattempted.is_empty()
+    }
+
+    /// 一つの取得結果を末尾に追加する
+    pub fn push(&mut self, item: FetchedItem<T, U>) {
+        match item {
+            FetchedItem::Found(data) => self.fetched.push_back(Ok(data)),
+            FetchedItem::NotFound(url) => self.fetched.push_back(Err(url)),
+            FetchedItem::Unattempted(url) => self.unattempted.push_back(url),
+            FetchedItem::Malformed(url, reason) => {
+                self.malformed.push_back((url, reason))
+            }
+            FetchedItem::Unsupported(url, reason) => {
+                self.unsupported.push_back((url, reason))
+            }
+        }
+    }
+
+    /// `Stream`から全ての取得結果を受け取りまとめる
+    ///
+    /// `Err`を受け取った時点でそれを返す
+    pub async fn try_collect<E, S>(stream: S) -> Result<Self, E>
+    where
+        S: Stream<Item = Result<FetchedItem<T, U>, E>>,
+    {
+        let mut stream = std::pin::pin!(stream);
+        let mut res = Self::default();
+        while let Some(item) = stream.next().await {
+            res.push(item?);
+        }
+        Ok(res)
+    }
+
+    /// 別の取得結果を末尾に結合する
+    pub fn extend(&mut self, other: Self) {
+        self.fetched.extend(other.fetched);
+        self.unattempted.extend(other.unattempted);
+        self.malformed.extend(other.malformed);
+        self.unsupported.extend(other.unsupported);
+    }
+
+    /// 内部の値をそれぞれ`Into`で変換する
+    pub fn convert<A, B>(self) -> FetchedResults<A, B>
+    where
+        T: Into<A>,
+        U: Into<B>,
+    {
+        FetchedResults {
+            fetched: self
+                .fetched
+                .into_iter()
+                .map(|res| res.map(Into::into).map_err(Into::into))
+                .collect(),
+            unattempted: self.unattempted.into_iter().map(Into::into).collect(),
+            malformed: self
+                .malformed
+                .into_iter()
+                .map(|(url, reason)| (url.into(), reason))
+                .collect(),
+            unsupported: self
+                .unsupported
+                .into_iter()
+                .map(|(url, reason)| (url.into(), reason))
+                .collect(),
+        }
+    }
+}
+
+impl<T, U> Default for FetchedResults<T, U> {
+    fn default() -> Self {
+        Self::new(VecDeque::new(), VecDeque::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetched_results_is_interrupted() {
+        let completed: FetchedResults<u8, u16> =
+            FetchedResults::new(vec![Ok(1), Err(2)].into(), VecDeque::new());
+        assert!(!completed.is_interrupted());
+
+        let interrupted: FetchedResults<u8, u16> =
+            FetchedResults::new(vec![Ok(1)].into(), vec![3].into());
+        assert!(interrupted.is_interrupted());
+    }
+
+    #[test]
+    fn test_fetched_results_extend_and_convert() {
+        let mut res: FetchedResults<u8, u16> =
+            FetchedResults::new(vec![Ok(1)].into(), VecDeque::new());
+        res.extend(FetchedResults::new(vec![Err(2)].into(), vec![3].into()));
+
+        let converted: FetchedResults<u32, u64> = res.convert();
+        assert_eq!(
+            converted,
+            FetchedResults::new(vec![Ok(1), Err(2)].into(), vec![3].into())
+        );
+    }
+
+    #[test]
+    fn test_malformed_entry_to_url_reason() {
+        let entry = MalformedEntry {
+            video_id: Some(VideoId::all_1()),
+            reason: "missing field".into(),
+        };
+        assert_eq!(
+            entry.to_url_reason(&PlaylistId::all_1()),
+            (
+                UrlVideo::from(VideoId::all_1()).into(),
+                format!("item in playlist `{}`: missing field", PlaylistId::all_1())
+            )
+        );
+
+        let entry = MalformedEntry {
+            video_id: None,
+            reason: "missing field".into(),
+        };
+        assert_eq!(
+            entry.to_url_reason(&PlaylistId::all_1()).0,
+            UrlPlaylist::from((None, PlaylistId::all_1())).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetched_results_try_collect() {
+        let items: Vec<Result<FetchedItem<u8, u16>, ()>> = vec![
+            Ok(FetchedItem::Found(1)),
+            Ok(FetchedItem::Unattempted(3)),
+            Ok(FetchedItem::NotFound(2)),
+            Ok(FetchedItem::Malformed(4, "reason".into())),
+            Ok(FetchedItem::Unsupported(5, "mix".into())),
+        ];
+        let res =
+            FetchedResults::try_collect(futures::stream::iter(items)).await.unwrap();
+        assert_eq!(res.fetched, VecDeque::from(vec![Ok(1), Err(2)]));
+        assert_eq!(res.unattempted, VecDeque::from(vec![3]));
+        assert_eq!(res.malformed, VecDeque::from(vec![(4, "reason".into())]));
+        assert_eq!(res.unsupported, VecDeque::from(vec![(5, "mix".into())]));
+
+        let items: Vec<Result<FetchedItem<u8, u16>, ()>> =
+            vec![Ok(FetchedItem::Found(1)), Err(())];
+        let res = FetchedResults::try_collect(futures::stream::iter(items)).await;
+        assert_eq!(res, Err(()));
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
+use crate::{ParseError, ParseErrorKind};
+
 /// チャンネルのハンドルネーム
 ///
 /// ascii文字以外も対応している
@@ -12,27 +14,31 @@ pub struct Handle(String);
 
 impl Handle {
     /// 引数の`id`は`@`が接頭語としてついていても問題ない
-    pub fn new(id: String) -> Result<Self, crate::Error> {
+    pub fn new(id: String) -> Result<Self, ParseError> {
         // `@`はハンドルネームとして使用できないかつ接頭語として付与することが多いので
         // `@`が存在すれば`@`を空文字に置換して処理
-        let id = id.replace("@", "");
-        if id.len() <= 30 && !id.is_empty() {
-            Ok(Self(format!("@{}", id)))
+        let handle = id.replace("@", "");
+        if handle.len() <= 30 && !handle.is_empty() {
+            Ok(Self(format!("@{}", handle)))
         } else {
-            Err(crate::Error::InvalidInput)
+            Err(ParseError::whole(
+                ParseErrorKind::InvalidLength(handle.len()),
+                &id,
+                "a handle of 1-30 bytes after `@`",
+            ))
         }
     }
 }
 
 impl TryFrom<String> for Handle {
-    type Error = crate::Error;
+    type Error = ParseError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
         Self::new(value)
     }
 }
 
 impl TryFrom<&str> for Handle {
-    type Error = crate::Error;
+    type Error = ParseError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         Self::new(value.to_string())
     }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use super::valid_ascii::validate_id;
+use crate::{ParseError, ParseErrorKind};
+
+/// チャンネルのID
+///
+/// `UC`から始まる24文字の固定長
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Hash)]
+pub struct ChannelId(String);
+
+impl ChannelId {
+    /// 期待する形式, エラーの表示に使用する
+    pub const EXPECTED: &'static str =
+        "a channel id of `UC` and 22 characters (`0-9`, `A-Z`, `a-z`, `-`, `_`)";
+
+    pub fn new(id: String) -> Result<Self, ParseError> {
+        validate_id(&id, 24, Self::EXPECTED)?;
+        if !id.starts_with("UC") {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidPrefix,
+                &id,
+                0,
+                &id[..2],
+                Self::EXPECTED,
+            ));
+        }
+        Ok(Self(id))
+    }
+}
+
+impl FromStr for ChannelId {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_string())
+    }
+}
+
+impl Display for ChannelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ChannelId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let id = String::deserialize(deserializer)?;
+        Self::new(id).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_id_gives_valid() {
+        let chars_len_24 = "UCaaaa-bbbb-cccc-dddd-ee".to_string();
+        assert!(ChannelId::new(chars_len_24).is_ok());
+    }
+
+    #[test]
+    fn test_channel_id_gives_invalid() {
+        let chars_len_23 = "UCaaaa-bbbb-cccc-dddd-e".to_string();
+        assert!(ChannelId::new(chars_len_23).is_err());
+        let err = ChannelId::new("UUaaaa-bbbb-cccc-dddd-ee".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidPrefix);
+        assert_eq!(err.fragment, "UU");
+    }
+}
@@ -1,4 +1,5 @@
 use super::{PlaylistId, VideoId};
+use crate::ParseErrorKind;
 
 /// domain層の内部でデータを相互変換しやすくするための型
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -44,8 +45,9 @@ impl From<(Option<VideoId>, PlaylistId)> for IdType {
     }
 }
 
+/// どちらのIDも無いときは`MissingId`
 impl TryFrom<(Option<VideoId>, Option<PlaylistId>)> for IdType {
-    type Error = crate::Error;
+    type Error = ParseErrorKind;
     fn try_from(
         value: (Option<VideoId>, Option<PlaylistId>),
     ) -> Result<Self, Self::Error> {
@@ -53,7 +55,7 @@ impl TryFrom<(Option<VideoId>, Option<PlaylistId>)> for IdType {
             (Some(v), None) => Ok(Self::Video(v)),
             (None, Some(pl)) => Ok(Self::Playlist(pl)),
             (Some(v), Some(pl)) => Ok(Self::VideoPlaylist(v, pl)),
-            (None, None) => Err(crate::Error::InvalidInput),
+            (None, None) => Err(ParseErrorKind::MissingId),
         }
     }
 }
@@ -62,7 +64,7 @@ impl IdType {
     pub fn new(
         v_id: Option<VideoId>,
         pl_id: Option<PlaylistId>,
-    ) -> Result<Self, crate::Error> {
+    ) -> Result<Self, ParseErrorKind> {
         (v_id, pl_id).try_into()
     }
 }
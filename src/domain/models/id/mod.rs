@@ -1,7 +1,11 @@
+mod channel_id;
 pub(super) mod id_type;
 mod playlist_id;
+mod playlist_kind;
 mod valid_ascii;
 mod video_id;
 
+pub use channel_id::ChannelId;
 pub use playlist_id::PlaylistId;
+pub use playlist_kind::PlaylistKind;
 pub use video_id::VideoId;
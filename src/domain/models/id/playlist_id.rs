@@ -1,26 +1,53 @@
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-use super::valid_ascii::VALID_ASCII_CHARS;
+use super::{playlist_kind::PlaylistKind, valid_ascii::validate_chars};
+use crate::{ParseError, ParseErrorKind};
 
 /// 再生リストのID
 ///
-/// 34文字の固定長
+/// 長さは接頭語から分かる種類(`PlaylistKind`)ごとに決まる
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Hash)]
 pub struct PlaylistId(String);
 
 impl PlaylistId {
-    pub fn new(id: String) -> Result<Self, crate::Error> {
-        if id.len() == 34 && id.chars().all(|c| VALID_ASCII_CHARS.contains(&c)) {
-            Ok(Self(id))
-        } else {
-            Err(crate::Error::InvalidInput)
+    /// 期待する形式, エラーの表示に使用する
+    pub const EXPECTED: &'static str =
+        "a playlist id (`0-9`, `A-Z`, `a-z`, `-`, `_`) of the length for its prefix";
+
+    pub fn new(id: String) -> Result<Self, ParseError> {
+        validate_chars(&id, Self::EXPECTED)?;
+        let kind = PlaylistKind::from_id(&id);
+        if !kind.accepts_len(id.len()) {
+            let expected = match kind.prefix() {
+                Some(prefix) => format!(
+                    "a playlist id starting with `{}` of {}",
+                    prefix,
+                    kind.length_rule()
+                ),
+                None => format!("a playlist id of {}", kind.length_rule()),
+            };
+            return Err(ParseError::whole(
+                ParseErrorKind::InvalidLength(id.len()),
+                &id,
+                expected,
+            ));
         }
+        Ok(Self(id))
+    }
+
+    pub fn kind(&self) -> PlaylistKind {
+        PlaylistKind::from_id(&self.0)
+    }
+
+    /// `playlistItems.list`で取得できない再生リストのとき, その理由
+    pub fn unsupported_reason(&self) -> Option<&'static str> {
+        self.kind().unsupported_reason(self.0.len())
     }
 }
 
 impl FromStr for PlaylistId {
-    type Err = crate::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Self::new(s.to_string())
     }
@@ -80,6 +107,34 @@ mod tests {
         assert!(PlaylistId::new(chars_len_35).is_err());
     }
 
+    #[test]
+    fn test_playlist_id_kinds() {
+        let cases = [
+            ("PLaaaa-bbbb-cccc-d", PlaylistKind::Playlist),
+            ("UUaaaa-bbbb-cccc-dddd-ee", PlaylistKind::Uploads),
+            ("LL", PlaylistKind::Liked),
+            ("RDaaaa-bbbb-c", PlaylistKind::Mix),
+            ("RDMM", PlaylistKind::Mix),
+            (
+                "OLAK5uy_aaaa-bbbb-cccc-dddd-eeee-ffff-ggg",
+                PlaylistKind::Album,
+            ),
+            ("ULaaaa-bbbb-c", PlaylistKind::VideoUploads),
+        ];
+        for (id, kind) in cases {
+            assert_eq!(PlaylistId::new(id.into()).map(|id| id.kind()), Ok(kind));
+        }
+
+        let err = PlaylistId::new("OLAK5uy_aaaa".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidLength(12));
+        assert_eq!(
+            err.expected,
+            "a playlist id starting with `OLAK5uy_` of 41 characters"
+        );
+        assert!(PlaylistId::new("FLaaaa".into()).is_err());
+        assert!(PlaylistId::new("RD".into()).is_err());
+    }
+
     #[test]
     fn test_playlist_id_deserialize() {
         let chars_len_34 = "\"aaaa-bbbb-cccc-dddd-eeee-ffff-gggg\"";
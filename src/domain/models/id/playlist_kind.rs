@@ -0,0 +1,154 @@
+use serde::Serialize;
+
+/// 再生リストのIDの接頭語から分かる種類
+///
+/// 種類ごとにIDの長さが決まっている
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistKind {
+    /// `PL`, ユーザーが作成した再生リスト
+    Playlist,
+    /// `UU`, チャンネルのアップロード動画, `UULF`(ショート以外)などを含む
+    Uploads,
+    /// `LL`, 高く評価した動画
+    Liked,
+    /// `FL`, お気に入り
+    Favorites,
+    /// `RD`, 視聴者ごとに生成されるミックスリスト
+    Mix,
+    /// `OLAK5uy_`, YouTube Musicのアルバム
+    Album,
+    /// `UL`, 動画を起点に生成される再生リスト
+    VideoUploads,
+    /// `WL`, 後で見る
+    WatchLater,
+    /// `LM`, YouTube Musicの高く評価した曲
+    LikedMusic,
+    /// 上記以外の接頭語, 34文字, 古い再生リスト(`EC`など)は18文字
+    Other,
+}
+
+impl PlaylistKind {
+    /// 長い接頭語から順に判定する
+    const PREFIXES: [(&'static str, Self); 9] = [
+        ("OLAK5uy_", Self::Album),
+        ("PL", Self::Playlist),
+        ("UU", Self::Uploads),
+        ("LL", Self::Liked),
+        ("FL", Self::Favorites),
+        ("RD", Self::Mix),
+        ("UL", Self::VideoUploads),
+        ("WL", Self::WatchLater),
+        ("LM", Self::LikedMusic),
+    ];
+
+    /// `id`の接頭語から判定する, 長さは確認しない
+    pub fn from_id(id: &str) -> Self {
+        Self::PREFIXES
+            .iter()
+            .find(|(prefix, _)| id.starts_with(prefix))
+            .map(|(_, kind)| *kind)
+            .unwrap_or(Self::Other)
+    }
+
+    /// `Other`以外の接頭語
+    pub fn prefix(self) -> Option<&'static str> {
+        Self::PREFIXES.iter().find(|(_, kind)| *kind == self).map(|(prefix, _)| *prefix)
+    }
+
+    /// 接頭語を含めたIDの長さとして正しいかどうか
+    pub fn accepts_len(self, len: usize) -> bool {
+        match self {
+            // 古い再生リストは`PL`と16文字
+            Self::Playlist => len == 34 || len == 18,
+            // `UU`とチャンネルのIDの22文字, `UULF`などは2文字多い
+            Self::Uploads => len == 24 || len == 26,
+            // `LL`のみは自分の高く評価した動画
+            Self::Liked => len == 2 || len == 24,
+            Self::Favorites => len == 24,
+            // `RD`と動画のID, `RDMM`, `RDCLAK5uy_`とアルバムのIDなど様々
+            Self::Mix => (4..=50).contains(&len),
+            Self::Album => len == 41,
+            // `UL`と動画のID, またはチャンネルのIDの22文字
+            Self::VideoUploads => len == 13 || len == 24,
+            Self::WatchLater | Self::LikedMusic => len == 2,
+            // 古い再生リストは`EC`などと16文字
+            Self::Other => len == 34 || len == 18,
+        }
+    }
+
+    /// エラーの表示に使用する, 接頭語を含めた長さ
+    pub fn length_rule(self) -> &'static str {
+        match self {
+            Self::Playlist => "34 or 18 characters",
+            Self::Uploads => "24 or 26 characters",
+            Self::Liked => "2 or 24 characters",
+            Self::Favorites => "24 characters",
+            Self::Mix => "4 to 50 characters",
+            Self::Album => "41 characters",
+            Self::VideoUploads => "13 or 24 characters",
+            Self::WatchLater | Self::LikedMusic => "2 characters",
+            Self::Other => "34 or 18 characters",
+        }
+    }
+
+    /// 接頭語を含めた長さが`len`のIDの再生リストを`playlistItems.list`で取得できない理由,
+    /// 取得できるときは`None`
+    ///
+    /// `Liked`は`LL`とチャンネルのIDのときのみ, 公開しているチャンネルがあるので試みる
+    pub fn unsupported_reason(self, len: usize) -> Option<&'static str> {
+        match self {
+            Self::Liked if len == 2 => Some(
+                "`LL` alone is the liked videos of the signed-in user and cannot be read with an api key",
+            ),
+            Self::Mix => Some(
+                "mixes (`RD...`) are generated for each viewer when played and are not stored as playlists, \
+                 so `playlistItems.list` cannot list their videos (it answers `playlistNotFound`)",
+            ),
+            Self::VideoUploads => Some(
+                "`UL...` lists are generated from a video like mixes and are not stored as playlists, \
+                 so `playlistItems.list` cannot list their videos",
+            ),
+            Self::WatchLater | Self::LikedMusic => Some(
+                "`WL` and `LM` are private lists of the signed-in user and cannot be read with an api key",
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_kind_from_id() {
+        assert_eq!(PlaylistKind::from_id("PLxxxx"), PlaylistKind::Playlist);
+        assert_eq!(PlaylistKind::from_id("OLAK5uy_xxxx"), PlaylistKind::Album);
+        assert_eq!(PlaylistKind::from_id("RDMM"), PlaylistKind::Mix);
+        assert_eq!(PlaylistKind::from_id("1234"), PlaylistKind::Other);
+        assert_eq!(PlaylistKind::Album.prefix(), Some("OLAK5uy_"));
+        assert_eq!(PlaylistKind::Other.prefix(), None);
+    }
+
+    #[test]
+    fn test_playlist_kind_unsupported_reason() {
+        assert!(PlaylistKind::Mix.unsupported_reason(13).is_some());
+        assert!(PlaylistKind::Playlist.unsupported_reason(34).is_none());
+        assert!(PlaylistKind::Uploads.unsupported_reason(24).is_none());
+        // `LL`のみは自分の高く評価した動画
+        assert!(PlaylistKind::Liked.unsupported_reason(2).is_some());
+        assert!(PlaylistKind::Liked.unsupported_reason(24).is_none());
+    }
+
+    #[test]
+    fn test_playlist_kind_accepts_legacy_len() {
+        assert_eq!(
+            PlaylistKind::from_id("EC1234567890123456"),
+            PlaylistKind::Other
+        );
+        assert!(PlaylistKind::Other.accepts_len(18));
+        assert!(PlaylistKind::Other.accepts_len(34));
+        assert!(!PlaylistKind::Other.accepts_len(20));
+    }
+}
@@ -1,9 +1,47 @@
 use std::sync::LazyLock;
 
+use crate::{ParseError, ParseErrorKind};
+
 /// return value includes `0-9`, `A-Z`, `a-z`, `-`, `_`
 pub(super) static VALID_ASCII_CHARS: LazyLock<Vec<char>> =
     LazyLock::new(valid_ascii_chars);
 
+/// `id`が`VALID_ASCII_CHARS`のみからなる`len`文字であることを確認する
+///
+/// 使用できない文字を含むときはその文字を, そうでなく長さが正しくないときは`id`全体をエラーとする
+pub(super) fn validate_id(
+    id: &str,
+    len: usize,
+    expected: &str,
+) -> Result<(), ParseError> {
+    validate_chars(id, expected)?;
+    if id.len() != len {
+        return Err(ParseError::whole(
+            ParseErrorKind::InvalidLength(id.len()),
+            id,
+            expected,
+        ));
+    }
+    Ok(())
+}
+
+/// `id`が`VALID_ASCII_CHARS`のみからなることを確認する, 長さは確認しない
+pub(super) fn validate_chars(id: &str, expected: &str) -> Result<(), ParseError> {
+    if let Some((pos, c)) =
+        id.char_indices().find(|(_, c)| !VALID_ASCII_CHARS.contains(c))
+    {
+        let fragment = &id[pos..pos + c.len_utf8()];
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidChar(c),
+            id,
+            pos,
+            fragment,
+            expected,
+        ));
+    }
+    Ok(())
+}
+
 /// return value includes `0-9`, `A-Z`, `a-z`, `-`, `_`
 fn valid_ascii_chars() -> Vec<char> {
     let valid_range1 = 0x30..=0x39; // 0-9
@@ -38,6 +76,16 @@ mod tests {
         assert!(valid.into_iter().all(|c| valid_range.contains(&c)));
     }
 
+    #[test]
+    fn test_validate_id() {
+        assert!(validate_id("abc_-", 5, "").is_ok());
+        let err = validate_id("ab!de", 5, "").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidChar('!'));
+        assert_eq!((err.fragment.as_str(), err.position), ("!", 2));
+        let err = validate_id("abc", 5, "").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidLength(3));
+    }
+
     /// 無効な範囲外の文字を与える
     #[test]
     fn test_valid_ascii_gives_invalid() {
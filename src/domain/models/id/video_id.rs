@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-use super::valid_ascii::VALID_ASCII_CHARS;
+use super::valid_ascii::validate_id;
+use crate::ParseError;
 
 /// 動画のID
 ///
@@ -10,17 +11,18 @@ use super::valid_ascii::VALID_ASCII_CHARS;
 pub struct VideoId(String);
 
 impl VideoId {
-    pub fn new(id: String) -> Result<Self, crate::Error> {
-        if id.len() == 11 && id.chars().all(|c| VALID_ASCII_CHARS.contains(&c)) {
-            Ok(Self(id))
-        } else {
-            Err(crate::Error::InvalidInput)
-        }
+    /// 期待する形式, エラーの表示に使用する
+    pub const EXPECTED: &'static str =
+        "a video id of 11 characters (`0-9`, `A-Z`, `a-z`, `-`, `_`)";
+
+    pub fn new(id: String) -> Result<Self, ParseError> {
+        validate_id(&id, 11, Self::EXPECTED)?;
+        Ok(Self(id))
     }
 }
 
 impl FromStr for VideoId {
-    type Err = crate::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Self::new(s.to_string())
     }
@@ -4,6 +4,7 @@ use super::{
     playlist_data::{BasicPlaylistData, FullPlaylistData},
     video_data::{BasicVideoData, FullVideoData},
 };
+use crate::{url::Url, MalformedEntries};
 
 /// 動画の基本的なデータ
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
@@ -36,6 +37,19 @@ impl From<FullPlaylistData> for BasicData {
     }
 }
 
+impl MalformedEntries for BasicData {
+    fn malformed_entries(&self) -> Vec<(Url, String)> {
+        match self {
+            Self::Video(_) => Vec::new(),
+            Self::Playlist(pl) => pl
+                .malformed
+                .iter()
+                .map(|entry| entry.to_url_reason(&pl.playlist_data_itself.id))
+                .collect(),
+        }
+    }
+}
+
 /// 動画の詳細なデータ
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub enum FullData {
@@ -65,6 +79,7 @@ impl From<FullData> for BasicData {
                 Self::Playlist(BasicPlaylistData {
                     videos: pl.videos.into_iter().map(Into::into).collect(),
                     playlist_data_itself,
+                    malformed: Vec::new(),
                 })
             }
         }
@@ -1,7 +1,9 @@
 mod data;
 mod playlist_data;
 mod video_data;
+mod video_projection;
 
 pub use data::{BasicData, FullData};
 pub use playlist_data::{BasicPlaylistData, FullPlaylistData, PlaylistDataItself};
 pub use video_data::{BasicVideoData, FullVideoData, Live};
+pub use video_projection::{VideoField, VideoProjection};
@@ -2,7 +2,9 @@ use serde::Serialize;
 use std::collections::VecDeque;
 
 use super::video_data::{BasicVideoData, FullVideoData};
-use crate::id::PlaylistId;
+use crate::id::{PlaylistId, PlaylistKind, VideoId};
+use crate::url::Position;
+use crate::MalformedEntry;
 
 /// 再生リストの基本的な情報
 ///
@@ -14,6 +16,9 @@ use crate::id::PlaylistId;
 pub struct BasicPlaylistData {
     pub videos: VecDeque<BasicVideoData>,
     pub playlist_data_itself: PlaylistDataItself,
+    /// 解釈できなかったため`videos`に含めなかった項目, 出力には含めない
+    #[serde(skip)]
+    pub malformed: Vec<MalformedEntry>,
 }
 
 impl BasicPlaylistData {
@@ -22,8 +27,42 @@ impl BasicPlaylistData {
         Self {
             videos,
             playlist_data_itself: pl_itself,
+            malformed: Vec::new(),
         }
     }
+
+    pub fn with_malformed(mut self, malformed: Vec<MalformedEntry>) -> Self {
+        self.malformed = malformed;
+        self
+    }
+
+    /// 入力の`url`で指定された位置を保持する
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.playlist_data_itself.position = position;
+        self
+    }
+
+    /// 入力の`url`で指定された動画を保持し, 一致する動画に`selected`を付ける
+    ///
+    /// `with_position`の後に呼ぶ, `index`の位置の動画が一致すればその動画, そうでなければ最初に一致した動画に付け,
+    /// 再生を始める位置を引き継ぐ
+    pub fn with_selected_video(mut self, video_id: Option<VideoId>) -> Self {
+        let position = self.playlist_data_itself.position;
+        if let Some(id) = &video_id {
+            let at_index = position
+                .index
+                .and_then(|index| (index as usize).checked_sub(1))
+                .filter(|&i| self.videos.get(i).is_some_and(|v| &v.id == id));
+            let found =
+                at_index.or_else(|| self.videos.iter().position(|v| &v.id == id));
+            if let Some(video) = found.and_then(|i| self.videos.get_mut(i)) {
+                video.selected = true;
+                video.start = position.start;
+            }
+        }
+        self.playlist_data_itself.selected_video = video_id;
+        self
+    }
 }
 
 /// 再生リストの詳細な情報
@@ -50,11 +89,13 @@ impl FullPlaylistData {
 
 impl From<FullPlaylistData> for BasicPlaylistData {
     fn from(value: FullPlaylistData) -> Self {
-        let pl_id = value.playlist_data_itself.id.clone();
+        let pl_itself = value.playlist_data_itself;
         BasicPlaylistData::new(
             value.videos.into_iter().map(Into::into).collect(),
-            pl_id,
+            pl_itself.id,
         )
+        .with_position(pl_itself.position)
+        .with_selected_video(pl_itself.selected_video)
     }
 }
 
@@ -63,13 +104,27 @@ impl From<FullPlaylistData> for BasicPlaylistData {
 pub struct PlaylistDataItself {
     /// 再生リスト自体のid
     pub id: PlaylistId,
+    /// `id`の接頭語から分かる再生リストの種類
+    pub kind: PlaylistKind,
     /// その再生リストが保持している有効な動画の数, 非公開や削除済みは含まない
     pub total: usize,
+    /// 入力の`url`で指定された位置, `start`と`index`として出力する
+    #[serde(flatten)]
+    pub position: Position,
+    /// 入力の`url`で指定された動画(`watch?v=(id)&list=(pl)`), 再生リストに含まれないこともある
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_video: Option<VideoId>,
 }
 
 impl PlaylistDataItself {
     pub fn new(id: PlaylistId, total: usize) -> Self {
-        Self { id, total }
+        Self {
+            kind: id.kind(),
+            id,
+            total,
+            position: Position::default(),
+            selected_video: None,
+        }
     }
 }
 
@@ -81,6 +136,7 @@ impl BasicPlaylistData {
         Self {
             videos,
             playlist_data_itself,
+            malformed: Vec::new(),
         }
     }
 
@@ -90,6 +146,7 @@ impl BasicPlaylistData {
         Self {
             videos,
             playlist_data_itself,
+            malformed: Vec::new(),
         }
     }
 
@@ -99,6 +156,7 @@ impl BasicPlaylistData {
         Self {
             videos: videos_length_0,
             playlist_data_itself,
+            malformed: Vec::new(),
         }
     }
 }
@@ -136,17 +194,11 @@ impl FullPlaylistData {
 #[cfg(test)]
 impl PlaylistDataItself {
     pub(crate) fn self_1() -> Self {
-        Self {
-            id: PlaylistId::all_1(),
-            total: 1,
-        }
+        Self::new(PlaylistId::all_1(), 1)
     }
 
     pub(crate) fn self_2() -> Self {
-        Self {
-            id: PlaylistId::all_2(),
-            total: 2,
-        }
+        Self::new(PlaylistId::all_2(), 2)
     }
 }
 
@@ -206,4 +258,31 @@ mod tests {
         let basic = BasicPlaylistData::self_1();
         assert_eq!(BasicPlaylistData::from(full), basic);
     }
+
+    #[test]
+    fn test_with_selected_video_prefers_index() {
+        let videos: VecDeque<_> =
+            vec![BasicVideoData::self_1(), BasicVideoData::self_1()].into();
+        let selected = |index| {
+            BasicPlaylistData::new(videos.clone(), PlaylistId::all_1())
+                .with_position(Position::new(None, index))
+                .with_selected_video(Some(VideoId::all_1()))
+                .videos
+                .iter()
+                .map(|v| v.selected)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(selected(Some(2)), vec![false, true]);
+        // `index`の位置の動画が一致しないときは最初に一致した動画
+        assert_eq!(selected(Some(5)), vec![true, false]);
+        assert_eq!(selected(None), vec![true, false]);
+
+        let data =
+            BasicPlaylistData::self_1().with_selected_video(Some(VideoId::all_2()));
+        assert!(data.videos.iter().all(|v| !v.selected));
+        assert_eq!(
+            data.playlist_data_itself.selected_video,
+            Some(VideoId::all_2())
+        );
+    }
 }
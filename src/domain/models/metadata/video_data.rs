@@ -1,24 +1,39 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fmt::Debug;
 
 use crate::id::VideoId;
 
 /// 動画の基本的な情報
+///
+/// 取得時に`fields`で除外した項目はデフォルト値(空文字列, `None`など)になる
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub struct BasicVideoData {
     pub id: VideoId,
-    pub upload_at: DateTime<Utc>,
+    /// 公開時刻, レスポンスに含まれなかったときは`None`
+    pub upload_at: Option<DateTime<Utc>>,
     pub title: String,
     pub description: String,
     pub channel_id: String,
     pub channel_title: String,
+    /// 入力の`url`で指定された再生を始める位置(秒), `t`, `start`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<u32>,
+    /// 再生リスト内の動画のうち, 入力の`url`で指定された動画のとき`true`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub selected: bool,
+    /// APIから返されたそのままの`item`, キーはエンドポイント名(`videos`, `playlistItems`)
+    ///
+    /// 取得時に指定したときのみ保持する
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Map<String, Value>>,
 }
 
 impl BasicVideoData {
     pub fn new(
         id: VideoId,
-        upload_at: DateTime<Utc>,
+        upload_at: Option<DateTime<Utc>>,
         title: String,
         description: String,
         channel_id: String,
@@ -31,8 +46,22 @@ impl BasicVideoData {
             description,
             channel_id,
             channel_title,
+            start: None,
+            selected: false,
+            raw: None,
         }
     }
+
+    /// 入力の`url`で指定された再生を始める位置を保持する
+    pub fn with_start(mut self, start: Option<u32>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// `endpoint`から返されたそのままの`item`を保持する
+    pub fn insert_raw(&mut self, endpoint: &str, item: Value) {
+        self.raw.get_or_insert_with(Map::new).insert(endpoint.into(), item);
+    }
 }
 
 /// 動画の詳細な情報
@@ -48,7 +77,7 @@ pub struct FullVideoData {
 impl FullVideoData {
     pub fn new(
         id: VideoId,
-        upload_at: DateTime<Utc>,
+        upload_at: Option<DateTime<Utc>>,
         title: String,
         description: String,
         channel_id: String,
@@ -79,11 +108,15 @@ impl<'a> From<&'a FullVideoData> for &'a BasicVideoData {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+/// 動画の公開状況
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub enum Live {
     Live,
     Upcoming,
     Published,
+    /// 上記以外, APIから想定していない値が返されたときはその値を保持する
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[cfg(test)]
@@ -94,7 +127,7 @@ impl BasicVideoData {
     pub(crate) fn self_1() -> Self {
         BasicVideoData::new(
             VideoId::all_1(),
-            Utc.with_ymd_and_hms(2024, 1, 1, 1, 1, 1).unwrap(),
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 1, 1, 1).unwrap()),
             "title_1".into(),
             "description_1".into(),
             "channel_id_1".into(),
@@ -102,10 +135,10 @@ impl BasicVideoData {
         )
     }
 
-    pub(super) fn self_2() -> Self {
+    pub(crate) fn self_2() -> Self {
         BasicVideoData::new(
             VideoId::all_2(),
-            Utc.with_ymd_and_hms(2024, 2, 2, 2, 2, 2).unwrap(),
+            Some(Utc.with_ymd_and_hms(2024, 2, 2, 2, 2, 2).unwrap()),
             "title_2".into(),
             "description_2".into(),
             "channel_id_2".into(),
@@ -157,7 +190,7 @@ mod tests {
         assert_eq!(b_v_data.channel_title, "channel_title_1");
         assert_eq!(
             b_v_data.upload_at,
-            Utc.with_ymd_and_hms(2024, 1, 1, 1, 1, 1).unwrap()
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 1, 1, 1).unwrap())
         );
     }
 
@@ -171,7 +204,7 @@ mod tests {
         assert_eq!(b_v_data.channel_title, "channel_title_2");
         assert_eq!(
             b_v_data.upload_at,
-            Utc.with_ymd_and_hms(2024, 2, 2, 2, 2, 2).unwrap()
+            Some(Utc.with_ymd_and_hms(2024, 2, 2, 2, 2, 2).unwrap())
         );
     }
 
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt::Display, str::FromStr};
+
+use super::data::BasicData;
+
+/// 出力する`BasicVideoData`の項目
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoField {
+    Id,
+    UploadAt,
+    Title,
+    Description,
+    ChannelId,
+    ChannelTitle,
+}
+
+impl VideoField {
+    pub const ALL: [VideoField; 6] = [
+        Self::Id,
+        Self::UploadAt,
+        Self::Title,
+        Self::Description,
+        Self::ChannelId,
+        Self::ChannelTitle,
+    ];
+
+    /// シリアライズしたときの`BasicVideoData`のキー
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::UploadAt => "upload_at",
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::ChannelId => "channel_id",
+            Self::ChannelTitle => "channel_title",
+        }
+    }
+}
+
+impl FromStr for VideoField {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|field| field.name() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|f| f.name()).collect();
+            format!(
+                "invalid field:`{}`, expected one of `{}`",
+                s,
+                names.join(", ")
+            )
+        })
+    }
+}
+
+impl Display for VideoField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// 出力する動画の項目の組み合わせ
+///
+/// 含まれない項目は出力から取り除く
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VideoProjection(Vec<VideoField>);
+
+impl VideoProjection {
+    /// 重複は取り除く, 空のときは全ての項目を出力する
+    pub fn new(fields: Vec<VideoField>) -> Self {
+        if fields.is_empty() {
+            return Self::default();
+        }
+        let mut unique: Vec<VideoField> = Vec::new();
+        for field in fields {
+            if !unique.contains(&field) {
+                unique.push(field);
+            }
+        }
+        Self(unique)
+    }
+
+    pub fn contains(&self, field: VideoField) -> bool {
+        self.0.contains(&field)
+    }
+
+    pub fn fields(&self) -> &[VideoField] {
+        &self.0
+    }
+
+    /// 全ての項目を出力するかどうか
+    pub fn is_all(&self) -> bool {
+        VideoField::ALL.iter().all(|field| self.contains(*field))
+    }
+
+    /// `data`をシリアライズし, 含まれない項目を各動画から取り除く
+    pub fn apply(&self, data: &BasicData) -> Value {
+        let mut value =
+            serde_json::to_value(data).expect("`BasicData` is always serializable");
+        match data {
+            BasicData::Video(_) => self.retain(&mut value["Video"]),
+            BasicData::Playlist(_) => {
+                if let Some(videos) = value["Playlist"]["videos"].as_array_mut() {
+                    videos.iter_mut().for_each(|video| self.retain(video));
+                }
+            }
+        }
+        value
+    }
+
+    /// 保持している場合`raw`と入力の`url`から得た`start`, `selected`は常に残す
+    fn retain(&self, video: &mut Value) {
+        if let Some(video) = video.as_object_mut() {
+            video.retain(|key, _| {
+                matches!(key.as_str(), "raw" | "start" | "selected")
+                    || self.0.iter().any(|field| field.name() == key)
+            });
+        }
+    }
+}
+
+impl Default for VideoProjection {
+    fn default() -> Self {
+        Self(VideoField::ALL.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::BasicVideoData;
+
+    #[test]
+    fn test_video_field_name_matches_serialized_key() {
+        let value = serde_json::to_value(BasicVideoData::self_1()).unwrap();
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys.len(), VideoField::ALL.len());
+        for field in VideoField::ALL {
+            assert!(keys.contains(&&field.name().to_string()), "{}", field);
+            assert_eq!(VideoField::from_str(field.name()), Ok(field));
+        }
+        assert!(VideoField::from_str("titel").is_err());
+    }
+
+    #[test]
+    fn test_video_projection_new() {
+        assert!(VideoProjection::new(vec![]).is_all());
+        let projection =
+            VideoProjection::new(vec![VideoField::Title, VideoField::Title]);
+        assert_eq!(projection.fields(), &[VideoField::Title]);
+        assert!(!projection.is_all());
+    }
+
+    #[test]
+    fn test_video_projection_apply() {
+        let projection = VideoProjection::new(vec![VideoField::Id, VideoField::Title]);
+
+        let value = projection.apply(&BasicData::holds_video());
+        assert_eq!(
+            value,
+            serde_json::json!({"Video": {"id": "11111111111", "title": "title_1"}})
+        );
+
+        let value = projection.apply(&BasicData::holds_playlist());
+        let videos = value["Playlist"]["videos"].as_array().unwrap();
+        assert_eq!(videos.len(), 2);
+        assert_eq!(
+            videos[1],
+            serde_json::json!({"id": "22222222222", "title": "title_2"})
+        );
+        // 再生リスト自体の情報は取り除かない
+        assert_eq!(value["Playlist"]["playlist_data_itself"]["total"], 2);
+        assert_eq!(value["Playlist"]["playlist_data_itself"]["kind"], "other");
+
+        // `raw`は取り除かない
+        let mut video = BasicVideoData::self_1();
+        video.insert_raw("videos", serde_json::json!({"kind": "youtube#video"}));
+        let value = projection.apply(&video.into());
+        assert_eq!(
+            value["Video"]["raw"],
+            serde_json::json!({"videos": {"kind": "youtube#video"}})
+        );
+    }
+}
@@ -1,6 +1,14 @@
+mod annotated;
+mod existence;
+mod fetched_results;
 mod handle;
 pub mod id;
 pub mod metadata;
 pub mod url;
 
+pub use annotated::{Annotated, Annotator};
+pub use existence::{Existence, ItemExistence, UrlExistence};
+pub use fetched_results::{
+    FetchedItem, FetchedResults, MalformedEntries, MalformedEntry,
+};
 pub use handle::Handle;
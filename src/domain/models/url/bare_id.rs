@@ -0,0 +1,198 @@
+use super::super::{
+    id::{ChannelId, PlaylistId, PlaylistKind, VideoId},
+    Handle,
+};
+use super::{Url, UrlPlaylist, UrlVideo};
+use crate::{ParseError, ParseErrorKind};
+
+/// 期待する形式, エラーの表示に使用する
+const EXPECTED: &str =
+    "a youtube url, a video id (11), a playlist id (`PL`, `UU`, ... or 34), a channel id (`UC`) or a handle (`@`)";
+
+/// 取得に対応していない入力の期待する形式
+const EXPECTED_FETCHABLE: &str =
+    "a video or playlist, channel ids and handles cannot be fetched yet";
+
+/// `url`を省略した入力をどこまで推測するか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdStrictness {
+    /// 形から一通りに決まらない文字列は`@`の無いハンドルとみなす
+    #[default]
+    Lenient,
+    /// 形から一通りに決まらない文字列は`Ambiguous`とする
+    Strict,
+}
+
+/// `url`またはIDとして解釈した入力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum BareId {
+    Url(Url),
+    Channel(ChannelId),
+    Handle(Handle),
+}
+
+/// `/`を含むときは`url`, それ以外は以下の順に解釈する
+///
+/// - `@`から始まる: ハンドル
+/// - `UC`から始まる24文字: チャンネルのID
+/// - 11文字: 動画のID
+/// - 接頭語(`PlaylistKind`)に対応する長さ: 再生リストのID
+/// - それ以外: `Lenient`のときは`@`の無いハンドル, `Strict`のときはエラー
+///
+/// ハンドルとみなすのはハンドルに使える文字(英数字と`._-`)のみのときで,
+/// それ以外の文字を含むときはその文字のエラーとする
+///
+/// 長さが一致するときは, そのIDとして無効であってもほかの解釈はしない
+///
+/// 動画と再生リストのどちらの長さにも一致するとき(`RD`と9文字など),
+/// `Lenient`のときは動画, `Strict`のときはエラーとする
+pub(super) fn parse_bare(
+    input: &str,
+    strictness: IdStrictness,
+) -> Result<BareId, ParseError> {
+    if input.contains('/') {
+        return Url::new(input.into()).map(BareId::Url);
+    }
+    if input.starts_with('@') {
+        return Handle::new(input.into()).map(BareId::Handle);
+    }
+    if input.len() == 24 && input.starts_with("UC") {
+        return ChannelId::new(input.into()).map(BareId::Channel);
+    }
+    let as_video = input.len() == 11;
+    let as_playlist = PlaylistKind::from_id(input).accepts_len(input.len());
+    match (as_video, as_playlist, strictness) {
+        (true, true, IdStrictness::Strict) | (false, false, IdStrictness::Strict) => {
+            Err(ParseError::whole(
+                ParseErrorKind::Ambiguous,
+                input,
+                EXPECTED,
+            ))
+        }
+        (true, _, _) => {
+            VideoId::new(input.into()).map(|id| BareId::Url(UrlVideo::from(id).into()))
+        }
+        (false, true, _) => PlaylistId::new(input.into())
+            .map(|id| BareId::Url(UrlPlaylist::new(None, id).into())),
+        (false, false, IdStrictness::Lenient) => {
+            match input.char_indices().find(|(_, c)| !is_handle_char(*c)) {
+                Some((pos, c)) => Err(ParseError::new(
+                    ParseErrorKind::InvalidChar(c),
+                    input,
+                    pos,
+                    &input[pos..pos + c.len_utf8()],
+                    EXPECTED,
+                )),
+                None => Handle::new(input.into())
+                    .map(BareId::Handle)
+                    .map_err(|e| ParseError::whole(e.kind, input, EXPECTED)),
+            }
+        }
+    }
+}
+
+/// ハンドルに使える文字, 英字以外の文字も含む
+fn is_handle_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+/// 取得できる`Url`のみを返す, チャンネルとハンドルは`Unsupported`
+pub(super) fn parse_fetchable(
+    input: &str,
+    strictness: IdStrictness,
+) -> Result<Url, ParseError> {
+    match parse_bare(input, strictness)? {
+        BareId::Url(url) => Ok(url),
+        BareId::Channel(_) | BareId::Handle(_) => Err(ParseError::whole(
+            ParseErrorKind::Unsupported,
+            input,
+            EXPECTED_FETCHABLE,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNEL: &str = "UCaaaa-bbbb-cccc-dddd-ee";
+
+    #[test]
+    fn test_parse_bare_ids() {
+        let lenient = IdStrictness::Lenient;
+        assert_eq!(
+            parse_bare("12345678901", lenient),
+            Ok(BareId::Url(Url::v_inc_from_1()))
+        );
+        assert_eq!(
+            parse_bare("1234567890123456789012345678901234", lenient),
+            Ok(BareId::Url(Url::pl_inc_from_1()))
+        );
+        let album = "OLAK5uy_aaaa-bbbb-cccc-dddd-eeee-ffff-ggg";
+        assert_eq!(
+            parse_bare(album, lenient),
+            Ok(BareId::Url(
+                UrlPlaylist::new(None, PlaylistId::new(album.into()).unwrap()).into()
+            ))
+        );
+        assert_eq!(
+            parse_bare("https://youtu.be/12345678901", lenient),
+            Ok(BareId::Url(Url::v_inc_from_1()))
+        );
+        assert_eq!(
+            parse_bare(CHANNEL, lenient),
+            Ok(BareId::Channel(ChannelId::new(CHANNEL.into()).unwrap()))
+        );
+        assert_eq!(
+            parse_bare("@foo_bar", lenient),
+            Ok(BareId::Handle(Handle::new("foo_bar".into()).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_parse_bare_strictness() {
+        // `@`の無いハンドルは推測する
+        assert_eq!(
+            parse_bare("foo_bar", IdStrictness::Lenient),
+            Ok(BareId::Handle(Handle::new("foo_bar".into()).unwrap()))
+        );
+        // ハンドルに使えない文字を含むときはその文字のエラー
+        let err = parse_bare("watch?v=abcd", IdStrictness::Lenient).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidChar('?'));
+        assert_eq!((err.fragment.as_str(), err.position), ("?", 5));
+        assert_eq!(err.expected, EXPECTED);
+
+        let err = parse_bare("foo_bar", IdStrictness::Strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Ambiguous);
+        assert_eq!(err.expected, EXPECTED);
+
+        // 動画と再生リストのどちらとも読める
+        assert_eq!(
+            parse_bare("RD123456789", IdStrictness::Lenient),
+            Ok(BareId::Url(
+                UrlVideo::from(VideoId::new("RD123456789".into()).unwrap()).into()
+            ))
+        );
+        let err = parse_bare("RD123456789", IdStrictness::Strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Ambiguous);
+
+        // 長さが一致するときはIDとしてのエラー
+        let err = parse_bare("1234567890!", IdStrictness::Lenient).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidChar('!'));
+        assert_eq!(err.expected, VideoId::EXPECTED);
+    }
+
+    #[test]
+    fn test_parse_fetchable() {
+        let strict = IdStrictness::Strict;
+        assert_eq!(
+            parse_fetchable("12345678901", strict),
+            Ok(Url::v_inc_from_1())
+        );
+        for input in [CHANNEL, "@foo_bar"] {
+            let err = parse_fetchable(input, strict).unwrap_err();
+            assert_eq!(err.kind, ParseErrorKind::Unsupported);
+            assert_eq!(err.input, input);
+        }
+    }
+}
@@ -1,13 +1,16 @@
 use super::super::id::{id_type::IdType, PlaylistId, VideoId};
+use super::Position;
 
 pub(super) const BASE_URL: &str = "https://www.youtube.com";
 
-pub(super) fn build_url(id: IdType) -> String {
-    match id {
+/// `position`は空でないときのみクエリの末尾に付ける
+pub(super) fn build_url(id: IdType, position: Position) -> String {
+    let url = match id {
         IdType::Video(v) => build_video_url(v),
         IdType::Playlist(pl) => build_playlist_url(pl),
         IdType::VideoPlaylist(v, pl) => build_video_playlist_url(v, pl),
-    }
+    };
+    format!("{}{}", url, position.to_query())
 }
 
 fn build_video_url(v_id: VideoId) -> String {
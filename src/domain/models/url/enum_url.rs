@@ -1,10 +1,14 @@
-use std::collections::VecDeque;
-use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
 
-use regex::Regex;
-
-use super::super::id::{id_type::IdType, PlaylistId, VideoId};
-use super::{UrlPlaylist, UrlVideo};
+use super::super::id::id_type::IdType;
+#[cfg(test)]
+use super::super::id::{PlaylistId, VideoId};
+use super::{
+    bare_id, extract, parse, ExtractedUrl, IdStrictness, Position, UrlPlaylist,
+    UrlVideo,
+};
+use crate::ParseError;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum Url {
@@ -13,51 +17,51 @@ pub enum Url {
 }
 
 impl Url {
-    pub fn new(s: String) -> Result<Self, crate::Error> {
-        // `v=`や`list=`などの指定に対応する`id`が取得できなければエラーを返す
-        //
-        // e.g. ~.com/watch?v=1234567890
-        // このとき`id`の文字数が足りず、`id`を求める正規表現にはマッチしないが
-        // `v=`が存在するので`id`が無いのはurlに誤りがあると判断
-        let must_include_id = |id: bool, must_include: bool| {
-            if must_include && !id {
-                Err(crate::Error::InvalidInput)
-            } else {
-                Ok(())
-            }
-        };
-
-        // [0] https://www.youtube.com/watch?v=(id)
-        // [1] https://www.youtube.com/playlist?list=(pl)
-        // [2] https://www.youtube.com/watch?v=(id)&list=(pl)
-        // [3] https://youtu.be/(id)
-        // [4] https://youtu.be/(id)&list=(pl)
-        // [5] https://www.youtube.com/shorts/(id)
-
-        // [0][2..=5]から抽出
-        let re_video_id = Regex::new(Self::RE_V_ID_VAL).unwrap();
-        let v_id: Option<VideoId> = Self::extract_id(re_video_id, &s)?;
-        let re_must_include_video_id = Regex::new(Self::RE_V_ID_KEY).unwrap();
-        must_include_id(v_id.is_some(), re_must_include_video_id.is_match(&s))?;
+    /// 以下の形式に対応する, スキームは省略できる
+    ///
+    /// - `(www.|m.|music.)youtube.com/watch?v=(id)&list=(pl)`
+    /// - `youtube.com/playlist?list=(pl)`
+    /// - `youtube.com/(shorts|embed|live|v|e)/(id)`
+    /// - `youtube-nocookie.com/embed/(id)`
+    /// - `youtube.com/attribution_link?u=(url)`
+    /// - `youtu.be/(id)?list=(pl)`
+    ///
+    /// `t`, `start`, `index`は`Position`として保持し, `si`, `pp`, `feature`などのクエリは無視する
+    pub fn new(s: String) -> Result<Self, ParseError> {
+        parse::parse(&s).map(|(id, position)| Url::from(id).with_position(position))
+    }
 
-        // [1][4]から抽出
-        let re_pl_id = Regex::new(Self::RE_PL_ID_VAL).unwrap();
-        let pl_id: Option<PlaylistId> = Self::extract_id(re_pl_id, &s)?;
-        let re_must_include_pl_id = Regex::new(Self::RE_PL_ID_KEY).unwrap();
-        must_include_id(pl_id.is_some(), re_must_include_pl_id.is_match(&s))?;
+    /// `url`に加えて, `url`を省略した動画と再生リストのIDを受け付ける
+    ///
+    /// チャンネルのID(`UC...`)とハンドル(`@...`)は解釈できるが, 取得に対応していないのでエラー
+    ///
+    /// 形から一通りに決まらない文字列の扱いは`strictness`による
+    pub fn from_input(s: &str, strictness: IdStrictness) -> Result<Self, ParseError> {
+        bare_id::parse_fetchable(s, strictness)
+    }
 
-        // urlに誤りがあり、どちらのidも含まれていない(どちらの正規表現にもマッチしない)とき
-        let id_type = IdType::new(v_id, pl_id)?;
+    /// チャットの記録, Markdown, HTMLなどの文章から対応する形式の`url`を全て探す
+    ///
+    /// 重複は取り除き, それぞれ最初に現れた行番号とともに返す
+    pub fn extract(text: &str) -> Vec<ExtractedUrl> {
+        extract::extract(text)
+    }
 
-        // NOTE `id`の文字数が規定数**以上**のとき、判定できないが一旦無視
-        Ok(id_type.into())
+    /// 動画のみの`url`では再生リスト内の位置(`index`)は意味を持たないので捨てる
+    pub fn with_position(self, position: Position) -> Self {
+        match self {
+            Self::Video(v) => v.with_start(position.start).into(),
+            Self::Playlist(pl) => pl.with_position(position).into(),
+        }
     }
 
-    const RE_V_ID_VAL: &str =
-        r"(?:(?:shorts/)|(?:v=)|(?:youtu\.be/))([0-9A-Za-z_-]{11})";
-    const RE_V_ID_KEY: &str = r"shorts/|v=|youtu.be/";
-    const RE_PL_ID_VAL: &str = r"(?:list=)([0-9A-Za-z_-]{34})";
-    const RE_PL_ID_KEY: &str = r"list=";
+    /// 再生を始める位置(秒)と再生リスト内の位置
+    pub fn position(&self) -> Position {
+        match self {
+            Self::Video(v) => Position::new(v.start(), None),
+            Self::Playlist(pl) => pl.position(),
+        }
+    }
 
     pub fn build_url(&self) -> String {
         match self {
@@ -66,27 +70,12 @@ impl Url {
         }
     }
 
-    /// 引数のurlに引数の正規表現を使用し、キャプチャグループの1番目の要素を返す
-    ///
-    /// 見つからないと`Ok(None)`を返す
-    ///
-    /// 見つかると`try_into`を適用し、`try_into`の戻り値の`Result`を露出させて返す
-    fn extract_id<T>(re: Regex, url: &str) -> Result<Option<T>, crate::Error>
-    where
-        T: FromStr<Err = crate::Error>,
-    {
-        re.captures(url)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str()))
-            .map(|s| T::from_str(s))
-            .transpose()
-    }
-
     pub fn separate_urls(
         mut urls: VecDeque<Url>,
     ) -> (VecDeque<UrlVideo>, VecDeque<UrlPlaylist>) {
         let mut multi_v: VecDeque<UrlVideo> = VecDeque::new();
         let mut multi_pl: VecDeque<UrlPlaylist> = VecDeque::new();
-        if let Some(url) = urls.pop_front() {
+        while let Some(url) = urls.pop_front() {
             match url {
                 Self::Video(v) => multi_v.push_back(v),
                 Self::Playlist(pl) => multi_pl.push_back(pl),
@@ -96,6 +85,41 @@ impl Url {
     }
 }
 
+/// 対応する全ての形式の`url`と, 一通りに決まる動画と再生リストのID(`IdStrictness::Strict`)を受け付ける
+impl FromStr for Url {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s.trim(), IdStrictness::Strict)
+    }
+}
+
+/// `build_url`の形式
+impl Display for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.build_url())
+    }
+}
+
+/// `build_url`の文字列
+impl Serialize for Url {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Url {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let url = String::deserialize(deserializer)?;
+        url.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<IdType> for Url {
     fn from(value: IdType) -> Self {
         match value {
@@ -146,6 +170,7 @@ impl Url {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ParseErrorKind;
 
     #[test]
     fn test_url_for_test_fn() {
@@ -198,6 +223,88 @@ mod tests {
         assert_eq!(Url::new(url.clone()), Ok(expect_url.clone()));
     }
 
+    /// `t`と`index`を保持し, `build_url`で再び付ける
+    #[test]
+    fn test_url_keeps_position() {
+        let url =
+            Url::new("https://youtu.be/12345678901?t=1h2m3s&si=x".into()).unwrap();
+        assert_eq!(url.position(), Position::new(Some(3723), None));
+        assert_eq!(
+            url.build_url(),
+            "https://www.youtube.com/watch?v=12345678901&t=3723s"
+        );
+        assert_ne!(url, Url::v_inc_from_1());
+
+        let url = Url::new(format!(
+            "{}{}",
+            "https://www.youtube.com/watch?v=12345678901&start=5&index=3",
+            "&list=1234567890123456789012345678901234"
+        ))
+        .unwrap();
+        assert_eq!(
+            url.build_url(),
+            format!(
+                "{}{}",
+                "https://www.youtube.com/watch?v=12345678901",
+                "&list=1234567890123456789012345678901234&index=3&t=5s"
+            )
+        );
+        assert_eq!(Url::new(url.build_url()), Ok(url));
+
+        // 動画のみの`url`では`index`を捨てる
+        let url =
+            Url::new("https://www.youtube.com/watch?v=12345678901&index=3".into());
+        assert_eq!(url, Ok(Url::v_inc_from_1()));
+    }
+
+    #[test]
+    fn test_url_from_str_and_serde() {
+        let v_pl = Url::v_pl_inc_from_1().with_position(Position::new(Some(90), None));
+        for input in [
+            "https://youtu.be/12345678901?list=1234567890123456789012345678901234&t=1m30s",
+            "  m.youtube.com/watch?v=12345678901&list=1234567890123456789012345678901234&t=90 ",
+        ] {
+            assert_eq!(input.parse::<Url>(), Ok(v_pl.clone()));
+        }
+        assert_eq!("12345678901".parse::<Url>(), Ok(Url::v_inc_from_1()));
+        assert_eq!(
+            "1234567890123456789012345678901234".parse::<Url>(),
+            Ok(Url::pl_inc_from_1())
+        );
+        // 推測はしない
+        assert!("handle".parse::<Url>().is_err());
+
+        assert_eq!(v_pl.to_string(), v_pl.build_url());
+        let json =
+            serde_json::to_string(&vec![v_pl.clone(), Url::v_inc_from_1()]).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "[\"{}\",\"https://www.youtube.com/watch?v=12345678901\"]",
+                v_pl.build_url()
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<Url>>(&json).unwrap(),
+            vec![v_pl, Url::v_inc_from_1()]
+        );
+        let err = serde_json::from_str::<Url>("\"https://example.com\"").unwrap_err();
+        assert!(err.to_string().starts_with("unsupported host"));
+    }
+
+    #[test]
+    fn test_url_separate_urls() {
+        let urls: VecDeque<Url> = vec![
+            Url::v_inc_from_1(),
+            Url::pl_inc_from_1(),
+            Url::v_pl_inc_from_1(),
+        ]
+        .into();
+        let (multi_v, multi_pl) = Url::separate_urls(urls);
+        assert_eq!(multi_v.len(), 1);
+        assert_eq!(multi_pl.len(), 2);
+    }
+
     #[test]
     fn test_url_gives_invalid() {
         // 動画idの指定が空文字列
@@ -218,4 +325,35 @@ mod tests {
         let invalid_url = "https://youtu.be".to_string();
         assert!(Url::new(invalid_url).is_err());
     }
+
+    #[test]
+    fn test_url_parse_error_details() {
+        let err = Url::new("https://www.youtube.com/watch?v=".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingValue);
+        assert_eq!((err.fragment.as_str(), err.position), ("v=", 30));
+
+        let err = Url::new("https://youtu.be/123&list=x".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidLength(3));
+        assert_eq!((err.fragment.as_str(), err.position), ("123", 17));
+        assert_eq!(err.expected, VideoId::EXPECTED);
+
+        let err = Url::new("https://youtu.be/1234567890!".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidChar('!'));
+        assert_eq!(err.position, 27);
+
+        let err =
+            Url::new("https://example.com/watch?v=12345678901".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnsupportedHost);
+        assert_eq!((err.fragment.as_str(), err.position), ("example.com", 8));
+
+        let err = Url::new("https://www.youtube.com/".into()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingId);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "no video id or playlist id at column 1: `https://www.youtube.com/`, expected {}",
+                parse::EXPECTED
+            )
+        );
+    }
 }
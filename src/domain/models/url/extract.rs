@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use super::{parse::percent_decode, Url};
+
+/// 文章中で`url`のホストとして探す文字列, 小文字
+const HOSTS: [&str; 3] = ["youtube.com", "youtu.be", "youtube-nocookie.com"];
+
+/// ホストの前に付けられるサブドメイン, 小文字
+const SUBDOMAINS: [&str; 3] = ["www.", "m.", "music."];
+
+/// 空白文字のほかに語を区切る文字, 引用符, 括弧など`url`に含まれないもの
+const DELIMITERS: [char; 13] = [
+    '"', '\'', '`', '<', '>', '(', ')', '[', ']', '{', '}', '|', ',',
+];
+
+/// `url`の末尾に続きやすい句読点, 動画のIDに使われない文字のみ
+const TRAILING: [char; 7] = ['.', ',', ';', ':', '!', '?', '*'];
+
+/// 二重にエンコードされたリダイレクトまで復号する
+const MAX_DECODE_DEPTH: usize = 2;
+
+/// 文章から見つけた`url`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedUrl {
+    pub url: Url,
+    /// 1から始まる行番号, 同じ`url`が複数あるときは最初の行
+    pub line: usize,
+}
+
+/// `text`から`url`を全て探す, 重複は取り除き現れた順に返す
+///
+/// 空白文字, 引用符, 括弧などで区切った語ごとにホストを探し, ホストからその語の末尾までを`url`として解析する
+///
+/// - `href="..."`の`&amp;`は`&`として扱う
+/// - 解析できない語にエンコードされた`url`が含まれていれば(`google.com/url?q=https%3A%2F%2F...`など)復号して探す
+/// - `url`を省略したIDは誤検出が多いので探さない
+pub(super) fn extract(text: &str) -> Vec<ExtractedUrl> {
+    let mut seen = HashSet::new();
+    let mut extracted = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        for word in line.split(is_delimiter).filter(|word| !word.is_empty()) {
+            if let Some(url) = find_in_word(&word.replace("&amp;", "&"), 0) {
+                if seen.insert(url.clone()) {
+                    extracted.push(ExtractedUrl { url, line: i + 1 });
+                }
+            }
+        }
+    }
+    extracted
+}
+
+/// `url`に含まれない区切りの文字
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || DELIMITERS.contains(&c)
+}
+
+/// 語に含まれる最初の`url`, 解析できなければ復号して探す
+fn find_in_word(word: &str, depth: usize) -> Option<Url> {
+    let found = host_starts(word).into_iter().find_map(|start| {
+        let candidate = word[start..].trim_end_matches(TRAILING);
+        Url::new(candidate.into()).ok()
+    });
+    match found {
+        Some(url) => Some(url),
+        None if depth < MAX_DECODE_DEPTH && word.contains('%') => {
+            let decoded = percent_decode(word);
+            (decoded != word).then(|| find_in_word(&decoded, depth + 1))?
+        }
+        None => None,
+    }
+}
+
+/// ホストが現れる位置, サブドメインとスキームを含める
+///
+/// ホストの直前が英数字などのときは別のホストの一部とみなす(`notyoutube.com`, `%2Fyoutu.be`)
+fn host_starts(word: &str) -> Vec<usize> {
+    let lower = word.to_ascii_lowercase();
+    let mut starts = Vec::new();
+    for host in HOSTS {
+        for (pos, _) in lower.match_indices(host) {
+            let before = &lower[..pos];
+            let start = SUBDOMAINS
+                .iter()
+                .find(|sub| before.ends_with(*sub))
+                .map_or(pos, |sub| pos - sub.len());
+            let is_part = lower[..start].chars().next_back().is_some_and(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_')
+            });
+            if !is_part {
+                starts.push(scheme_start(&lower, start));
+            }
+        }
+    }
+    starts.sort_unstable();
+    starts
+}
+
+/// `https://`や`//`が前にあれば含める
+fn scheme_start(lower: &str, start: usize) -> usize {
+    let before = &lower[..start];
+    match before.strip_suffix("://") {
+        Some(rest) => {
+            let scheme_len =
+                rest.bytes().rev().take_while(|b| b.is_ascii_alphabetic()).count();
+            rest.len() - scheme_len
+        }
+        None if before.ends_with("//") => start - 2,
+        None => start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::url::Position;
+
+    const V: &str = "12345678901";
+    const PL: &str = "1234567890123456789012345678901234";
+
+    fn urls(text: &str) -> Vec<(Url, usize)> {
+        extract(text).into_iter().map(|e| (e.url, e.line)).collect()
+    }
+
+    #[test]
+    fn test_extract_from_text() {
+        let text = format!(
+            "12:00 <alice> look at https://youtu.be/{V}!\n\
+             12:01 <bob> also youtube.com/watch?v={V}&list={PL}, and again (https://youtu.be/{V}).\n\
+             12:02 <carol> notyoutube.com/watch?v={V} is not it, nor is {V}\n"
+        );
+        assert_eq!(
+            urls(&text),
+            vec![(Url::v_inc_from_1(), 1), (Url::v_pl_inc_from_1(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_extract_from_markdown_and_html() {
+        let text = format!(
+            "# notes\n\
+             - [video](https://www.youtube.com/watch?v={V}&t=90s)\n\
+             - <https://www.youtube.com/playlist?list={PL}>\n\
+             <a href=\"https://www.youtube.com/watch?v={V}&amp;list={PL}\">link</a>\n"
+        );
+        assert_eq!(
+            urls(&text),
+            vec![
+                (
+                    Url::v_inc_from_1().with_position(Position::new(Some(90), None)),
+                    2
+                ),
+                (Url::pl_inc_from_1(), 3),
+                (Url::v_pl_inc_from_1(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_from_redirects() {
+        let text = format!(
+            "https://www.google.com/url?q=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3D{V}&sa=D\n\
+             https://l.example.com/l.php?u=https%253A%252F%252Fyoutu.be%252F{V}%253Flist%253D{PL}\n\
+             https://www.youtube.com/attribution_link?u=%2Fplaylist%3Flist%3D{PL}\n"
+        );
+        assert_eq!(
+            urls(&text),
+            vec![
+                (Url::v_inc_from_1(), 1),
+                (Url::v_pl_inc_from_1(), 2),
+                (Url::pl_inc_from_1(), 3),
+            ]
+        );
+    }
+}
@@ -1,8 +1,17 @@
+mod bare_id;
 mod build_url;
 mod enum_url;
+mod extract;
+mod parse;
+mod playlist_policy;
 mod playlist_url;
+mod position;
 mod video_url;
 
+pub use bare_id::IdStrictness;
 pub use enum_url::Url;
+pub use extract::ExtractedUrl;
+pub use playlist_policy::PlaylistPolicy;
 pub use playlist_url::UrlPlaylist;
+pub use position::Position;
 pub use video_url::UrlVideo;
@@ -0,0 +1,523 @@
+use super::super::id::{id_type::IdType, PlaylistId, VideoId};
+use super::position::{self, Position};
+use crate::{ParseError, ParseErrorKind};
+
+/// 期待する形式, エラーの表示に使用する
+pub(super) const EXPECTED: &str = "a youtube url of a video or playlist";
+
+/// `attribution_link`の`u=`が相対パスのときに補う
+const ATTRIBUTION_BASE: &str = "https://www.youtube.com";
+
+/// 動画のIDをパスに含むときの接頭語, `/shorts/<id>`など
+const VIDEO_PATH_PREFIXES: [&str; 5] = ["shorts", "embed", "live", "v", "e"];
+
+/// 対応しているホスト
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Host {
+    /// `youtube.com`, `www.`, `m.`, `music.`
+    YouTube,
+    /// `youtube-nocookie.com`, 埋め込みのみ
+    NoCookie,
+    /// `youtu.be`
+    Short,
+}
+
+impl Host {
+    fn new(host: &str) -> Option<Self> {
+        let host = host.to_lowercase();
+        match host.strip_prefix("www.").unwrap_or(&host) {
+            "youtube.com" | "m.youtube.com" | "music.youtube.com" => {
+                Some(Self::YouTube)
+            }
+            "youtube-nocookie.com" => Some(Self::NoCookie),
+            "youtu.be" => Some(Self::Short),
+            _ => None,
+        }
+    }
+}
+
+/// 入力の一部とその位置(バイト)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span<'a> {
+    pos: usize,
+    s: &'a str,
+}
+
+impl<'a> Span<'a> {
+    fn new(pos: usize, s: &'a str) -> Self {
+        Self { pos, s }
+    }
+}
+
+/// クエリの`key=value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueryPair<'a> {
+    key: Span<'a>,
+    value: Span<'a>,
+}
+
+/// `url`から取り出したIDと位置
+#[derive(Debug)]
+struct Ids {
+    v_id: Option<VideoId>,
+    pl_id: Option<PlaylistId>,
+    position: Position,
+}
+
+/// `url`を構成要素に分けたもの, フラグメント(`#`以降)は含めない
+#[derive(Debug)]
+struct UrlParts<'a> {
+    input: &'a str,
+    host: Host,
+    path: Span<'a>,
+    query: Vec<QueryPair<'a>>,
+}
+
+impl<'a> UrlParts<'a> {
+    /// スキームは省略でき, `http`か`https`のみ
+    fn new(input: &'a str) -> Result<Self, ParseError> {
+        let rest_pos = match input.find("://") {
+            Some(end) if is_scheme(&input[..end]) => {
+                let scheme = &input[..end];
+                if !matches!(scheme.to_lowercase().as_str(), "http" | "https") {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnsupportedScheme,
+                        input,
+                        0,
+                        scheme,
+                        "`https`, `http` or no scheme",
+                    ));
+                }
+                end + 3
+            }
+            _ if input.starts_with("//") => 2,
+            _ => 0,
+        };
+
+        let rest = &input[rest_pos..];
+        let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let authority = &rest[..authority_end];
+        // `user@host:port`から`host`を取り出す
+        let host_pos = authority.rfind('@').map_or(0, |i| i + 1);
+        let host = &authority[host_pos..];
+        let host = host.split(':').next().unwrap_or(host);
+        let host_span = Span::new(rest_pos + host_pos, host);
+        let host = Host::new(host).ok_or_else(|| {
+            ParseError::new(
+                ParseErrorKind::UnsupportedHost,
+                input,
+                host_span.pos,
+                host_span.s,
+                "`youtube.com`, `youtu.be` or `youtube-nocookie.com`",
+            )
+        })?;
+
+        let path_pos = rest_pos + authority_end;
+        let after = &input[path_pos..];
+        let after = &after[..after.find('#').unwrap_or(after.len())];
+        let (path, query) = match after.find('?') {
+            Some(i) => (
+                &after[..i],
+                Some(Span::new(path_pos + i + 1, &after[i + 1..])),
+            ),
+            None => (after, None),
+        };
+
+        Ok(Self {
+            input,
+            host,
+            path: Span::new(path_pos, path),
+            query: query.map(parse_query).unwrap_or_default(),
+        })
+    }
+
+    /// 空でないパスの要素
+    fn segments(&self) -> Vec<Span<'a>> {
+        let mut segments = Vec::new();
+        let mut pos = self.path.pos;
+        for segment in self.path.s.split('/') {
+            if !segment.is_empty() {
+                segments.push(Span::new(pos, segment));
+            }
+            pos += segment.len() + 1;
+        }
+        segments
+    }
+
+    /// 最初に現れる`key`の値, 空のときは`MissingValue`
+    fn query_value(
+        &self,
+        key: &str,
+        expected: &str,
+    ) -> Result<Option<Span<'a>>, ParseError> {
+        let Some(pair) = self.query.iter().find(|pair| pair.key.s == key) else {
+            return Ok(None);
+        };
+        if pair.value.s.is_empty() {
+            // `=`があれば含める
+            let key = match self.input[pair.key.pos..].strip_prefix(pair.key.s) {
+                Some(rest) if rest.starts_with('=') => {
+                    Span::new(pair.key.pos, &self.input[pair.key.pos..pair.value.pos])
+                }
+                _ => pair.key,
+            };
+            return Err(missing_value(self.input, key, expected));
+        }
+        Ok(Some(pair.value))
+    }
+
+    fn video_id(&mut self) -> Result<Option<VideoId>, ParseError> {
+        let segments = self.segments();
+        let first = segments.first().map(|span| span.s);
+        let id = match (self.host, first) {
+            (Host::Short, Some(_)) => {
+                // 以前の形式`youtu.be/<id>&list=<pl>`にも対応する
+                let segment = segments[0];
+                let (id, legacy) = match segment.s.split_once('&') {
+                    Some((id, legacy)) => {
+                        (id, Some(Span::new(segment.pos + id.len() + 1, legacy)))
+                    }
+                    None => (segment.s, None),
+                };
+                self.query.extend(legacy.map(parse_query).unwrap_or_default());
+                Span::new(segment.pos, id)
+            }
+            (Host::Short, None) => {
+                let kind = ParseErrorKind::MissingId;
+                return Err(ParseError::whole(kind, self.input, VideoId::EXPECTED));
+            }
+            (Host::YouTube, Some("watch")) => {
+                match self.query_value("v", VideoId::EXPECTED)? {
+                    Some(value) => value,
+                    None => return Ok(None),
+                }
+            }
+            (Host::YouTube, Some(prefix))
+            | (Host::NoCookie, Some(prefix @ "embed"))
+                if VIDEO_PATH_PREFIXES.contains(&prefix) =>
+            {
+                match segments.get(1) {
+                    // 再生リストの埋め込み`/embed/videoseries?list=<pl>`
+                    Some(id) if prefix == "embed" && id.s == "videoseries" => {
+                        return Ok(None)
+                    }
+                    Some(id) => *id,
+                    None => {
+                        return Err(missing_value(
+                            self.input,
+                            segments[0],
+                            VideoId::EXPECTED,
+                        ));
+                    }
+                }
+            }
+            _ => return Ok(None),
+        };
+        parse_id(self.input, id).map(Some)
+    }
+
+    /// `attribution_link?u=<url>`の`u`を`url`として解析する
+    ///
+    /// `u`に`list`や`t`などが無いときは外側のものを使用する
+    fn attribution_link(&self) -> Result<Ids, ParseError> {
+        let expected = "`u=<url>` after `attribution_link`";
+        let Some(value) = self.query_value("u", expected)? else {
+            return Err(missing_value(self.input, self.path, expected));
+        };
+        let decoded = percent_decode(value.s);
+        let nested = if decoded.starts_with('/') {
+            format!("{}{}", ATTRIBUTION_BASE, decoded)
+        } else {
+            decoded
+        };
+        // 復号した後の位置は入力と対応しないので, `u`の値全体をエラーの位置とする
+        let ids =
+            UrlParts::new(&nested).and_then(|mut parts| parts.ids()).map_err(|e| {
+                ParseError::new(e.kind, self.input, value.pos, value.s, e.expected)
+            })?;
+        let outer = self.position();
+        Ok(Ids {
+            v_id: ids.v_id,
+            pl_id: match ids.pl_id {
+                Some(pl_id) => Some(pl_id),
+                None => self.playlist_id()?,
+            },
+            position: Position::new(
+                ids.position.start.or(outer.start),
+                ids.position.index.or(outer.index),
+            ),
+        })
+    }
+
+    fn ids(&mut self) -> Result<Ids, ParseError> {
+        let first = self.segments().first().map(|span| span.s);
+        if self.host == Host::YouTube && first == Some("attribution_link") {
+            return self.attribution_link();
+        }
+        // `youtu.be/<id>&t=<t>`のクエリは`video_id`で加わる
+        let v_id = self.video_id()?;
+        Ok(Ids {
+            v_id,
+            pl_id: self.playlist_id()?,
+            position: self.position(),
+        })
+    }
+
+    /// 最初に現れる`t`, 無いか解釈できないときは`start`, 値が不正なときは無視する
+    fn position(&self) -> Position {
+        let value = |key: &str| {
+            self.query.iter().find(|pair| pair.key.s == key).map(|pair| pair.value.s)
+        };
+        let start = value("t")
+            .and_then(position::parse_start)
+            .or_else(|| value("start").and_then(position::parse_start));
+        Position::new(start, value("index").and_then(position::parse_index))
+    }
+
+    fn playlist_id(&self) -> Result<Option<PlaylistId>, ParseError> {
+        match self.query_value("list", PlaylistId::EXPECTED)? {
+            Some(value) => parse_id(self.input, value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `url`を解析し, 含まれる動画と再生リストのIDと位置を返す
+///
+/// `si`, `pp`, `feature`などの`v`, `list`, `t`, `start`, `index`以外のクエリは無視する
+pub(super) fn parse(input: &str) -> Result<(IdType, Position), ParseError> {
+    let ids = UrlParts::new(input)?.ids()?;
+    IdType::new(ids.v_id, ids.pl_id)
+        .map(|id| (id, ids.position))
+        .map_err(|kind| ParseError::whole(kind, input, EXPECTED))
+}
+
+fn is_scheme(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn parse_query(query: Span) -> Vec<QueryPair> {
+    let mut pairs = Vec::new();
+    let mut pos = query.pos;
+    for pair in query.s.split('&') {
+        if !pair.is_empty() {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            pairs.push(QueryPair {
+                key: Span::new(pos, key),
+                value: Span::new(pos + key.len() + 1, value),
+            });
+        }
+        pos += pair.len() + 1;
+    }
+    pairs
+}
+
+/// エラーの位置を`input`におけるものにする
+fn parse_id<T>(input: &str, id: Span) -> Result<T, ParseError>
+where
+    T: std::str::FromStr<Err = ParseError>,
+{
+    T::from_str(id.s).map_err(|e| e.within(input, id.pos))
+}
+
+/// `key`に対応する値が無い
+fn missing_value(input: &str, key: Span, expected: &str) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::MissingValue,
+        input,
+        key.pos,
+        key.s,
+        format!("{} after `{}`", expected, key.s),
+    )
+}
+
+/// `%xx`と`+`を復号する, 不正な`%`はそのまま残す
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if bytes
+                .get(i + 1..i + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+                continue;
+            }
+            b'+' => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V: &str = "12345678901";
+    const PL: &str = "1234567890123456789012345678901234";
+
+    fn video() -> IdType {
+        VideoId::inc_from_1().into()
+    }
+
+    fn playlist() -> IdType {
+        PlaylistId::inc_from_1().into()
+    }
+
+    fn video_playlist() -> IdType {
+        (VideoId::inc_from_1(), PlaylistId::inc_from_1()).into()
+    }
+
+    #[test]
+    fn test_parse_video_url_shapes() {
+        let urls = [
+            format!("https://www.youtube.com/watch?v={}", V),
+            format!("http://youtube.com/watch?feature=share&v={}&si=abc&pp=x", V),
+            format!("https://m.youtube.com/watch?v={}#t=10", V),
+            format!("https://music.youtube.com/watch?v={}", V),
+            format!("https://WWW.YouTube.com:443/watch?v={}", V),
+            format!("www.youtube.com/watch?v={}", V),
+            format!("youtube.com/shorts/{}?feature=share", V),
+            format!("https://www.youtube.com/embed/{}", V),
+            format!("https://www.youtube-nocookie.com/embed/{}?start=3", V),
+            format!("https://www.youtube.com/live/{}", V),
+            format!("https://www.youtube.com/v/{}", V),
+            format!("https://www.youtube.com/e/{}", V),
+            format!("//youtu.be/{}", V),
+            format!("youtu.be/{}?si=abc", V),
+            format!(
+                "https://www.youtube.com/attribution_link?a=x&u=%2Fwatch%3Fv%3D{}%26feature%3Dshare",
+                V
+            ),
+        ];
+        for url in urls {
+            assert_eq!(parse(&url).map(|(id, _)| id), Ok(video()), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_playlist_url_shapes() {
+        let urls = [
+            format!("https://www.youtube.com/playlist?list={}", PL),
+            format!("https://music.youtube.com/playlist?list={}&si=x", PL),
+            format!("https://www.youtube.com/embed/videoseries?list={}", PL),
+        ];
+        for url in urls {
+            assert_eq!(parse(&url).map(|(id, _)| id), Ok(playlist()), "{}", url);
+        }
+
+        let urls = [
+            format!("https://www.youtube.com/watch?v={}&list={}&index=2", V, PL),
+            format!("https://youtu.be/{}?list={}", V, PL),
+            // 以前の形式
+            format!("https://youtu.be/{}&list={}", V, PL),
+            format!(
+                "https://www.youtube.com/attribution_link?u=/watch%3Fv%3D{}%26list%3D{}",
+                V, PL
+            ),
+        ];
+        for url in urls {
+            assert_eq!(
+                parse(&url).map(|(id, _)| id),
+                Ok(video_playlist()),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let position = |url: String| parse(&url).unwrap().1;
+        assert_eq!(
+            position(format!("https://www.youtube.com/watch?v={}&t=1h2m3s", V)),
+            Position::new(Some(3723), None)
+        );
+        assert_eq!(
+            position(format!("https://youtu.be/{}?t=90", V)),
+            Position::new(Some(90), None)
+        );
+        // 以前の形式
+        assert_eq!(
+            position(format!("https://youtu.be/{}&t=90s", V)),
+            Position::new(Some(90), None)
+        );
+        assert_eq!(
+            position(format!("https://www.youtube.com/embed/{}?start=30", V)),
+            Position::new(Some(30), None)
+        );
+        assert_eq!(
+            position(format!(
+                "https://www.youtube.com/watch?v={}&list={}&index=4&t=2m",
+                V, PL
+            )),
+            Position::new(Some(120), Some(4))
+        );
+        assert_eq!(
+            position(format!(
+                "https://www.youtube.com/attribution_link?u=%2Fwatch%3Fv%3D{}%26t%3D5s&index=2",
+                V
+            )),
+            Position::new(Some(5), Some(2))
+        );
+
+        // 不正な値は無視する
+        assert_eq!(
+            position(format!(
+                "https://www.youtube.com/watch?v={}&t=abc&index=0",
+                V
+            )),
+            Position::default()
+        );
+        assert_eq!(
+            position(format!("https://www.youtube.com/watch?v={}&t=x&start=7", V)),
+            Position::new(Some(7), None)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_url() {
+        let kind = |url: &str| parse(url).unwrap_err().kind;
+        assert_eq!(
+            kind(&format!("ftp://youtube.com/watch?v={}", V)),
+            ParseErrorKind::UnsupportedScheme
+        );
+        assert_eq!(
+            kind(&format!("https://youtube.com.evil.com/watch?v={}", V)),
+            ParseErrorKind::UnsupportedHost
+        );
+        assert_eq!(
+            kind(&format!("https://www.youtube-nocookie.com/watch?v={}", V)),
+            ParseErrorKind::MissingId
+        );
+        assert_eq!(
+            kind("https://youtube.com/shorts/"),
+            ParseErrorKind::MissingValue
+        );
+        assert_eq!(
+            kind("https://youtube.com/attribution_link?u="),
+            ParseErrorKind::MissingValue
+        );
+        assert_eq!(kind("https://youtu.be"), ParseErrorKind::MissingId);
+
+        let err = parse("https://youtube.com/attribution_link?u=%2Fwatch%3Fv%3D123")
+            .unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidLength(3));
+        assert_eq!(err.fragment, "%2Fwatch%3Fv%3D123");
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("%2Fwatch%3Fv%3Dx+y"), "/watch?v=x y");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%e3%81%82"), "%zzあ");
+    }
+}
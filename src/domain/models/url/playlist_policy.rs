@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use std::fmt::Display;
+
+use super::{Url, UrlVideo};
+
+/// 動画と再生リストの両方を指す`url`(`watch?v=(id)&list=(pl)`)をどちらとして取得するか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistPolicy {
+    /// 再生リスト全体を取得し, 指定された動画を出力で示す
+    #[default]
+    Playlist,
+    /// 指定された動画のみを取得する
+    Video,
+    /// 指定された動画と再生リスト全体の両方を取得する
+    Both,
+}
+
+impl PlaylistPolicy {
+    pub const ALL: [Self; 3] = [Self::Playlist, Self::Video, Self::Both];
+
+    /// 設定と入力の接頭語で使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Playlist => "playlist",
+            Self::Video => "video",
+            Self::Both => "both",
+        }
+    }
+
+    /// 入力の先頭の`video:`, `playlist:`, `both:`を取り除く, 大文字と小文字を区別しない
+    ///
+    /// 接頭語が無いときは`None`と入力をそのまま返す
+    pub fn strip_prefix(input: &str) -> (Option<Self>, &str) {
+        for policy in Self::ALL {
+            let name = policy.name();
+            let has_prefix = input
+                .get(..name.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(name))
+                && input[name.len()..].starts_with(':');
+            if has_prefix {
+                return (Some(policy), input[name.len() + 1..].trim_start());
+            }
+        }
+        (None, input)
+    }
+
+    /// 動画と再生リストの両方を指す`url`を取得する`url`に分ける, それ以外はそのまま返す
+    ///
+    /// 動画のみの`url`は再生を始める位置を引き継ぐ
+    ///
+    /// `Playlist`でもミックスリストなど取得できない再生リストのときは動画を取得する
+    pub fn apply(self, url: Url) -> Vec<Url> {
+        let video = match &url {
+            Url::Playlist(pl) => pl
+                .get_video_id()
+                .clone()
+                .map(|v| Url::from(UrlVideo::from(v).with_start(pl.position().start))),
+            Url::Video(_) => None,
+        };
+        let unsupported =
+            matches!(&url, Url::Playlist(pl) if pl.unsupported_reason().is_some());
+        match (self, video) {
+            (Self::Video, Some(video)) => vec![video],
+            (Self::Playlist, Some(video)) if unsupported => vec![video],
+            (Self::Both, Some(video)) => vec![video, url],
+            _ => vec![url],
+        }
+    }
+}
+
+impl Display for PlaylistPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        id::{PlaylistId, VideoId},
+        url::{Position, UrlPlaylist},
+    };
+
+    #[test]
+    fn test_strip_prefix() {
+        assert_eq!(
+            PlaylistPolicy::strip_prefix("video:https://youtu.be/12345678901"),
+            (Some(PlaylistPolicy::Video), "https://youtu.be/12345678901")
+        );
+        assert_eq!(
+            PlaylistPolicy::strip_prefix("Both: 12345678901"),
+            (Some(PlaylistPolicy::Both), "12345678901")
+        );
+        for input in [
+            "https://youtu.be/12345678901",
+            "videos:a",
+            "video",
+            "@video",
+        ] {
+            assert_eq!(PlaylistPolicy::strip_prefix(input), (None, input));
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let position = Position::new(Some(90), Some(2));
+        let v_pl = Url::v_pl_inc_from_1().with_position(position);
+        let v = Url::v_inc_from_1().with_position(position);
+
+        assert_eq!(
+            PlaylistPolicy::Playlist.apply(v_pl.clone()),
+            vec![v_pl.clone()]
+        );
+        assert_eq!(PlaylistPolicy::Video.apply(v_pl.clone()), vec![v.clone()]);
+        assert_eq!(
+            PlaylistPolicy::Both.apply(v_pl.clone()),
+            vec![v.clone(), v_pl]
+        );
+
+        // 取得できない再生リストは`Playlist`でも動画に置き換える
+        let mix = Url::from(UrlPlaylist::new(
+            Some(VideoId::inc_from_1()),
+            PlaylistId::new("RD12345678901".into()).unwrap(),
+        ));
+        let v = Url::v_inc_from_1();
+        assert_eq!(PlaylistPolicy::Playlist.apply(mix.clone()), vec![v.clone()]);
+        assert_eq!(PlaylistPolicy::Video.apply(mix.clone()), vec![v.clone()]);
+        assert_eq!(
+            PlaylistPolicy::Both.apply(mix.clone()),
+            vec![v.clone(), mix]
+        );
+
+        // 動画のみ, 再生リストのみの`url`は変わらない
+        for policy in PlaylistPolicy::ALL {
+            assert_eq!(policy.apply(v.clone()), vec![v.clone()]);
+            assert_eq!(
+                policy.apply(Url::pl_inc_from_1()),
+                vec![Url::pl_inc_from_1()]
+            );
+        }
+    }
+}
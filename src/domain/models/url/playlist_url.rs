@@ -1,18 +1,29 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
 use super::super::id::{id_type::IdType, PlaylistId, VideoId};
-use super::build_url;
+use super::{build_url, Position, Url};
+use crate::{ParseError, ParseErrorKind};
 
+/// 再生リストの`url`, 動画のIDと再生を始める位置, 再生リスト内の位置を保持する
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct UrlPlaylist(Option<VideoId>, PlaylistId);
+pub struct UrlPlaylist(Option<VideoId>, PlaylistId, Position);
 
 impl UrlPlaylist {
+    /// `index`と`t`を含めて`url`を組み立てる
     pub fn build_url(&self) -> String {
         let both_id: (Option<VideoId>, PlaylistId) = (self.0.clone(), self.1.clone());
         let id_type: IdType = both_id.into();
-        build_url::build_url(id_type)
+        build_url::build_url(id_type, self.2)
     }
 
     pub fn new(v_id: Option<VideoId>, pl_id: PlaylistId) -> Self {
-        Self(v_id, pl_id)
+        Self(v_id, pl_id, Position::default())
+    }
+
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.2 = position;
+        self
     }
 
     pub fn get_playlist_id(&self) -> &PlaylistId {
@@ -22,10 +33,82 @@ impl UrlPlaylist {
     pub fn get_video_id(&self) -> &Option<VideoId> {
         &self.0
     }
+
+    /// 再生を始める位置(秒)と再生リスト内の位置
+    pub fn position(&self) -> Position {
+        self.2
+    }
+
+    /// `playlistItems.list`で取得できない種類の再生リストのとき, その理由
+    pub fn unsupported_reason(&self) -> Option<&'static str> {
+        self.1.unsupported_reason()
+    }
+}
+
+/// `Url`と同じ形式を受け付け, 再生リストのIDを含まない動画の`url`はエラー
+impl FromStr for UrlPlaylist {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<Url>()? {
+            Url::Playlist(pl) => Ok(pl),
+            Url::Video(_) => Err(ParseError::whole(
+                ParseErrorKind::MissingId,
+                s,
+                "a playlist url or id",
+            )),
+        }
+    }
+}
+
+/// `build_url`の形式
+impl Display for UrlPlaylist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.build_url())
+    }
+}
+
+/// `build_url`の文字列
+impl Serialize for UrlPlaylist {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UrlPlaylist {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let url = String::deserialize(deserializer)?;
+        url.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 impl From<(Option<VideoId>, PlaylistId)> for UrlPlaylist {
     fn from(value: (Option<VideoId>, PlaylistId)) -> Self {
-        Self(value.0, value.1)
+        Self::new(value.0, value.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_playlist_from_str_and_serde() {
+        let pl =
+            UrlPlaylist::new(Some(VideoId::inc_from_1()), PlaylistId::inc_from_1())
+                .with_position(Position::new(None, Some(3)));
+        let input = "https://youtu.be/12345678901?list=1234567890123456789012345678901234&index=3";
+        assert_eq!(input.parse(), Ok(pl.clone()));
+        let err = "https://youtu.be/12345678901".parse::<UrlPlaylist>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingId);
+
+        let json = serde_json::to_string(&pl).unwrap();
+        assert_eq!(json, format!("\"{}\"", pl.build_url()));
+        assert_eq!(serde_json::from_str::<UrlPlaylist>(&json).unwrap(), pl);
     }
 }
@@ -0,0 +1,137 @@
+use serde::Serialize;
+
+/// `url`のクエリで指定された再生を始める位置と再生リスト内の位置
+///
+/// 解釈できない値は無視する, `url`自体はエラーにしない
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Position {
+    /// 再生を始める位置(秒), `t=1h2m3s`, `t=90`, `start=90`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<u32>,
+    /// 再生リスト内の位置(1から), `index=3`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+impl Position {
+    pub fn new(start: Option<u32>, index: Option<u32>) -> Self {
+        Self { start, index }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start.is_none() && self.index.is_none()
+    }
+
+    /// `build_url`の末尾に付けるクエリ, `&index=3&t=90s`
+    pub(super) fn to_query(self) -> String {
+        let mut query = String::new();
+        if let Some(index) = self.index {
+            query.push_str(&format!("&index={}", index));
+        }
+        if let Some(start) = self.start {
+            query.push_str(&format!("&t={}s", start));
+        }
+        query
+    }
+}
+
+/// `t`と`start`の値を秒に変換する
+///
+/// - `90`, `90s`: 秒
+/// - `1h2m3s`, `2m`: 単位は`h`, `m`, `s`の順で, それぞれ省略できる
+/// - `1:02:03`, `2:03`: 時:分:秒, 分:秒
+pub(super) fn parse_start(s: &str) -> Option<u32> {
+    if s.contains(':') {
+        return parse_clock(s);
+    }
+    if s.bytes().all(|b| b.is_ascii_digit()) {
+        return s.parse().ok();
+    }
+    let mut total: u32 = 0;
+    let mut rest = s;
+    // 単位ごとの秒, 一度使った単位とそれより大きい単位は使えない
+    let mut units: &[(char, u32)] = &[('h', 3600), ('m', 60), ('s', 1)];
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        if digits == 0 {
+            return None;
+        }
+        let value: u32 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let pos = units.iter().position(|(u, _)| *u == unit)?;
+        total = total.checked_add(value.checked_mul(units[pos].1)?)?;
+        units = &units[pos + 1..];
+        rest = &rest[digits + 1..];
+    }
+    Some(total)
+}
+
+/// `h:mm:ss`または`m:ss`
+fn parse_clock(s: &str) -> Option<u32> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    parts.iter().try_fold(0u32, |total, part| {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        total.checked_mul(60)?.checked_add(part.parse().ok()?)
+    })
+}
+
+/// `index`の値, 1以上の整数のみ
+pub(super) fn parse_index(s: &str) -> Option<u32> {
+    if !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok().filter(|index| *index > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_start() {
+        assert_eq!(parse_start("90"), Some(90));
+        assert_eq!(parse_start("90s"), Some(90));
+        assert_eq!(parse_start("2m"), Some(120));
+        assert_eq!(parse_start("1h2m3s"), Some(3723));
+        assert_eq!(parse_start("1h3s"), Some(3603));
+        assert_eq!(parse_start("1:02:03"), Some(3723));
+        assert_eq!(parse_start("2:03"), Some(123));
+
+        for invalid in [
+            "",
+            "s",
+            "1x",
+            "3s2m",
+            "1m1m",
+            "-1",
+            "1:2:3:4",
+            "1::2",
+            "99999999999",
+        ] {
+            assert_eq!(parse_start(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_index() {
+        assert_eq!(parse_index("3"), Some(3));
+        assert_eq!(parse_index("0"), None);
+        assert_eq!(parse_index("+3"), None);
+        assert_eq!(parse_index(""), None);
+    }
+
+    #[test]
+    fn test_position_to_query() {
+        assert_eq!(Position::default().to_query(), "");
+        assert_eq!(
+            Position::new(Some(90), Some(3)).to_query(),
+            "&index=3&t=90s"
+        );
+        assert!(Position::default().is_empty());
+    }
+}
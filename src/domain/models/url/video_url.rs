@@ -1,19 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
 use super::super::id::{id_type::IdType, VideoId};
-use super::build_url;
+use super::{build_url, Position, Url};
+use crate::{ParseError, ParseErrorKind};
 
+/// 動画の`url`, 再生を始める位置(秒)を保持する
+///
+/// 再生を始める位置が異なるときは別の`url`とする
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct UrlVideo(VideoId);
+pub struct UrlVideo(VideoId, Option<u32>);
 
 impl UrlVideo {
+    /// `t`を含めて`url`を組み立てる
     pub fn build_url(&self) -> String {
         let id_type: IdType = self.0.clone().into();
-        build_url::build_url(id_type)
+        build_url::build_url(id_type, Position::new(self.1, None))
+    }
+
+    /// 再生を始める位置(秒)
+    pub fn start(&self) -> Option<u32> {
+        self.1
+    }
+
+    pub fn with_start(mut self, start: Option<u32>) -> Self {
+        self.1 = start;
+        self
+    }
+}
+
+/// `Url`と同じ形式を受け付け, 動画のIDを含まない再生リストの`url`はエラー
+///
+/// 動画と再生リストの両方を指す`url`は動画のみとする
+impl FromStr for UrlVideo {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<Url>()? {
+            Url::Video(v) => Ok(v),
+            Url::Playlist(pl) => match pl.get_video_id() {
+                Some(v) => Ok(Self::from(v.clone()).with_start(pl.position().start)),
+                None => Err(ParseError::whole(
+                    ParseErrorKind::MissingId,
+                    s,
+                    "a video url or id",
+                )),
+            },
+        }
+    }
+}
+
+/// `build_url`の形式
+impl Display for UrlVideo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.build_url())
+    }
+}
+
+/// `build_url`の文字列
+impl Serialize for UrlVideo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UrlVideo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let url = String::deserialize(deserializer)?;
+        url.parse().map_err(serde::de::Error::custom)
     }
 }
 
 impl From<VideoId> for UrlVideo {
     fn from(value: VideoId) -> Self {
-        Self(value)
+        Self(value, None)
     }
 }
 
@@ -28,3 +93,29 @@ impl<'a> From<&'a UrlVideo> for &'a VideoId {
         &value.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_video_from_str_and_serde() {
+        let v = UrlVideo::from(VideoId::inc_from_1()).with_start(Some(90));
+        assert_eq!("youtu.be/12345678901?t=90".parse(), Ok(v.clone()));
+        // 再生リストの部分は捨てる
+        assert_eq!(
+            "https://www.youtube.com/watch?v=12345678901&list=1234567890123456789012345678901234&t=90"
+                .parse(),
+            Ok(v.clone())
+        );
+        let err = "1234567890123456789012345678901234".parse::<UrlVideo>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingId);
+
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(
+            json,
+            "\"https://www.youtube.com/watch?v=12345678901&t=90s\""
+        );
+        assert_eq!(serde_json::from_str::<UrlVideo>(&json).unwrap(), v);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+use thiserror::Error;
+
+/// 入力の解析に失敗した原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// 長さが正しくない, 値は実際の長さ
+    InvalidLength(usize),
+    /// 使用できない文字を含む
+    InvalidChar(char),
+    /// 接頭語が正しくない
+    InvalidPrefix,
+    /// `v=`などのキーに対応する値が無い
+    MissingValue,
+    /// 対応していないスキーム
+    UnsupportedScheme,
+    /// 対応していないホスト
+    UnsupportedHost,
+    /// 動画のIDも再生リストのIDも含まない
+    MissingId,
+    /// 複数の解釈ができる
+    Ambiguous,
+    /// 解釈はできるが取得には対応していない
+    Unsupported,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(f, "invalid length ({})", len),
+            Self::InvalidChar(c) => write!(f, "invalid character `{}`", c),
+            Self::InvalidPrefix => write!(f, "invalid prefix"),
+            Self::MissingValue => write!(f, "missing value"),
+            Self::UnsupportedScheme => write!(f, "unsupported scheme"),
+            Self::UnsupportedHost => write!(f, "unsupported host"),
+            Self::MissingId => write!(f, "no video id or playlist id"),
+            Self::Ambiguous => write!(f, "ambiguous input"),
+            Self::Unsupported => write!(f, "not supported yet"),
+        }
+    }
+}
+
+/// `url`やIDの解析に失敗したときのエラー
+///
+/// 入力全体と, 原因となった部分文字列とその位置, 期待する形式を保持する
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{kind} at column {}: `{fragment}`, expected {expected}", .position + 1)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// 解析した文字列全体
+    pub input: String,
+    /// 原因となった部分文字列
+    pub fragment: String,
+    /// `input`における`fragment`の位置, 0から始まる文字数
+    pub position: usize,
+    /// 期待する形式
+    pub expected: String,
+}
+
+impl ParseError {
+    /// `fragment`は`input`の`byte_pos`バイト目から始まる
+    pub fn new(
+        kind: ParseErrorKind,
+        input: &str,
+        byte_pos: usize,
+        fragment: &str,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            input: input.into(),
+            fragment: fragment.into(),
+            position: input[..byte_pos].chars().count(),
+            expected: expected.into(),
+        }
+    }
+
+    /// `input`全体が原因のとき
+    pub fn whole(
+        kind: ParseErrorKind,
+        input: &str,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self::new(kind, input, 0, input, expected)
+    }
+
+    /// 部分文字列を解析したエラーを, `byte_pos`バイト目から部分文字列が始まる`input`のエラーにする
+    pub fn within(mut self, input: &str, byte_pos: usize) -> Self {
+        self.position += input[..byte_pos].chars().count();
+        self.input = input.into();
+        self
+    }
+
+    /// `input`と, その下に`fragment`の位置を示す`^`を並べた2行
+    pub fn annotate(&self) -> String {
+        let width = self.fragment.chars().count().max(1);
+        format!(
+            "{}\n{}{}",
+            self.input,
+            " ".repeat(self.position),
+            "^".repeat(width)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_within() {
+        let err =
+            ParseError::whole(ParseErrorKind::InvalidLength(3), "123", "11 chars");
+        let err = err.within("https://youtu.be/123", 17);
+        assert_eq!(err.position, 17);
+        assert_eq!(
+            err.to_string(),
+            "invalid length (3) at column 18: `123`, expected 11 chars"
+        );
+        assert_eq!(
+            err.annotate(),
+            format!("https://youtu.be/123\n{}^^^", " ".repeat(17))
+        );
+    }
+}
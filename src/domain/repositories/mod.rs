@@ -1,3 +1,3 @@
 mod youtube;
 
-pub use youtube::{FetchBasicDataTrait, FetchDetailedDataTrait};
+pub use youtube::{CheckExistenceTrait, FetchBasicDataTrait, FetchDetailedDataTrait};
@@ -1,8 +1,11 @@
+use futures::stream::BoxStream;
 use std::collections::VecDeque;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     metadata::{BasicData, FullData},
     url::Url,
+    FetchedItem, FetchedResults, UrlExistence,
 };
 
 /// VideoIdを色々な方法で抽出
@@ -10,16 +13,37 @@ use crate::{
 pub trait FetchBasicDataTrait {
     /// 動画の基本的な情報を複数の`url(id)`から取得
     ///
-    /// 内側の`Result<Video, Url>`:
-    /// - Ok(Video): 正常に動画の情報を取得できたとき
-    /// - Err(Url): `url(id)`が存在せず取得できなかったとき,
-    /// その存在しない`url(id)`を含む
+    /// `FetchedResults`:
+    /// - fetched: 取得を試みた`url(id)`の結果
+    /// - unattempted: `cancel`により中断され, 取得を試みなかった`url(id)`
+    /// - malformed: レスポンスを解釈できなかった`url(id)`とその理由
+    /// - unsupported: APIで取得できないため取得を試みなかった`url(id)`とその理由
+    ///
+    /// 中断されたときも, それまでに取得できた情報は返す
     ///
     /// 外側の`Result`: ネットワークエラーなどのエラー
     async fn fetch_basic_data_with_urls(
         &self,
         urls: VecDeque<Url>,
-    ) -> Result<VecDeque<Result<BasicData, Url>>, crate::Error>;
+        cancel: CancellationToken,
+    ) -> Result<FetchedResults<BasicData, Url>, crate::Error>;
+    /// 動画の基本的な情報を複数の`url(id)`から取得し, 取得できたものから順に返す
+    ///
+    /// 返す順番は`urls`の順番と一致しない
+    ///
+    /// `FetchedItem`:
+    /// - Found: 正常に情報を取得できたとき
+    /// - NotFound: `url(id)`が存在せず取得できなかったとき
+    /// - Unattempted: `cancel`により中断され, 取得を試みなかったとき
+    /// - Malformed: レスポンスのその`url(id)`の部分を解釈できなかったとき
+    /// - Unsupported: ミックスリストなどAPIで取得できないため, 取得を試みなかったとき
+    ///
+    /// `Err`: ネットワークエラーなどのエラー, これを返した後`Stream`は終了する
+    fn fetch_basic_data_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<BasicData, Url>, crate::Error>>;
     // 動画の基本的な情報を一つの`url(id)`から取得
     //
     // 内側の`Result<Video, Url>`:
@@ -27,13 +51,35 @@ pub trait FetchBasicDataTrait {
     // - Err(Url): `url(id)`が存在せず取得できなかったとき,
     // その存在しない`url(id)`を含む
     //
-    // 外側の`Result`: ネットワークエラーなどのエラー
+    // 外側の`Result`: ネットワークエラーなどのエラー,
+    // `cancel`により中断されたときは`crate::Error::Interrupted`,
+    // ミックスリストなどAPIで取得できないときは`crate::Error::Unsupported`
     async fn fetch_basic_data_with_url(
         &self,
         url: Url,
+        cancel: CancellationToken,
     ) -> Result<Result<BasicData, Url>, crate::Error>;
 }
 
+/// 動画の情報を取得せずに存在のみを確認する
+pub trait CheckExistenceTrait {
+    /// 複数の`url(id)`の存在を確認し, 確認できたものから順に返す
+    ///
+    /// 返す順番は`urls`の順番と一致しない
+    ///
+    /// `FetchedItem`:
+    /// - Found: 確認できたとき, 存在しなかったときも含む
+    /// - Unattempted: `cancel`により中断され, 確認を試みなかったとき
+    /// - Unsupported: ミックスリストなどAPIで確認できないため, 確認を試みなかったとき
+    ///
+    /// `Err`: ネットワークエラーなどのエラー, これを返した後`Stream`は終了する
+    fn check_existence_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<UrlExistence, Url>, crate::Error>>;
+}
+
 // FIXME いつか使うので #[allow(unused)]
 #[allow(unused)]
 #[async_trait::async_trait]
@@ -1,3 +1,7 @@
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
 use std::{
     collections::VecDeque,
     sync::{
@@ -5,10 +9,21 @@ use std::{
         Arc,
     },
 };
+use tokio_util::sync::CancellationToken;
 use tracing::Level;
 
-use super::ApiKey;
-use crate::{domain::repositories::FetchBasicDataTrait, metadata::BasicData, url::Url};
+use super::{
+    batch::{batch_stream, BasicDataOperation},
+    existence::ExistenceOperation,
+    observer::{FetchEvent, FetchObserver, SharedObserver},
+    ApiFields, ApiKey, ApiOptionsPart, PlaylistItemPart, QuotaCosts,
+};
+use crate::{
+    domain::repositories::{CheckExistenceTrait, FetchBasicDataTrait},
+    metadata::{BasicData, BasicVideoData},
+    url::{Url, UrlPlaylist, UrlVideo},
+    FetchedItem, FetchedResults, UrlExistence,
+};
 
 use crate::infrastructure::fetch::{
     playlist::fetch_data::PlaylistApiClient, video::fetch_data::VideoApiClient,
@@ -18,6 +33,13 @@ use crate::infrastructure::fetch::{
 pub struct ApiClient {
     api_key: ApiKey,
     used_quota: Arc<AtomicUsize>,
+    schema_drift: Arc<AtomicUsize>,
+    quota_costs: QuotaCosts,
+    observer: SharedObserver,
+    fields: ApiFields,
+    keep_raw: bool,
+    video_parts: ApiOptionsPart,
+    playlist_items_parts: ApiOptionsPart<PlaylistItemPart>,
 }
 
 impl ApiClient {
@@ -25,129 +47,224 @@ impl ApiClient {
         Self {
             api_key,
             used_quota: Arc::new(AtomicUsize::new(0)),
+            schema_drift: Arc::new(AtomicUsize::new(0)),
+            quota_costs: QuotaCosts::default(),
+            observer: SharedObserver::default(),
+            fields: ApiFields::default(),
+            keep_raw: false,
+            video_parts: ApiOptionsPart::default(),
+            playlist_items_parts: ApiOptionsPart::default(),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl FetchBasicDataTrait for ApiClient {
-    #[tracing::instrument(level = Level::DEBUG)]
-    async fn fetch_basic_data_with_urls(
-        &self,
-        urls: VecDeque<Url>,
-    ) -> Result<VecDeque<Result<BasicData, Url>>, crate::Error> {
-        let (urls_v, urls_pl) = Url::separate_urls(urls);
-        let mut res: VecDeque<Result<BasicData, Url>> = VecDeque::new();
+    /// レスポンスのそのままの`item`を結果の`raw`に含める
+    ///
+    /// `fields`で除外したフィールドは含まれないので`ApiFields::All`と併せて使用する
+    pub fn with_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+
+    /// `videos`へリクエストする`part`を指定する, 型で扱っていない`part`は`raw`でのみ確認できる
+    pub fn with_video_parts(mut self, parts: ApiOptionsPart) -> Self {
+        self.video_parts = parts;
+        self
+    }
 
-        let video_api_call = VideoApiClient::new_default(
+    /// `playlistItems`へリクエストする`part`を指定する, `snippet`以外は`raw`でのみ確認できる
+    pub fn with_playlist_items_parts(
+        mut self,
+        parts: ApiOptionsPart<PlaylistItemPart>,
+    ) -> Self {
+        self.playlist_items_parts = parts;
+        self
+    }
+
+    /// レスポンスに含めるフィールドを指定する, 詳しくは`ApiFields`を参照
+    pub fn with_fields(mut self, fields: ApiFields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// 取得処理の進捗を`observer`へ通知する
+    pub fn with_observer(mut self, observer: Arc<dyn FetchObserver>) -> Self {
+        self.observer = SharedObserver::new(observer);
+        self
+    }
+
+    /// 消費するquotaの計算に使う表を指定する, 指定しないときは公式の値を使用する
+    pub fn with_quota_costs(mut self, quota_costs: QuotaCosts) -> Self {
+        self.quota_costs = quota_costs;
+        self
+    }
+
+    /// これまでに消費したquotaの合計
+    pub fn used_quota(&self) -> usize {
+        self.used_quota.load(Ordering::Relaxed)
+    }
+
+    /// これまでにレスポンスに含まれていた想定していない値の数, APIの仕様変更の検知に使用
+    ///
+    /// 個々の値は`FetchEvent::SchemaDrift`で通知される
+    pub fn schema_drift(&self) -> usize {
+        self.schema_drift.load(Ordering::Relaxed)
+    }
+
+    fn video_api_client(&self) -> VideoApiClient {
+        VideoApiClient::new_default(
             self.api_key.clone(),
             Arc::clone(&self.used_quota),
-        );
-        let pl_api_call = PlaylistApiClient::new_default(
+            self.observer.clone(),
+        )
+        .with_quota_costs(self.quota_costs.clone())
+        .with_schema_drift(Arc::clone(&self.schema_drift))
+        .with_fields(self.fields.clone())
+        .with_raw(self.keep_raw)
+        .with_parts(self.video_parts.clone())
+    }
+
+    fn playlist_api_client(&self) -> PlaylistApiClient {
+        PlaylistApiClient::new_default(
             self.api_key.clone(),
             Arc::clone(&self.used_quota),
-        );
-
-        let (video_res, pl_res) = tokio::join!(
-            video_api_call.fetch_all_video_data(urls_v),
-            pl_api_call.fetch_all_playlist_data(urls_pl)
-        );
+            self.observer.clone(),
+        )
+        .with_quota_costs(self.quota_costs.clone())
+        .with_schema_drift(Arc::clone(&self.schema_drift))
+        .with_fields(self.fields.clone())
+        .with_raw(self.keep_raw)
+        .with_parts(self.playlist_items_parts.clone())
+    }
 
-        res.extend(transform_vec_result(video_res?));
-        res.extend(transform_vec_result(pl_res?));
+    fn notify_started(
+        &self,
+        urls_v: &VecDeque<UrlVideo>,
+        urls_pl: &VecDeque<UrlPlaylist>,
+    ) {
+        self.observer.notify(FetchEvent::Started {
+            videos: urls_v.len(),
+            playlists: urls_pl.len(),
+        });
+    }
+}
 
+#[async_trait::async_trait]
+impl FetchBasicDataTrait for ApiClient {
+    #[tracing::instrument(level = Level::DEBUG, skip(cancel))]
+    async fn fetch_basic_data_with_urls(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> Result<FetchedResults<BasicData, Url>, crate::Error> {
+        let res =
+            FetchedResults::try_collect(self.fetch_basic_data_stream(urls, cancel))
+                .await;
         tracing::debug!("used quota:`{}`", self.used_quota.load(Ordering::Relaxed));
-        Ok(res)
+        res
+    }
+
+    /// 直接指定された動画と再生リスト内の動画で重複する`VideoId`は一度だけ取得する
+    fn fetch_basic_data_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<BasicData, Url>, crate::Error>> {
+        let (urls_v, urls_pl) = Url::separate_urls(urls);
+        let (urls_pl, unsupported) = split_unsupported(urls_pl);
+        self.notify_started(&urls_v, &urls_pl);
+
+        let fetched = batch_stream(
+            BasicDataOperation::new(
+                self.video_api_client(),
+                self.playlist_api_client(),
+            ),
+            urls_v,
+            urls_pl,
+            cancel,
+        )
+        .map(|item| item.map_err(Into::into));
+        stream::iter(unsupported.into_iter().map(Ok)).chain(fetched).boxed()
     }
 
     async fn fetch_basic_data_with_url(
         &self,
         url: Url,
+        cancel: CancellationToken,
     ) -> Result<Result<BasicData, Url>, crate::Error> {
         match url {
             Url::Video(v) => {
-                let video_api_call = VideoApiClient::new_default(
-                    self.api_key.clone(),
-                    Arc::clone(&self.used_quota),
-                );
-                Ok(transform_result(video_api_call.fetch_video_data(v).await?))
+                let start = v.start();
+                let video_api_call = self.video_api_client();
+                let res = video_api_call.fetch_video_data(v, &cancel).await?;
+                Ok(res
+                    .map(|data| BasicVideoData::from(data).with_start(start).into())
+                    .map_err(Into::into))
             }
             Url::Playlist(pl) => {
-                let pl_api_call = PlaylistApiClient::new_default(
-                    self.api_key.clone(),
-                    Arc::clone(&self.used_quota),
-                );
-                Ok(transform_result(pl_api_call.fetch_playlist_data(pl).await?))
+                if let Some(reason) = pl.unsupported_reason() {
+                    return Err(crate::Error::Unsupported(reason.into()));
+                }
+                let mut res = FetchedResults::try_collect(batch_stream(
+                    BasicDataOperation::new(
+                        self.video_api_client(),
+                        self.playlist_api_client(),
+                    ),
+                    VecDeque::new(),
+                    vec![pl].into(),
+                    cancel,
+                ))
+                .await?;
+                if res.is_interrupted() {
+                    return Err(crate::Error::Interrupted);
+                }
+                Ok(res.fetched.pop_front().unwrap())
             }
         }
     }
 }
 
-fn transform_vec_result<T, E, A, B>(
-    input: VecDeque<Result<T, E>>,
-) -> VecDeque<Result<A, B>>
-where
-    T: Into<A>,
-    E: Into<B>,
-{
-    input.into_iter().map(|res| res.map(Into::into).map_err(Into::into)).collect()
-}
+impl CheckExistenceTrait for ApiClient {
+    /// 重複する`VideoId`は一度だけ確認する
+    fn check_existence_stream(
+        &self,
+        urls: VecDeque<Url>,
+        cancel: CancellationToken,
+    ) -> BoxStream<'_, Result<FetchedItem<UrlExistence, Url>, crate::Error>> {
+        let (urls_v, urls_pl) = Url::separate_urls(urls);
+        let (urls_pl, unsupported) = split_unsupported(urls_pl);
+        self.notify_started(&urls_v, &urls_pl);
 
-fn transform_result<T, E, A, B>(input: Result<T, E>) -> Result<A, B>
-where
-    T: Into<A>,
-    E: Into<B>,
-{
-    input.map(Into::into).map_err(Into::into)
+        let checked = batch_stream(
+            ExistenceOperation::new(
+                self.video_api_client(),
+                self.playlist_api_client(),
+            ),
+            urls_v,
+            urls_pl,
+            cancel,
+        )
+        .map(|item| item.map_err(Into::into));
+        stream::iter(unsupported.into_iter().map(Ok)).chain(checked).boxed()
+    }
 }
 
-// impl ApiCall {
-//     #[allow(unused)]
-//     #[tracing::instrument(level = Level::DEBUG)]
-//     async fn refetch_for_v_data_in_pl(
-//         &self,
-//         video_ids_from_multi_pl: VecDeque<Result<VideosInPlaylist, UrlPlaylist>>,
-//         video_api_call: &VideoApiCall,
-//     ) -> Result<VecDeque<Result<BasicPlaylistData, UrlPlaylist>>, FetchError> {
-//         // 最後に無効な`url_playlist`を追加しているので順番が一部失われる
-//         let mut invalid_url_playlist: VecDeque<UrlPlaylist> = VecDeque::new();
-//         let mut res: VecDeque<Result<BasicPlaylistData, UrlPlaylist>> = VecDeque::new();
-//         let mut tasks = FuturesOrdered::new();
-//         let mut pl_data_itself = VecDeque::new();
-
-//         for v_ids_from_pl in video_ids_from_multi_pl {
-//             match v_ids_from_pl {
-//                 Ok(v) => {
-//                     pl_data_itself.push_back(v.playlist_data_itself.clone());
-//                     let args: VecDeque<UrlVideo> =
-//                         v.video_ids.into_iter().map(Into::into).collect();
-//                     tasks.push_back(video_api_call.fetch_data(args));
-//                 }
-//                 Err(url) => invalid_url_playlist.push_back(url),
-//             }
-//         }
-//         while let Some(res_v) = tasks.next().await {
-//             let playlist_data = match res_v {
-//                 Ok(v) => {
-//                     let a: VecDeque<BasicVideoData> = v
-//                         .iter()
-//                         .flat_map(|item| match item {
-//                             Ok(single_video_data) => Some(single_video_data.clone()),
-//                             Err(_) => None,
-//                         })
-//                         .collect();
-//                     into_fetch_data(a, pl_data_itself.pop_front().unwrap())
-//                 }
-//                 Err(e) => return Err(e),
-//             };
-//             res.push_back(Ok(playlist_data));
-//         }
-//         res.extend(
-//             invalid_url_playlist
-//                 .into_iter()
-//                 .map(Err)
-//                 .collect::<VecDeque<Result<BasicPlaylistData, UrlPlaylist>>>(),
-//         );
-//         Ok(res)
-//     }
-// }
+/// `playlistItems.list`で取得できない再生リストを取り除き, `Unsupported`として返す
+///
+/// ミックスリストなどはAPIを呼び出しても`playlistNotFound`になるだけなので, quotaを消費しない
+fn split_unsupported<T>(
+    urls_pl: VecDeque<UrlPlaylist>,
+) -> (VecDeque<UrlPlaylist>, Vec<FetchedItem<T, Url>>) {
+    let mut unsupported = Vec::new();
+    let mut supported = VecDeque::new();
+    for url_pl in urls_pl {
+        match url_pl.unsupported_reason() {
+            Some(reason) => {
+                tracing::info!("skipped `{}`: {}", url_pl, reason);
+                unsupported
+                    .push(FetchedItem::Unsupported(url_pl.into(), reason.into()));
+            }
+            None => supported.push_back(url_pl),
+        }
+    }
+    (supported, unsupported)
+}
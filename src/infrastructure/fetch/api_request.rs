@@ -1,25 +1,129 @@
 use serde_json::Value;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
 
-use super::FetchError;
+use super::{
+    observer::{FetchEvent, SharedObserver},
+    Endpoint, FetchError, Method, QuotaCosts,
+};
 
 pub(super) trait ApiRequest {
-    async fn api_call(&self, url: &str) -> Result<Value, FetchError> {
-        let resp = reqwest::Client::new()
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| FetchError::SendRequest(e.to_string()))?;
-        let status = resp.status();
-        if status.is_client_error() {
-            Err(FetchError::Client(status.to_string()))?
-        } else if status.is_server_error() {
-            Err(FetchError::Server(status.to_string()))?
+    fn observer(&self) -> &SharedObserver;
+
+    fn used_quota(&self) -> &AtomicUsize;
+
+    fn schema_drift(&self) -> &AtomicUsize;
+
+    fn quota_costs(&self) -> &QuotaCosts;
+
+    /// quotaを消費したことを記録する
+    fn spend_quota(&self, cost: usize) {
+        let total = self.used_quota().fetch_add(cost, Ordering::Relaxed) + cost;
+        self.observer().notify(FetchEvent::QuotaSpent { cost, total });
+    }
+
+    /// APIが想定していない値を返したことを記録する
+    fn report_schema_drift(&self, detail: String) {
+        tracing::warn!("unexpected value in api response: {}", detail);
+        self.schema_drift().fetch_add(1, Ordering::Relaxed);
+        self.observer().notify(FetchEvent::SchemaDrift { detail });
+    }
+
+    /// `url`にリクエストを送信する
+    ///
+    /// サーバーエラー, 送信の失敗のときは`MAX_RETRIES`回まで再送する
+    ///
+    /// quotaは応答があったリクエストのみ消費したとみなす, 送信に失敗した, あるいは
+    /// 中断したリクエストは含めない
+    ///
+    /// `cancel`されたとき送信中のリクエストは破棄し`FetchError::Interrupted`を返す
+    ///
+    /// 既に`cancel`されているときはリクエストを送信しない
+    async fn api_call(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Value, FetchError> {
+        let mut attempt: usize = 0;
+        loop {
+            if cancel.is_cancelled() {
+                return Err(FetchError::Interrupted);
+            }
+            self.observer().notify(FetchEvent::RequestStarted { endpoint });
+            let res = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => Err(FetchError::Interrupted),
+                res = send_request(url) => res,
+            };
+            if !matches!(
+                res,
+                Err(FetchError::Interrupted | FetchError::SendRequest(_))
+            ) {
+                self.spend_quota(self.quota_costs().cost(endpoint, Method::List));
+            }
+            self.observer().notify(FetchEvent::RequestFinished {
+                endpoint,
+                success: res.is_ok(),
+            });
+
+            match res {
+                Err(e @ (FetchError::Server(_) | FetchError::SendRequest(_)))
+                    if attempt < Self::MAX_RETRIES =>
+                {
+                    attempt += 1;
+                    let delay = Self::retry_delay(attempt);
+                    tracing::warn!(
+                        "request to `{}` failed: {}, retry after {:?}",
+                        endpoint,
+                        e,
+                        delay
+                    );
+                    self.observer().notify(FetchEvent::RetryScheduled {
+                        endpoint,
+                        attempt,
+                        delay,
+                        reason: e.to_string(),
+                    });
+                    tokio::select! {
+                        biased;
+                        _ = cancel.cancelled() => return Err(FetchError::Interrupted),
+                        _ = tokio::time::sleep(delay) => {},
+                    }
+                }
+                res => return res,
+            }
         }
-        let resp_value: Value =
-            resp.json().await.map_err(|e| FetchError::Deserialize(e.to_string()))?;
-        Ok(resp_value)
+    }
+
+    /// `attempt`回目(1始まり)の再送までの待ち時間, 1秒から倍々に増やす
+    fn retry_delay(attempt: usize) -> Duration {
+        Duration::from_secs(1 << (attempt.saturating_sub(1)).min(6))
     }
 
     /// end of string is `/`
     const BASE_API_URL: &str = "https://www.googleapis.com/youtube/v3/";
+
+    /// 失敗したリクエストを再送する最大回数
+    const MAX_RETRIES: usize = 3;
+}
+
+async fn send_request(url: &str) -> Result<Value, FetchError> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| FetchError::SendRequest(e.to_string()))?;
+    let status = resp.status();
+    if status.is_client_error() {
+        Err(FetchError::Client(status.to_string()))?
+    } else if status.is_server_error() {
+        Err(FetchError::Server(status.to_string()))?
+    }
+    let resp_value: Value =
+        resp.json().await.map_err(|e| FetchError::Deserialize(e.to_string()))?;
+    Ok(resp_value)
 }
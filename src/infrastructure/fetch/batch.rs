@@ -0,0 +1,888 @@
+use futures::{
+    future::{BoxFuture, OptionFuture},
+    stream::{self, BoxStream},
+    Future, FutureExt, Stream, StreamExt,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    api_request::ApiRequest,
+    observer::FetchEvent,
+    playlist::{fetch_data::PlaylistApiClient, PlaylistEntry},
+    response::MalformedItem,
+    video::fetch_data::VideoApiClient,
+    Endpoint, FetchError,
+};
+use crate::{
+    id::VideoId,
+    metadata::{BasicData, BasicPlaylistData, BasicVideoData, FullVideoData},
+    url::{Url, UrlPlaylist, UrlVideo},
+    FetchedItem, MalformedEntry,
+};
+
+/// `batch_stream`で`VideoId`をまとめて処理する操作
+///
+/// 再生リストを項目に展開し, 直接指定された動画と合わせて重複を除いた`VideoId`を
+/// `max_id`個ずつ処理して, その結果を`url`ごとに振り分ける
+pub(super) trait BatchOperation: Send + Sync + 'static {
+    /// 再生リスト内の項目
+    type Entry: Send + 'static;
+    /// 1つの`VideoId`を処理した結果
+    type Outcome: Send + 'static;
+    /// `url`ごとの結果
+    type Output: Send + 'static;
+
+    fn video_client(&self) -> &VideoApiClient;
+
+    /// 再生リスト内の項目を全て取得する, 再生リストが存在しないときは`Ok(Err(url))`
+    fn list(
+        &self,
+        url_pl: UrlPlaylist,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Result<Vec<Self::Entry>, UrlPlaylist>, FetchError>> + Send;
+
+    /// `ids`をまとめて処理し, 全ての`VideoId`の結果を返す
+    ///
+    /// - 必須: `ids`は`max_id`の値以下
+    fn process(
+        &self,
+        ids: &VecDeque<VideoId>,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Vec<(VideoId, Self::Outcome)>, FetchError>> + Send;
+
+    /// 項目のうち処理が必要な`VideoId`, 不要なときは`None`
+    fn entry_id(entry: &Self::Entry) -> Option<&VideoId>;
+
+    /// 直接指定された動画の結果
+    fn video_item(
+        url: UrlVideo,
+        outcome: &Self::Outcome,
+    ) -> FetchedItem<Self::Output, Url>;
+
+    /// 再生リストの結果, `outcomes`は`entry_id`が`Some`の全ての項目の結果を含む
+    fn playlist_item(
+        url: UrlPlaylist,
+        entries: Vec<Self::Entry>,
+        outcomes: &HashMap<VideoId, Self::Outcome>,
+    ) -> FetchedItem<Self::Output, Url>;
+
+    /// 存在しなかった再生リストの結果
+    fn missing_playlist(url: UrlPlaylist) -> FetchedItem<Self::Output, Url>;
+}
+
+/// 一回の取得処理で必要な動画の`VideoId`をまとめて管理する
+///
+/// 直接指定された動画と再生リスト内の動画の間で重複する`VideoId`は一度だけ処理し,
+/// その結果をそれを必要とする全ての`url`へ振り分ける
+pub(super) struct VideoBatch<O: BatchOperation> {
+    /// まだ処理していない`VideoId`, 重複なしで追加した順
+    pending: VecDeque<VideoId>,
+    /// 一度でも`pending`に追加した`VideoId`
+    known: HashSet<VideoId>,
+    /// 処理済みの`VideoId`とその結果
+    outcomes: HashMap<VideoId, O::Outcome>,
+    /// 結果を待っている`url`がその`VideoId`を必要とする数
+    needed: HashMap<VideoId, usize>,
+    /// 全ての再生リストを追加し終えたかどうか, 以降はどの`url`も必要としない結果を破棄する
+    complete: bool,
+    /// 結果を待っている直接指定された動画
+    waiting_videos: VecDeque<UrlVideo>,
+    /// 結果を待っている再生リストとその中の項目
+    waiting_playlists: VecDeque<(UrlPlaylist, Vec<O::Entry>)>,
+}
+
+impl<O: BatchOperation> VideoBatch<O> {
+    pub fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            known: HashSet::new(),
+            outcomes: HashMap::new(),
+            needed: HashMap::new(),
+            complete: false,
+            waiting_videos: VecDeque::new(),
+            waiting_playlists: VecDeque::new(),
+        }
+    }
+
+    /// 直接指定された動画を追加する
+    pub fn add_video(&mut self, url: UrlVideo) {
+        let id: &VideoId = (&url).into();
+        self.request(id.clone());
+        self.waiting_videos.push_back(url);
+    }
+
+    /// 再生リストとその中の項目を追加する
+    pub fn add_playlist(&mut self, url: UrlPlaylist, entries: Vec<O::Entry>) {
+        for id in entries.iter().filter_map(O::entry_id) {
+            self.request(id.clone());
+        }
+        self.waiting_playlists.push_back((url, entries));
+    }
+
+    fn request(&mut self, id: VideoId) {
+        *self.needed.entry(id.clone()).or_default() += 1;
+        if self.known.insert(id.clone()) {
+            self.pending.push_back(id);
+        }
+    }
+
+    /// 結果を取り出した`url`が必要としていた`VideoId`を手放す
+    fn release(&mut self, id: &VideoId) {
+        let Some(count) = self.needed.get_mut(id) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.needed.remove(id);
+            if self.complete {
+                self.outcomes.remove(id);
+            }
+        }
+    }
+
+    /// 全ての再生リストを追加し終えたことを記録し, どの`url`も必要としない結果を破棄する
+    ///
+    /// それまでは後から追加される再生リストが必要とするかもしれないので結果を残す
+    pub fn finish_adding(&mut self) {
+        self.complete = true;
+        let needed = &self.needed;
+        self.outcomes.retain(|id, _| needed.contains_key(id));
+    }
+
+    /// まだ処理していない`VideoId`があるかどうか
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 次に処理する最大`max`個の`VideoId`を取り出す
+    pub fn next_chunk(&mut self, max: usize) -> VecDeque<VideoId> {
+        self.pending.drain(..max.min(self.pending.len())).collect()
+    }
+
+    /// 処理した結果を記録する
+    pub fn record(&mut self, outcomes: Vec<(VideoId, O::Outcome)>) {
+        self.outcomes.extend(outcomes);
+    }
+
+    /// 必要な動画を全て処理し終えた`url`の結果を取り出す
+    pub fn take_resolved(&mut self) -> Vec<FetchedItem<O::Output, Url>> {
+        let mut res = Vec::new();
+
+        let (resolved, waiting) = std::mem::take(&mut self.waiting_videos)
+            .into_iter()
+            .partition(|url| self.outcomes.contains_key(<&VideoId>::from(url)));
+        self.waiting_videos = waiting;
+        for url in resolved {
+            let id = <&VideoId>::from(&url).clone();
+            res.push(O::video_item(url, &self.outcomes[&id]));
+            self.release(&id);
+        }
+
+        let (resolved, waiting) = std::mem::take(&mut self.waiting_playlists)
+            .into_iter()
+            .partition(|(_, entries)| {
+                entries
+                    .iter()
+                    .filter_map(O::entry_id)
+                    .all(|id| self.outcomes.contains_key(id))
+            });
+        self.waiting_playlists = waiting;
+        for (url, entries) in resolved {
+            let ids: Vec<VideoId> =
+                entries.iter().filter_map(O::entry_id).cloned().collect();
+            res.push(O::playlist_item(url, entries, &self.outcomes));
+            for id in &ids {
+                self.release(id);
+            }
+        }
+        res
+    }
+
+    /// 結果を待っている全ての`url`を取り出す
+    pub fn take_unresolved(&mut self) -> Vec<Url> {
+        self.pending.clear();
+        let videos = self.waiting_videos.drain(..).map(Into::into);
+        let playlists = self.waiting_playlists.drain(..).map(|(url, _)| url.into());
+        videos.chain(playlists).collect()
+    }
+}
+
+/// 並行して項目を取得する再生リストの最大数
+const MAX_CONCURRENT_LISTINGS: usize = 8;
+
+type Listing<E> = (UrlPlaylist, Result<Result<Vec<E>, UrlPlaylist>, FetchError>);
+
+type Processed<T> = Result<Vec<(VideoId, T)>, FetchError>;
+
+type BatchItem<O> = Result<FetchedItem<<O as BatchOperation>::Output, Url>, FetchError>;
+
+struct BatchState<O: BatchOperation> {
+    op: Arc<O>,
+    listings: BoxStream<'static, Listing<O::Entry>>,
+    listings_done: bool,
+    /// 項目を取得し終えていない再生リスト
+    unlisted: Vec<UrlPlaylist>,
+    /// 処理中の`VideoId`
+    chunk: Option<BoxFuture<'static, Processed<O::Outcome>>>,
+    batch: VideoBatch<O>,
+    cancel: CancellationToken,
+}
+
+enum Step<O: BatchOperation> {
+    Listed(Option<Listing<O::Entry>>),
+    Processed(Processed<O::Outcome>),
+}
+
+impl<O: BatchOperation> BatchState<O> {
+    /// 次に返す結果と, それを返した後に終了するかどうか
+    ///
+    /// 返す結果がもう無いときは`None`
+    async fn advance(&mut self) -> Option<(Vec<BatchItem<O>>, bool)> {
+        loop {
+            // 再生リストの取得を待たずに, その時点で集まっている`VideoId`を処理する
+            if self.chunk.is_none() && self.batch.has_pending() {
+                let ids = self.batch.next_chunk(self.op.video_client().max_id());
+                let op = Arc::clone(&self.op);
+                let cancel = self.cancel.clone();
+                self.chunk =
+                    Some(async move { op.process(&ids, &cancel).await }.boxed());
+            }
+            let step = tokio::select! {
+                Some(res) = OptionFuture::from(self.chunk.as_mut()), if self.chunk.is_some() => {
+                    Step::<O>::Processed(res)
+                }
+                listing = self.listings.next(), if !self.listings_done => {
+                    Step::Listed(listing)
+                }
+                else => return None,
+            };
+            let items = match step {
+                Step::Listed(None) => {
+                    self.listings_done = true;
+                    self.batch.finish_adding();
+                    continue;
+                }
+                Step::Listed(Some((url_pl, res))) => {
+                    let res = match res {
+                        Ok(res) => res,
+                        Err(e) => return Some((self.abort(e), true)),
+                    };
+                    if let Some(i) = self.unlisted.iter().position(|url| *url == url_pl)
+                    {
+                        self.unlisted.remove(i);
+                    }
+                    match res {
+                        Ok(entries) => {
+                            self.batch.add_playlist(url_pl, entries);
+                            self.resolved()
+                        }
+                        Err(url_pl) => vec![Ok(O::missing_playlist(url_pl))],
+                    }
+                }
+                Step::Processed(res) => {
+                    self.chunk = None;
+                    match res {
+                        Ok(outcomes) => {
+                            self.batch.record(outcomes);
+                            self.resolved()
+                        }
+                        Err(e) => return Some((self.abort(e), true)),
+                    }
+                }
+            };
+            if !items.is_empty() {
+                return Some((items, false));
+            }
+        }
+    }
+
+    fn resolved(&mut self) -> Vec<BatchItem<O>> {
+        self.batch.take_resolved().into_iter().map(Ok).collect()
+    }
+
+    /// 結果が確定した`url`の数を通知する
+    fn notify_resolved(&self, items: &[BatchItem<O>]) {
+        let urls = items.iter().filter(|item| item.is_ok()).count();
+        if urls != 0 {
+            self.op.video_client().observer().notify(FetchEvent::UrlsResolved { urls });
+        }
+    }
+
+    /// 結果を返していない`url`を`Unattempted`として返す
+    ///
+    /// 中断以外のエラーはその後に返す
+    fn abort(&mut self, e: FetchError) -> Vec<BatchItem<O>> {
+        let unlisted = self.unlisted.drain(..).map(Into::into);
+        let mut items: Vec<BatchItem<O>> = self
+            .batch
+            .take_unresolved()
+            .into_iter()
+            .chain(unlisted)
+            .map(|url| Ok(FetchedItem::Unattempted(url)))
+            .collect();
+        match e {
+            FetchError::Interrupted => {
+                tracing::debug!("batch processing is interrupted")
+            }
+            e => items.push(Err(e)),
+        }
+        items
+    }
+}
+
+/// 最大`MAX_CONCURRENT_LISTINGS`個の再生リストの項目を並行して取得しながら,
+/// 直接指定された動画と合わせて重複を除き`max_id`個ずつまとめて`op`で処理する
+///
+/// 必要な動画を全て処理し終えた`url`から順に返す
+///
+/// `cancel`されたとき, あるいは失敗したとき, 結果を返していない`url`を`Unattempted`として返す
+///
+/// 失敗したときはその後に`Err`を返して終了する
+pub(super) fn batch_stream<O: BatchOperation>(
+    op: O,
+    urls_v: VecDeque<UrlVideo>,
+    urls_pl: VecDeque<UrlPlaylist>,
+    cancel: CancellationToken,
+) -> impl Stream<Item = BatchItem<O>> {
+    let mut batch = VideoBatch::new();
+    for url in urls_v {
+        batch.add_video(url);
+    }
+    let op = Arc::new(op);
+    let listings = {
+        let op = Arc::clone(&op);
+        let cancel = cancel.clone();
+        stream::iter(urls_pl.clone())
+            .map(move |url_pl| {
+                let op = Arc::clone(&op);
+                let cancel = cancel.clone();
+                async move {
+                    let res = op.list(url_pl.clone(), &cancel).await;
+                    (url_pl, res)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_LISTINGS)
+            .boxed()
+    };
+    let state = BatchState {
+        op,
+        listings,
+        listings_done: false,
+        unlisted: urls_pl.into(),
+        chunk: None,
+        batch,
+        cancel,
+    };
+
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        let (items, finished) = state.advance().await?;
+        state.notify_resolved(&items);
+        Some((items, (!finished).then_some(state)))
+    })
+    .flat_map(stream::iter)
+}
+
+/// 取得済みの`VideoId`の結果
+#[derive(Debug, Clone)]
+pub(super) enum Fetched {
+    Found(FullVideoData),
+    NotFound,
+    /// レスポンスの`item`をデシリアライズできなかった
+    Malformed(String),
+}
+
+/// 動画の情報を取得する
+pub(super) struct BasicDataOperation {
+    video_client: VideoApiClient,
+    playlist_client: PlaylistApiClient,
+}
+
+impl BasicDataOperation {
+    pub fn new(
+        video_client: VideoApiClient,
+        playlist_client: PlaylistApiClient,
+    ) -> Self {
+        Self {
+            video_client,
+            playlist_client,
+        }
+    }
+}
+
+impl BatchOperation for BasicDataOperation {
+    /// デシリアライズできなかった`item`は`Err`
+    type Entry = Result<PlaylistEntry, MalformedEntry>;
+    type Outcome = Fetched;
+    type Output = BasicData;
+
+    fn video_client(&self) -> &VideoApiClient {
+        &self.video_client
+    }
+
+    async fn list(
+        &self,
+        url_pl: UrlPlaylist,
+        cancel: &CancellationToken,
+    ) -> Result<Result<Vec<Self::Entry>, UrlPlaylist>, FetchError> {
+        self.playlist_client.fetch_video_ids(url_pl, cancel).await
+    }
+
+    async fn process(
+        &self,
+        ids: &VecDeque<VideoId>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<(VideoId, Fetched)>, FetchError> {
+        let (videos, malformed) = self.video_client.fetch_chunk(ids, cancel).await?;
+        Ok(fetched_outcomes(ids, videos, malformed))
+    }
+
+    fn entry_id(entry: &Self::Entry) -> Option<&VideoId> {
+        entry.as_ref().ok().map(|entry| &entry.id)
+    }
+
+    fn video_item(url: UrlVideo, outcome: &Fetched) -> FetchedItem<BasicData, Url> {
+        match outcome.clone() {
+            Fetched::Found(data) => FetchedItem::Found(
+                BasicVideoData::from(data).with_start(url.start()).into(),
+            ),
+            Fetched::NotFound => FetchedItem::NotFound(url.into()),
+            Fetched::Malformed(reason) => FetchedItem::Malformed(url.into(), reason),
+        }
+    }
+
+    /// 再生リスト内の存在しなかった動画は結果に含めない
+    ///
+    /// デシリアライズできなかった動画は`malformed`に含める
+    fn playlist_item(
+        url: UrlPlaylist,
+        entries: Vec<Self::Entry>,
+        outcomes: &HashMap<VideoId, Fetched>,
+    ) -> FetchedItem<BasicData, Url> {
+        let mut videos = VecDeque::new();
+        let mut malformed = Vec::new();
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    malformed.push(e);
+                    continue;
+                }
+            };
+            match &outcomes[&entry.id] {
+                Fetched::Found(data) => {
+                    let mut video: BasicVideoData = data.clone().into();
+                    if let Some(raw) = entry.raw {
+                        video.insert_raw(Endpoint::PlaylistItems.path(), raw);
+                    }
+                    videos.push_back(video);
+                }
+                Fetched::NotFound => {}
+                Fetched::Malformed(reason) => malformed.push(MalformedEntry {
+                    video_id: Some(entry.id),
+                    reason: reason.clone(),
+                }),
+            }
+        }
+        let data = BasicPlaylistData::new(videos, url.get_playlist_id().clone())
+            .with_position(url.position())
+            .with_selected_video(url.get_video_id().clone())
+            .with_malformed(malformed);
+        FetchedItem::Found(data.into())
+    }
+
+    fn missing_playlist(url: UrlPlaylist) -> FetchedItem<BasicData, Url> {
+        FetchedItem::NotFound(url.into())
+    }
+}
+
+/// `ids`を取得した結果を`VideoId`ごとに振り分ける
+///
+/// `videos`にも`malformed`にも含まれない`VideoId`は存在しなかったとみなす,
+/// ただしその数が`id`を読み取れない`malformed`の数と一致するときは, それらが対応するので`Malformed`とする
+fn fetched_outcomes(
+    ids: &VecDeque<VideoId>,
+    videos: VecDeque<FullVideoData>,
+    malformed: Vec<MalformedItem>,
+) -> Vec<(VideoId, Fetched)> {
+    let mut found: HashMap<VideoId, FullVideoData> =
+        videos.into_iter().map(|v| (v.basic_v_data.id.clone(), v)).collect();
+    let mut unidentified: Vec<String> = Vec::new();
+    let mut broken: HashMap<String, String> = HashMap::new();
+    for item in malformed {
+        match item.id {
+            Some(id) => {
+                broken.insert(id, item.reason);
+            }
+            None => unidentified.push(item.reason),
+        }
+    }
+    let mut outcomes: Vec<(VideoId, Option<Fetched>)> = ids
+        .iter()
+        .map(|id| {
+            let res = if let Some(data) = found.remove(id) {
+                Some(Fetched::Found(data))
+            } else {
+                broken.remove(&id.to_string()).map(Fetched::Malformed)
+            };
+            (id.clone(), res)
+        })
+        .collect();
+    let missing = outcomes.iter().filter(|(_, res)| res.is_none()).count();
+    let identified = !unidentified.is_empty() && missing == unidentified.len();
+    if !unidentified.is_empty() && !identified {
+        tracing::warn!(
+            "skipped {} malformed items without id: {}",
+            unidentified.len(),
+            unidentified.join("; ")
+        );
+    }
+    for (_, res) in outcomes.iter_mut().filter(|(_, res)| res.is_none()) {
+        *res = Some(if identified {
+            Fetched::Malformed(format!(
+                "response contains malformed items without id: {}",
+                unidentified.join("; ")
+            ))
+        } else {
+            Fetched::NotFound
+        });
+    }
+    outcomes.into_iter().map(|(id, res)| (id, res.unwrap())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{id::PlaylistId, url::Position};
+
+    fn playlist(id: PlaylistId) -> UrlPlaylist {
+        (None, id).into()
+    }
+
+    fn entries(ids: Vec<VideoId>) -> Vec<Result<PlaylistEntry, MalformedEntry>> {
+        ids.into_iter().map(|id| Ok(PlaylistEntry { id, raw: None })).collect()
+    }
+
+    #[test]
+    fn test_video_batch_dedup_across_playlists() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()),
+            entries(vec![VideoId::all_1(), VideoId::all_2()]),
+        );
+        batch.add_playlist(
+            playlist(PlaylistId::all_2()),
+            entries(vec![VideoId::all_2(), VideoId::all_1()]),
+        );
+
+        // 重複を除いた2件のみを取得する
+        assert_eq!(
+            batch.next_chunk(50),
+            VecDeque::from(vec![VideoId::all_1(), VideoId::all_2()])
+        );
+        assert!(!batch.has_pending());
+    }
+
+    #[test]
+    fn test_video_batch_next_chunk_packs_max() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()),
+            entries(vec![VideoId::all_1()]),
+        );
+        batch.add_playlist(
+            playlist(PlaylistId::all_2()),
+            entries(vec![VideoId::all_2()]),
+        );
+
+        // 再生リストをまたいで`max`個までまとめる
+        assert_eq!(batch.next_chunk(2).len(), 2);
+
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        batch.add_video(VideoId::all_2().into());
+        assert_eq!(batch.next_chunk(1), VecDeque::from(vec![VideoId::all_1()]));
+        assert_eq!(batch.next_chunk(1), VecDeque::from(vec![VideoId::all_2()]));
+    }
+
+    #[test]
+    fn test_video_batch_fan_out() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        batch.add_video(VideoId::all_2().into());
+        batch.add_playlist(
+            playlist(PlaylistId::all_2()),
+            entries(vec![VideoId::all_1(), VideoId::all_2()]),
+        );
+
+        // `all_1`のみ取得できた
+        let ids = batch.next_chunk(1);
+        batch.record(fetched_outcomes(
+            &ids,
+            vec![FullVideoData::self_1()].into(),
+            vec![],
+        ));
+        assert_eq!(
+            batch.take_resolved(),
+            vec![FetchedItem::Found(FullVideoData::self_1().into())]
+        );
+
+        // `all_2`は存在しなかった
+        let ids = batch.next_chunk(1);
+        batch.record(fetched_outcomes(&ids, VecDeque::new(), vec![]));
+        let expected_pl = BasicPlaylistData::new(
+            vec![BasicVideoData::self_1()].into(),
+            PlaylistId::all_2(),
+        );
+        assert_eq!(
+            batch.take_resolved(),
+            vec![
+                FetchedItem::NotFound(UrlVideo::from(VideoId::all_2()).into()),
+                FetchedItem::Found(expected_pl.into()),
+            ]
+        );
+        assert!(batch.take_unresolved().is_empty());
+    }
+
+    #[test]
+    fn test_video_batch_keeps_position() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        // 同じ動画でも再生を始める位置ごとに出力する
+        batch.add_video(UrlVideo::from(VideoId::all_1()).with_start(Some(90)));
+        batch.add_video(VideoId::all_1().into());
+        let position = Position::new(Some(30), Some(2));
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()).with_position(position),
+            entries(vec![VideoId::all_1()]),
+        );
+        let ids = batch.next_chunk(50);
+        batch.record(fetched_outcomes(
+            &ids,
+            vec![FullVideoData::self_1()].into(),
+            vec![],
+        ));
+
+        let expected_pl = BasicPlaylistData::new(
+            vec![BasicVideoData::self_1()].into(),
+            PlaylistId::all_1(),
+        )
+        .with_position(position);
+        assert_eq!(
+            batch.take_resolved(),
+            vec![
+                FetchedItem::Found(
+                    BasicVideoData::self_1().with_start(Some(90)).into()
+                ),
+                FetchedItem::Found(BasicVideoData::self_1().into()),
+                FetchedItem::Found(expected_pl.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_video_batch_marks_selected_video() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        let position = Position::new(Some(30), None);
+        batch.add_playlist(
+            UrlPlaylist::new(Some(VideoId::all_2()), PlaylistId::all_1())
+                .with_position(position),
+            entries(vec![VideoId::all_1(), VideoId::all_2()]),
+        );
+        let ids = batch.next_chunk(50);
+        batch.record(fetched_outcomes(
+            &ids,
+            vec![FullVideoData::self_1(), FullVideoData::self_2()].into(),
+            vec![],
+        ));
+
+        let mut selected = BasicVideoData::self_2().with_start(Some(30));
+        selected.selected = true;
+        let mut expected_pl = BasicPlaylistData::new(
+            vec![BasicVideoData::self_1(), selected].into(),
+            PlaylistId::all_1(),
+        )
+        .with_position(position);
+        expected_pl.playlist_data_itself.selected_video = Some(VideoId::all_2());
+        assert_eq!(
+            batch.take_resolved(),
+            vec![FetchedItem::Found(expected_pl.into())]
+        );
+    }
+
+    #[test]
+    fn test_video_batch_malformed() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        let unreadable = MalformedEntry {
+            video_id: None,
+            reason: "missing field `snippet`".into(),
+        };
+        let mut items = entries(vec![VideoId::all_1(), VideoId::all_2()]);
+        items.push(Err(unreadable.clone()));
+        batch.add_playlist(playlist(PlaylistId::all_1()), items);
+        let ids = batch.next_chunk(50);
+        let malformed = vec![MalformedItem {
+            id: Some(VideoId::all_1().to_string()),
+            reason: "unknown variant".into(),
+        }];
+        batch.record(fetched_outcomes(
+            &ids,
+            vec![FullVideoData::self_2()].into(),
+            malformed,
+        ));
+
+        // 解釈できなかった動画のみ除いて`malformed`に含め, 他は残す
+        let expected_pl = BasicPlaylistData::new(
+            vec![BasicVideoData::self_2()].into(),
+            PlaylistId::all_1(),
+        )
+        .with_malformed(vec![
+            MalformedEntry {
+                video_id: Some(VideoId::all_1()),
+                reason: "unknown variant".into(),
+            },
+            unreadable,
+        ]);
+        assert_eq!(
+            batch.take_resolved(),
+            vec![
+                FetchedItem::Malformed(
+                    UrlVideo::from(VideoId::all_1()).into(),
+                    "unknown variant".into()
+                ),
+                FetchedItem::Found(expected_pl.into()),
+            ]
+        );
+
+        // `id`を読み取れない`item`と見つからなかった動画の数が一致するときは`Malformed`とする
+        let ids = VecDeque::from(vec![VideoId::all_1()]);
+        let malformed = vec![MalformedItem {
+            id: None,
+            reason: "missing field `id`".into(),
+        }];
+        assert!(matches!(
+            fetched_outcomes(&ids, VecDeque::new(), malformed)[..],
+            [(_, Fetched::Malformed(_))]
+        ));
+
+        // 一致しないときは対応が分からないので見つからなかったとする
+        let ids =
+            VecDeque::from(vec![VideoId::all_1(), VideoId::all_2(), VideoId::all_0()]);
+        let malformed = vec![MalformedItem {
+            id: None,
+            reason: "missing field `id`".into(),
+        }];
+        let outcomes =
+            fetched_outcomes(&ids, vec![FullVideoData::self_2()].into(), malformed);
+        assert!(matches!(
+            outcomes[..],
+            [
+                (_, Fetched::NotFound),
+                (_, Fetched::Found(_)),
+                (_, Fetched::NotFound)
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_video_batch_take_unresolved() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        batch.add_playlist(playlist(PlaylistId::all_1()), Vec::new());
+        batch.add_playlist(
+            playlist(PlaylistId::all_2()),
+            entries(vec![VideoId::all_2()]),
+        );
+
+        // 動画を含まない再生リストはすぐに結果を返せる
+        let resolved = batch.take_resolved();
+        assert_eq!(resolved.len(), 1);
+
+        assert_eq!(
+            batch.take_unresolved(),
+            vec![
+                Url::from(UrlVideo::from(VideoId::all_1())),
+                Url::from(playlist(PlaylistId::all_2())),
+            ]
+        );
+        assert!(!batch.has_pending());
+    }
+
+    #[test]
+    fn test_video_batch_prunes_outcomes() {
+        let mut batch = VideoBatch::<BasicDataOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        let ids = batch.next_chunk(50);
+        batch.record(fetched_outcomes(
+            &ids,
+            vec![FullVideoData::self_1()].into(),
+            vec![],
+        ));
+        assert_eq!(batch.take_resolved().len(), 1);
+
+        // 後から追加される再生リストが必要とするかもしれないので残す
+        assert!(batch.outcomes.contains_key(&VideoId::all_1()));
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()),
+            entries(vec![VideoId::all_1(), VideoId::all_2()]),
+        );
+        assert_eq!(batch.next_chunk(50), VecDeque::from(vec![VideoId::all_2()]));
+        batch.finish_adding();
+        assert!(batch.outcomes.contains_key(&VideoId::all_1()));
+
+        // どの`url`も必要としなくなった結果は破棄する
+        batch.record(fetched_outcomes(
+            &VecDeque::from(vec![VideoId::all_2()]),
+            VecDeque::new(),
+            vec![],
+        ));
+        assert_eq!(batch.take_resolved().len(), 1);
+        assert!(batch.outcomes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_stream_cancelled_before_request() {
+        use super::super::{
+            observer::{tests::RecordObserver, SharedObserver},
+            ApiKey,
+        };
+        use crate::FetchedResults;
+        use std::sync::atomic::AtomicUsize;
+
+        let used_quota = Arc::new(AtomicUsize::new(0));
+        let record = Arc::new(RecordObserver::default());
+        let video_client = VideoApiClient::new_default(
+            ApiKey::new("dummy".into()),
+            Arc::clone(&used_quota),
+            SharedObserver::new(record.clone()),
+        );
+        let playlist_client = PlaylistApiClient::new_default(
+            ApiKey::new("dummy".into()),
+            Arc::clone(&used_quota),
+            SharedObserver::default(),
+        );
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        // 中断済みなのでリクエストは送信されず, 全て`unattempted`になる
+        let stream = batch_stream(
+            BasicDataOperation::new(video_client, playlist_client),
+            vec![VideoId::all_1().into(), VideoId::all_2().into()].into(),
+            vec![playlist(PlaylistId::all_1())].into(),
+            cancel,
+        );
+        let res = FetchedResults::try_collect(stream).await.unwrap();
+        assert!(res.fetched.is_empty());
+        assert_eq!(res.unattempted.len(), 3);
+        // `unattempted`も結果が確定した`url`として通知する
+        assert_eq!(
+            *record.0.lock().unwrap(),
+            vec![FetchEvent::UrlsResolved { urls: 3 }]
+        );
+    }
+}
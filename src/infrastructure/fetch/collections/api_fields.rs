@@ -0,0 +1,225 @@
+use super::Endpoint;
+use crate::metadata::{VideoField, VideoProjection};
+
+/// レスポンスに含めるフィールド(`fields`パラメータ)
+///
+/// 詳細については以下参照
+///
+/// - partial response:
+///   https://developers.google.com/youtube/v3/getting-started#partial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiFields {
+    /// `fields`を指定せず, 全てのフィールドを含める
+    All,
+    /// 出力する動画の項目から求める
+    Projection(VideoProjection),
+    /// 指定した値をエンドポイントごとにそのまま使用する, `None`のときは指定しない
+    ///
+    /// `kind`, 動画のid, `liveBroadcastContent`(videos),
+    /// `title`と`description`(playlistItems)が含まれていないとデシリアライズできない
+    Explicit {
+        videos: Option<String>,
+        playlist_items: Option<String>,
+    },
+}
+
+impl ApiFields {
+    /// 存在の確認に必要なフィールドのみ
+    ///
+    /// `snippet`を含めないので`Projection`の出力には使用できない
+    pub(in super::super) fn existence() -> Self {
+        Self::Explicit {
+            videos: Some("items/id".into()),
+            playlist_items: Some(
+                "nextPageToken,pageInfo,items(contentDetails/videoId,status/privacyStatus)".into(),
+            ),
+        }
+    }
+
+    /// `endpoint`へのリクエストに付与する`fields`の値, `None`のときは付与しない
+    ///
+    /// `part_fields`は要求した`part`によって`items`に追加されるフィールドで,
+    /// `Projection`のときはこれも含める
+    pub fn for_endpoint(
+        &self,
+        endpoint: Endpoint,
+        part_fields: &[&str],
+    ) -> Option<String> {
+        match self {
+            Self::All => None,
+            Self::Projection(projection) => {
+                projection_mask(projection, endpoint, part_fields)
+            }
+            Self::Explicit {
+                videos,
+                playlist_items,
+            } => match endpoint {
+                Endpoint::Videos => videos.clone(),
+                Endpoint::PlaylistItems => playlist_items.clone(),
+                Endpoint::Playlists | Endpoint::Channels | Endpoint::Search => None,
+            },
+        }
+    }
+
+    /// リクエストの`url`に続けるクエリ, `fields`を付与しないときは空文字列
+    ///
+    /// 値はパーセントエンコードする
+    pub(in super::super) fn to_query(
+        &self,
+        endpoint: Endpoint,
+        part_fields: &[&str],
+    ) -> String {
+        self.for_endpoint(endpoint, part_fields)
+            .map(|fields| format!("&fields={}", percent_encode(&fields)))
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ApiFields {
+    fn default() -> Self {
+        Self::Projection(VideoProjection::default())
+    }
+}
+
+/// 出力する項目に加え, デシリアライズに必須のフィールドを常に含める
+///
+/// 動画の情報を含まないエンドポイントには付与しない
+fn projection_mask(
+    projection: &VideoProjection,
+    endpoint: Endpoint,
+    part_fields: &[&str],
+) -> Option<String> {
+    // `snippet`と`id`は以下で必要なものだけを指定する
+    let extra = part_fields
+        .iter()
+        .filter(|field| !matches!(**field, "snippet" | "id"))
+        .fold(String::new(), |acc, field| acc + "," + field);
+    match endpoint {
+        Endpoint::Videos => {
+            let mut snippet = vec!["liveBroadcastContent"];
+            for field in projection.fields() {
+                match field {
+                    // `id`は結果を`url`と対応付けるので常に含める
+                    VideoField::Id => {}
+                    VideoField::UploadAt => snippet.push("publishedAt"),
+                    VideoField::Title => snippet.push("title"),
+                    VideoField::Description => snippet.push("description"),
+                    VideoField::ChannelId => snippet.push("channelId"),
+                    VideoField::ChannelTitle => snippet.push("channelTitle"),
+                }
+            }
+            Some(format!(
+                "kind,items(kind,id,snippet({}){})",
+                snippet.join(","),
+                extra
+            ))
+        }
+        // 動画の情報は`videos`から取得するので`VideoId`のみでよい
+        // 非公開や削除済みの動画の判断に`title`と`description`を使用する
+        Endpoint::PlaylistItems => Some(format!(
+            "kind,nextPageToken,pageInfo,items(kind,snippet(title,description,resourceId/videoId){})",
+            extra
+        )),
+        Endpoint::Playlists | Endpoint::Channels | Endpoint::Search => None,
+    }
+}
+
+/// 英数字と`-._~`以外をパーセントエンコードする
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_fields_projection() {
+        let fields = ApiFields::Projection(VideoProjection::new(vec![
+            VideoField::Id,
+            VideoField::Title,
+        ]));
+        assert_eq!(
+            fields.for_endpoint(Endpoint::Videos, &[]).unwrap(),
+            "kind,items(kind,id,snippet(liveBroadcastContent,title))"
+        );
+        assert_eq!(
+            fields.for_endpoint(Endpoint::PlaylistItems, &[]).unwrap(),
+            "kind,nextPageToken,pageInfo,items(kind,snippet(title,description,resourceId/videoId))"
+        );
+    }
+
+    #[test]
+    fn test_api_fields_projection_with_part_fields() {
+        let fields =
+            ApiFields::Projection(VideoProjection::new(vec![VideoField::Title]));
+        assert_eq!(
+            fields.for_endpoint(Endpoint::Videos, &["snippet", "statistics"]).unwrap(),
+            "kind,items(kind,id,snippet(liveBroadcastContent,title),statistics)"
+        );
+        assert_eq!(
+            fields
+                .for_endpoint(Endpoint::PlaylistItems, &["snippet", "status"])
+                .unwrap(),
+            "kind,nextPageToken,pageInfo,items(kind,snippet(title,description,resourceId/videoId),status)"
+        );
+        assert_eq!(
+            ApiFields::All.for_endpoint(Endpoint::Videos, &["statistics"]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_api_fields_all_and_explicit() {
+        assert_eq!(ApiFields::All.for_endpoint(Endpoint::Videos, &[]), None);
+
+        let fields = ApiFields::Explicit {
+            videos: Some("kind,items(kind,id,snippet(liveBroadcastContent))".into()),
+            playlist_items: None,
+        };
+        assert_eq!(
+            fields.for_endpoint(Endpoint::Videos, &[]).unwrap(),
+            "kind,items(kind,id,snippet(liveBroadcastContent))"
+        );
+        assert_eq!(fields.for_endpoint(Endpoint::PlaylistItems, &[]), None);
+    }
+
+    #[test]
+    fn test_api_fields_existence() {
+        let fields = ApiFields::existence();
+        assert_eq!(
+            fields.for_endpoint(Endpoint::Videos, &[]).unwrap(),
+            "items/id"
+        );
+        assert_eq!(
+            fields.for_endpoint(Endpoint::PlaylistItems, &["status"]).unwrap(),
+            "nextPageToken,pageInfo,items(contentDetails/videoId,status/privacyStatus)"
+        );
+    }
+
+    #[test]
+    fn test_api_fields_to_query_encodes() {
+        let fields = ApiFields::Explicit {
+            videos: Some("kind,items(id,snippet/title)".into()),
+            playlist_items: Some("a b&c=d".into()),
+        };
+        assert_eq!(
+            fields.to_query(Endpoint::Videos, &[]),
+            "&fields=kind%2Citems%28id%2Csnippet%2Ftitle%29"
+        );
+        assert_eq!(
+            fields.to_query(Endpoint::PlaylistItems, &[]),
+            "&fields=a%20b%26c%3Dd"
+        );
+        assert_eq!(ApiFields::All.to_query(Endpoint::Videos, &[]), "");
+    }
+}
@@ -1,67 +1,128 @@
-#[derive(Debug, Clone)]
-pub struct ApiOptionsPart(Vec<String>);
+use super::parts::{Part, VideoPart};
 
-impl ApiOptionsPart {
-    /// キーの詳細については以下参照
-    ///
-    /// - video:
-    ///     https://developers.google.com/youtube/v3/docs/videos/list#part
-    ///
-    /// - playlist:
-    ///     https://developers.google.com/youtube/v3/docs/playlists/list#part
+/// リクエストする`part`の組み合わせ
+///
+/// `P`で指定したエンドポイントの`part`のみを含められる
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiOptionsPart<P: Part = VideoPart>(Vec<P>);
+
+impl<P: Part> ApiOptionsPart<P> {
+    /// キーの詳細については`P`のドキュメント参照
     ///
     /// `Err`: 必須のオプションが含まれていないとき
     ///
-    /// `Self::required_keys()`で確認可能
-    pub fn new(part: Vec<String>) -> Result<Self, String> {
-        let required_keys = Self::required_parts();
+    /// `Self::required_parts()`で確認可能
+    pub fn new(part: Vec<P>) -> Result<Self, String> {
+        let required_parts = Self::required_parts();
 
-        if !part.iter().any(|part_key| required_keys.contains(part_key)) {
+        if !part.iter().any(|part_key| required_parts.contains(part_key)) {
             Err(format!(
-                "keys:`{}` is required to create `ApiOptionKey`, but gives `{}`",
-                required_keys.join(", "),
-                part.join(", ")
+                "parts:`{}` is required to request `{}`, but gives `{}`",
+                join_names(required_parts),
+                P::ENDPOINT,
+                join_names(&part)
             ))
         } else {
-            Ok(Self(part))
+            let mut unique: Vec<P> = Vec::new();
+            for part_key in part {
+                if !unique.contains(&part_key) {
+                    unique.push(part_key);
+                }
+            }
+            Ok(Self(unique))
+        }
+    }
+
+    /// 必須の`part`を確認せずに作成する
+    ///
+    /// 存在の確認など`snippet`を必要としないリクエストで使用する
+    pub(in super::super) fn exact(parts: Vec<P>) -> Self {
+        Self(Vec::new()).with_extra(parts)
+    }
+
+    pub fn required_parts() -> &'static [P] {
+        P::REQUIRED
+    }
+
+    /// `extra`のうちまだ含まれていない`part`を追加する
+    pub fn with_extra(mut self, extra: Vec<P>) -> Self {
+        for part in extra {
+            if !self.0.contains(&part) {
+                self.0.push(part);
+            }
         }
+        self
     }
 
-    pub fn required_parts() -> Vec<String> {
-        // 今のところ必須なものが共通なのでこれで大丈夫
-        const REQUIRED_KEY_1: &str = "snippet";
-        vec![REQUIRED_KEY_1.into()]
+    pub fn parts(&self) -> &[P] {
+        &self.0
+    }
+
+    /// レスポンスの`items`に含まれるフィールド
+    pub fn fields(&self) -> Vec<&'static str> {
+        self.0.iter().flat_map(|part| part.fields()).copied().collect()
     }
 
     pub fn join(&self, sep: &str) -> String {
-        self.0.join(sep)
+        self.0.iter().map(|part| part.name()).collect::<Vec<_>>().join(sep)
     }
 }
 
-impl Default for ApiOptionsPart {
+impl<P: Part> Default for ApiOptionsPart<P> {
     fn default() -> Self {
-        Self::new(Self::required_parts()).unwrap()
+        Self::new(Self::required_parts().to_vec()).unwrap()
     }
 }
 
+fn join_names<P: Part>(parts: &[P]) -> String {
+    ApiOptionsPart(parts.to_vec()).join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::infrastructure::fetch::collections::parts::PlaylistItemPart;
 
     #[test]
     fn test_api_option_key_for_new() {
         let valid_keys =
-            vec!["snippet".to_string(), "foo".to_string(), "bar".to_string()];
+            vec![VideoPart::Snippet, VideoPart::Statistics, VideoPart::Status];
         assert!(ApiOptionsPart::new(valid_keys).is_ok());
 
-        let invalid_keys = vec!["foo".to_string(), "bar".to_string()];
-        assert!(ApiOptionsPart::new(invalid_keys).is_err());
+        let invalid_keys = vec![VideoPart::Statistics, VideoPart::Status];
+        let err = ApiOptionsPart::new(invalid_keys).unwrap_err();
+        assert_eq!(
+            err,
+            "parts:`snippet` is required to request `videos`, but gives `statistics, status`"
+        );
     }
 
     #[test]
     fn test_api_option_key_for_join() {
-        let keys = vec!["snippet".to_string(), "foo".to_string()];
+        let keys = vec![VideoPart::Snippet, VideoPart::ContentDetails];
         let api_option_key = ApiOptionsPart::new(keys).unwrap();
-        assert_eq!(api_option_key.join(","), "snippet,foo");
+        assert_eq!(api_option_key.join(","), "snippet,contentDetails");
+        assert_eq!(api_option_key.fields(), ["snippet", "contentDetails"]);
+    }
+
+    #[test]
+    fn test_api_option_key_exact() {
+        let api_option_key = ApiOptionsPart::exact(vec![
+            PlaylistItemPart::ContentDetails,
+            PlaylistItemPart::Status,
+            PlaylistItemPart::Status,
+        ]);
+        assert_eq!(api_option_key.join(","), "contentDetails,status");
+    }
+
+    #[test]
+    fn test_api_option_key_with_extra() {
+        let api_option_key = ApiOptionsPart::default()
+            .with_extra(vec![VideoPart::Statistics, VideoPart::Snippet]);
+        assert_eq!(api_option_key.join(","), "snippet,statistics");
+
+        let api_option_key = ApiOptionsPart::<PlaylistItemPart>::default()
+            .with_extra(vec![PlaylistItemPart::Status]);
+        assert_eq!(api_option_key.join(","), "snippet,status");
     }
 }
@@ -0,0 +1,57 @@
+use std::{fmt::Display, str::FromStr};
+
+/// YouTube Data Apiのエンドポイント
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// https://developers.google.com/youtube/v3/docs/videos/list
+    Videos,
+    /// https://developers.google.com/youtube/v3/docs/playlistItems/list
+    PlaylistItems,
+    /// https://developers.google.com/youtube/v3/docs/playlists/list
+    Playlists,
+    /// https://developers.google.com/youtube/v3/docs/channels/list
+    Channels,
+    /// https://developers.google.com/youtube/v3/docs/search/list
+    Search,
+}
+
+impl Endpoint {
+    /// `BASE_API_URL`に続くパス
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::Videos => "videos",
+            Self::PlaylistItems => "playlistItems",
+            Self::Playlists => "playlists",
+            Self::Channels => "channels",
+            Self::Search => "search",
+        }
+    }
+
+    const ALL: [Endpoint; 5] = [
+        Self::Videos,
+        Self::PlaylistItems,
+        Self::Playlists,
+        Self::Channels,
+        Self::Search,
+    ];
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|e| e.path() == s).ok_or_else(|| {
+            let paths: Vec<&str> = Self::ALL.iter().map(|e| e.path()).collect();
+            format!(
+                "invalid endpoint:`{}`, expected one of `{}`",
+                s,
+                paths.join(", ")
+            )
+        })
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path())
+    }
+}
@@ -1,8 +1,15 @@
+mod api_fields;
 mod api_key;
 mod api_options_part;
+mod endpoint;
 mod max_id_num;
+mod parts;
 pub mod quota;
 
+pub use api_fields::ApiFields;
 pub use api_key::ApiKey;
 pub use api_options_part::ApiOptionsPart;
+pub use endpoint::Endpoint;
 pub use max_id_num::MaxIdNum;
+pub use parts::{ChannelPart, Part, PlaylistItemPart, PlaylistPart, VideoPart};
+pub use quota::{Method, Operation, QuotaCosts};
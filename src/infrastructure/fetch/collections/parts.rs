@@ -0,0 +1,223 @@
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt::Display, str::FromStr};
+
+use super::Endpoint;
+
+/// エンドポイントごとに指定できる`part`
+///
+/// 各`part`はレスポンスの`items`に同名のフィールドを追加する
+///
+/// なお, `list`のquotaは指定した`part`によらず一定である
+pub trait Part: Copy + Eq + Display + FromStr<Err = String> + 'static {
+    /// この`part`を指定できるエンドポイント
+    const ENDPOINT: Endpoint;
+    /// 指定できる全ての`part`
+    const ALL: &'static [Self];
+    /// 結果の解析に必須の`part`
+    const REQUIRED: &'static [Self];
+
+    /// Apiでの名前
+    fn name(&self) -> &'static str;
+    /// この`part`によってレスポンスの`items`に追加されるフィールド
+    fn fields(&self) -> &'static [&'static str];
+}
+
+/// `Part`を実装する列挙型を定義する
+macro_rules! define_part {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $endpoint:expr, required: [$($required:ident),*],
+        { $($variant:ident => $api_name:literal [$($field:literal),*]),* $(,)? }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl Part for $name {
+            const ENDPOINT: Endpoint = $endpoint;
+            const ALL: &'static [Self] = &[$(Self::$variant),*];
+            const REQUIRED: &'static [Self] = &[$(Self::$required),*];
+
+            fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $api_name),*
+                }
+            }
+
+            fn fields(&self) -> &'static [&'static str] {
+                match self {
+                    $(Self::$variant => &[$($field),*]),*
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_part(s)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+define_part!(
+    /// https://developers.google.com/youtube/v3/docs/videos/list#part
+    VideoPart, Endpoint::Videos, required: [Snippet],
+    {
+        ContentDetails => "contentDetails" ["contentDetails"],
+        FileDetails => "fileDetails" ["fileDetails"],
+        Id => "id" ["id"],
+        LiveStreamingDetails => "liveStreamingDetails" ["liveStreamingDetails"],
+        Localizations => "localizations" ["localizations"],
+        Player => "player" ["player"],
+        ProcessingDetails => "processingDetails" ["processingDetails"],
+        RecordingDetails => "recordingDetails" ["recordingDetails"],
+        Snippet => "snippet" ["snippet"],
+        Statistics => "statistics" ["statistics"],
+        Status => "status" ["status"],
+        Suggestions => "suggestions" ["suggestions"],
+        TopicDetails => "topicDetails" ["topicDetails"],
+    }
+);
+
+define_part!(
+    /// https://developers.google.com/youtube/v3/docs/playlistItems/list#part
+    PlaylistItemPart, Endpoint::PlaylistItems, required: [Snippet],
+    {
+        ContentDetails => "contentDetails" ["contentDetails"],
+        Id => "id" ["id"],
+        Snippet => "snippet" ["snippet"],
+        Status => "status" ["status"],
+    }
+);
+
+define_part!(
+    /// https://developers.google.com/youtube/v3/docs/playlists/list#part
+    PlaylistPart, Endpoint::Playlists, required: [Snippet],
+    {
+        ContentDetails => "contentDetails" ["contentDetails"],
+        Id => "id" ["id"],
+        Localizations => "localizations" ["localizations"],
+        Player => "player" ["player"],
+        Snippet => "snippet" ["snippet"],
+        Status => "status" ["status"],
+    }
+);
+
+define_part!(
+    /// https://developers.google.com/youtube/v3/docs/channels/list#part
+    ChannelPart, Endpoint::Channels, required: [Snippet],
+    {
+        AuditDetails => "auditDetails" ["auditDetails"],
+        BrandingSettings => "brandingSettings" ["brandingSettings"],
+        ContentDetails => "contentDetails" ["contentDetails"],
+        ContentOwnerDetails => "contentOwnerDetails" ["contentOwnerDetails"],
+        Id => "id" ["id"],
+        Localizations => "localizations" ["localizations"],
+        Snippet => "snippet" ["snippet"],
+        Statistics => "statistics" ["statistics"],
+        Status => "status" ["status"],
+        TopicDetails => "topicDetails" ["topicDetails"],
+    }
+);
+
+/// 名前が一致しないときは近い名前を提案する
+fn parse_part<P: Part>(s: &str) -> Result<P, String> {
+    if let Some(part) = P::ALL.iter().find(|part| part.name() == s) {
+        return Ok(*part);
+    }
+    let names: Vec<&str> = P::ALL.iter().map(|part| part.name()).collect();
+    let suggestion = names
+        .iter()
+        .map(|name| (edit_distance(&s.to_lowercase(), &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| format!(", did you mean `{}`?", name))
+        .unwrap_or_default();
+    Err(format!(
+        "invalid part:`{}` for `{}`{} expected one of `{}`",
+        s,
+        P::ENDPOINT,
+        if suggestion.is_empty() {
+            ","
+        } else {
+            &suggestion
+        },
+        names.join(", ")
+    ))
+}
+
+/// レーベンシュタイン距離
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push((prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_from_str() {
+        for part in VideoPart::ALL {
+            assert_eq!(VideoPart::from_str(part.name()), Ok(*part));
+        }
+        assert_eq!(
+            PlaylistItemPart::from_str("contentDetails"),
+            Ok(PlaylistItemPart::ContentDetails)
+        );
+        assert_eq!(VideoPart::Statistics.fields(), &["statistics"]);
+
+        let err = VideoPart::from_str("statistcs").unwrap_err();
+        assert!(err.contains("did you mean `statistics`?"), "{}", err);
+        assert!(err.contains("`videos`"), "{}", err);
+
+        // 他のエンドポイントの`part`は指定できない
+        let err = PlaylistItemPart::from_str("statistics").unwrap_err();
+        assert!(!err.contains("did you mean"), "{}", err);
+        assert!(
+            err.contains("contentDetails, id, snippet, status"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_part_deserialize() {
+        let parts: Vec<ChannelPart> =
+            serde_json::from_str(r#"["snippet", "brandingSettings"]"#).unwrap();
+        assert_eq!(parts, [ChannelPart::Snippet, ChannelPart::BrandingSettings]);
+        assert!(serde_json::from_str::<PlaylistPart>(r#""snipet""#).is_err());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("statistcs", "statistics"), 1);
+        assert_eq!(edit_distance("", "id"), 2);
+        assert_eq!(edit_distance("snippet", "snippet"), 0);
+    }
+}
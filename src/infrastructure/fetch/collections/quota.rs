@@ -1,6 +1,169 @@
-// https://masaki-blog.net/youtube-data-api-quotas
-// 他の基準があればそこに従う
+// https://developers.google.com/youtube/v3/determine_quota_cost
+// Google Cloud consoleの値と一致させるため公式の値に従う
 
-#[allow(unused)]
-pub const SEARCH: usize = 100;
-pub const VIDEO_INFO: usize = 2;
+use serde::{de, Deserialize, Deserializer};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use super::Endpoint;
+
+/// Apiのメソッド
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    List,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Method {
+    const ALL: [Method; 4] = [Self::List, Self::Insert, Self::Update, Self::Delete];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Insert => "insert",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// quotaを消費する操作, `videos.list`のように表す
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Operation {
+    pub endpoint: Endpoint,
+    pub method: Method,
+}
+
+impl Operation {
+    pub fn new(endpoint: Endpoint, method: Method) -> Self {
+        Self { endpoint, method }
+    }
+
+    /// 設定で上書きされていないときの1回あたりのquota
+    pub fn default_cost(&self) -> usize {
+        match (self.endpoint, self.method) {
+            (Endpoint::Search, Method::List) => 100,
+            (Endpoint::Videos, Method::Insert) => 1600,
+            (_, Method::List) => 1,
+            (_, Method::Insert | Method::Update | Method::Delete) => 50,
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (endpoint, method) = s.split_once('.').ok_or_else(|| {
+            format!(
+                "invalid operation:`{}`, expected `<endpoint>.<method>` like `videos.list`",
+                s
+            )
+        })?;
+        let endpoint = Endpoint::from_str(endpoint)?;
+        let method =
+            Method::ALL.into_iter().find(|m| m.name() == method).ok_or_else(|| {
+                let names: Vec<&str> = Method::ALL.iter().map(|m| m.name()).collect();
+                format!(
+                    "invalid method:`{}`, expected one of `{}`",
+                    method,
+                    names.join(", ")
+                )
+            })?;
+        Ok(Self::new(endpoint, method))
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.endpoint, self.method.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Operation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// 操作ごとに消費するquotaの表
+///
+/// 上書きされていない操作は`Operation::default_cost`を使用する
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaCosts(HashMap<Operation, usize>);
+
+impl QuotaCosts {
+    /// `overrides`で指定した操作のquotaを上書きする, 同じ操作は後のものを優先する
+    pub fn with_overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = (Operation, usize)>,
+    ) -> Self {
+        self.0.extend(overrides);
+        self
+    }
+
+    pub fn cost(&self, endpoint: Endpoint, method: Method) -> usize {
+        let operation = Operation::new(endpoint, method);
+        self.0.get(&operation).copied().unwrap_or_else(|| operation.default_cost())
+    }
+
+    /// `videos.list=1`の形式をparseする
+    pub fn parse_override(s: &str) -> Result<(Operation, usize), String> {
+        let (operation, cost) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid quota cost:`{}`, expected `<endpoint>.<method>=<cost>`",
+                s
+            )
+        })?;
+        let cost = cost
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid quota cost:`{}`: {}", s, e))?;
+        Ok((operation.trim().parse()?, cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_costs_default() {
+        let costs = QuotaCosts::default();
+        assert_eq!(costs.cost(Endpoint::Videos, Method::List), 1);
+        assert_eq!(costs.cost(Endpoint::PlaylistItems, Method::List), 1);
+        assert_eq!(costs.cost(Endpoint::Search, Method::List), 100);
+        assert_eq!(costs.cost(Endpoint::Playlists, Method::Update), 50);
+    }
+
+    #[test]
+    fn test_quota_costs_with_overrides() {
+        let costs = QuotaCosts::default().with_overrides([
+            QuotaCosts::parse_override("videos.list=2").unwrap(),
+            QuotaCosts::parse_override("videos.list = 3").unwrap(),
+        ]);
+        assert_eq!(costs.cost(Endpoint::Videos, Method::List), 3);
+        assert_eq!(costs.cost(Endpoint::PlaylistItems, Method::List), 1);
+    }
+
+    #[test]
+    fn test_parse_override() {
+        assert_eq!(
+            QuotaCosts::parse_override("search.list=100"),
+            Ok((Operation::new(Endpoint::Search, Method::List), 100))
+        );
+        assert!(QuotaCosts::parse_override("videos.list").is_err());
+        assert!(QuotaCosts::parse_override("videos=1").is_err());
+        assert!(QuotaCosts::parse_override("video.list=1").is_err());
+        assert!(QuotaCosts::parse_override("videos.get=1").is_err());
+        assert!(QuotaCosts::parse_override("videos.list=-1").is_err());
+
+        let costs: HashMap<Operation, usize> =
+            toml::from_str(r#""playlistItems.list" = 2"#).unwrap();
+        assert_eq!(
+            costs[&Operation::new(Endpoint::PlaylistItems, Method::List)],
+            2
+        );
+    }
+}
@@ -12,10 +12,22 @@ pub enum FetchError {
     Deserialize(String),
     #[error("failed to parse:`{0}`")]
     Parse(String),
+    #[error("interrupted")]
+    Interrupted,
+}
+
+impl FetchError {
+    /// 対象(再生リストなど)が存在しないことを示すエラーかどうか
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::Client(status) if status.starts_with("404"))
+    }
 }
 
 impl From<FetchError> for crate::Error {
     fn from(value: FetchError) -> Self {
-        crate::Error::NetworkError(value.to_string())
+        match value {
+            FetchError::Interrupted => crate::Error::Interrupted,
+            _ => crate::Error::NetworkError(value.to_string()),
+        }
     }
 }
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, VecDeque};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    batch::BatchOperation, playlist::fetch_data::PlaylistApiClient,
+    response::IdOnlyItem, video::fetch_data::VideoApiClient, FetchError,
+};
+use crate::{
+    id::VideoId,
+    url::{Url, UrlPlaylist, UrlVideo},
+    Existence, FetchedItem, ItemExistence, MalformedEntry, UrlExistence,
+};
+
+/// 再生リスト内の動画, 非公開の動画は`videos`で確認できないので確認しない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ListedItem {
+    id: VideoId,
+    private: bool,
+}
+
+/// `snippet`を含めずに`part=id`で`url`の存在を確認する
+///
+/// 存在しない`url`も`Found`として返す
+pub(super) struct ExistenceOperation {
+    video_client: VideoApiClient,
+    playlist_client: PlaylistApiClient,
+}
+
+impl ExistenceOperation {
+    pub fn new(
+        video_client: VideoApiClient,
+        playlist_client: PlaylistApiClient,
+    ) -> Self {
+        Self {
+            video_client,
+            playlist_client,
+        }
+    }
+}
+
+impl BatchOperation for ExistenceOperation {
+    /// `videoId`を読み取れなかった`item`は`Err`
+    type Entry = Result<ListedItem, MalformedEntry>;
+    /// 存在したとき`true`
+    type Outcome = bool;
+    type Output = UrlExistence;
+
+    fn video_client(&self) -> &VideoApiClient {
+        &self.video_client
+    }
+
+    async fn list(
+        &self,
+        url_pl: UrlPlaylist,
+        cancel: &CancellationToken,
+    ) -> Result<Result<Vec<Self::Entry>, UrlPlaylist>, FetchError> {
+        let res = self.playlist_client.fetch_item_statuses(url_pl, cancel).await?;
+        Ok(res.map(listed_items))
+    }
+
+    async fn process(
+        &self,
+        ids: &VecDeque<VideoId>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<(VideoId, bool)>, FetchError> {
+        let existing = self.video_client.check_chunk(ids, cancel).await?;
+        Ok(ids.iter().map(|id| (id.clone(), existing.contains(id))).collect())
+    }
+
+    fn entry_id(item: &Self::Entry) -> Option<&VideoId> {
+        item.as_ref().ok().filter(|item| !item.private).map(|item| &item.id)
+    }
+
+    fn video_item(url: UrlVideo, exists: &bool) -> FetchedItem<UrlExistence, Url> {
+        let existence = if *exists {
+            Existence::Existing
+        } else {
+            Existence::Missing
+        };
+        FetchedItem::Found(UrlExistence::new(url.into(), existence))
+    }
+
+    /// 再生リスト内の動画は, 非公開のものを`Private`, 存在しなかったものを`Deleted`とする
+    ///
+    /// `videoId`を読み取れなかった`item`は`malformed`に含める
+    fn playlist_item(
+        url: UrlPlaylist,
+        items: Vec<Self::Entry>,
+        outcomes: &HashMap<VideoId, bool>,
+    ) -> FetchedItem<UrlExistence, Url> {
+        let (items, malformed): (Vec<_>, Vec<_>) =
+            items.into_iter().partition(Result::is_ok);
+        let items = items
+            .into_iter()
+            .flatten()
+            .map(|item| {
+                let existence = if item.private {
+                    Existence::Private
+                } else if outcomes[&item.id] {
+                    Existence::Existing
+                } else {
+                    Existence::Deleted
+                };
+                ItemExistence {
+                    video_id: item.id,
+                    existence,
+                }
+            })
+            .collect();
+        let malformed = malformed.into_iter().filter_map(Result::err).collect();
+        FetchedItem::Found(
+            UrlExistence::new(url.into(), Existence::Existing)
+                .with_items(items)
+                .with_malformed(malformed),
+        )
+    }
+
+    fn missing_playlist(url: UrlPlaylist) -> FetchedItem<UrlExistence, Url> {
+        FetchedItem::Found(UrlExistence::new(url.into(), Existence::Missing))
+    }
+}
+
+/// `videoId`を読み取れない`item`は`Err`とする
+fn listed_items(items: Vec<IdOnlyItem>) -> Vec<Result<ListedItem, MalformedEntry>> {
+    items
+        .into_iter()
+        .map(|item| {
+            let private = item.is_private();
+            match item.contentDetails.and_then(|details| details.videoId) {
+                Some(id) => match VideoId::new(id.clone()) {
+                    Ok(id) => Ok(ListedItem { id, private }),
+                    Err(e) => Err(MalformedEntry {
+                        video_id: None,
+                        reason: format!("invalid video id `{}`: {}", id, e),
+                    }),
+                },
+                None => Err(MalformedEntry {
+                    video_id: None,
+                    reason: "missing field `contentDetails.videoId`".into(),
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::batch::VideoBatch;
+    use super::*;
+    use crate::id::PlaylistId;
+
+    fn playlist(id: PlaylistId) -> UrlPlaylist {
+        (None, id).into()
+    }
+
+    fn item(id: VideoId, private: bool) -> Result<ListedItem, MalformedEntry> {
+        Ok(ListedItem { id, private })
+    }
+
+    #[test]
+    fn test_existence_batch() {
+        let mut batch = VideoBatch::<ExistenceOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        batch.add_video(VideoId::all_2().into());
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()),
+            vec![
+                item(VideoId::all_1(), false),
+                item(VideoId::all_2(), false),
+                item(VideoId::all_0(), true),
+            ],
+        );
+
+        // 非公開の動画は確認せず, 重複も除く
+        let ids = batch.next_chunk(50);
+        assert_eq!(
+            ids,
+            VecDeque::from(vec![VideoId::all_1(), VideoId::all_2()])
+        );
+
+        batch.record(vec![(VideoId::all_1(), true), (VideoId::all_2(), false)]);
+        let resolved = batch.take_resolved();
+        let expected: Vec<_> = vec![
+            UrlExistence::new(
+                UrlVideo::from(VideoId::all_1()).into(),
+                Existence::Existing,
+            ),
+            UrlExistence::new(
+                UrlVideo::from(VideoId::all_2()).into(),
+                Existence::Missing,
+            ),
+            UrlExistence::new(
+                playlist(PlaylistId::all_1()).into(),
+                Existence::Existing,
+            )
+            .with_items(vec![
+                ItemExistence {
+                    video_id: VideoId::all_1(),
+                    existence: Existence::Existing,
+                },
+                ItemExistence {
+                    video_id: VideoId::all_2(),
+                    existence: Existence::Deleted,
+                },
+                ItemExistence {
+                    video_id: VideoId::all_0(),
+                    existence: Existence::Private,
+                },
+            ]),
+        ]
+        .into_iter()
+        .map(FetchedItem::Found)
+        .collect();
+        assert_eq!(resolved, expected);
+        assert!(batch.take_unresolved().is_empty());
+    }
+
+    #[test]
+    fn test_existence_batch_take_unresolved() {
+        let mut batch = VideoBatch::<ExistenceOperation>::new();
+        batch.add_video(VideoId::all_1().into());
+        // 非公開の動画のみの再生リストはすぐに結果を返せる
+        batch.add_playlist(
+            playlist(PlaylistId::all_1()),
+            vec![item(VideoId::all_2(), true)],
+        );
+        assert_eq!(batch.take_resolved().len(), 1);
+        assert_eq!(
+            batch.take_unresolved(),
+            vec![Url::from(UrlVideo::from(VideoId::all_1()))]
+        );
+        assert!(!batch.has_pending());
+    }
+
+    #[test]
+    fn test_listed_items_malformed() {
+        let raw = serde_json::json!([
+            {"contentDetails": {"videoId": "11111111111"}, "status": {"privacyStatus": "private"}},
+            {"status": {"privacyStatus": "public"}},
+        ]);
+        let items: Vec<IdOnlyItem> = serde_json::from_value(raw).unwrap();
+        let listed = listed_items(items);
+        assert_eq!(listed[0], item(VideoId::all_1(), true));
+        assert!(matches!(
+            &listed[1],
+            Err(MalformedEntry { video_id: None, .. })
+        ));
+
+        // `videoId`を読み取れなかった`item`は結果の`malformed`に含める
+        let mut batch = VideoBatch::<ExistenceOperation>::new();
+        batch.add_playlist(playlist(PlaylistId::all_1()), listed);
+        let resolved = batch.take_resolved();
+        let [FetchedItem::Found(existence)] = &resolved[..] else {
+            panic!("unexpected result: {:?}", resolved);
+        };
+        assert_eq!(existence.items.len(), 1);
+        assert_eq!(existence.malformed.len(), 1);
+    }
+}
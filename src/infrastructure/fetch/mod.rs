@@ -1,6 +1,10 @@
 mod api_client;
+mod batch;
 mod collections;
 mod error;
+mod existence;
+mod observer;
+mod plan;
 mod response;
 
 pub mod playlist;
@@ -9,5 +13,10 @@ pub(super) mod api_request;
 pub(super) mod video;
 
 pub use api_client::ApiClient;
-pub use collections::{ApiKey, ApiOptionsPart, MaxIdNum};
+pub use collections::{
+    ApiFields, ApiKey, ApiOptionsPart, ChannelPart, Endpoint, MaxIdNum, Method,
+    Operation, Part, PlaylistItemPart, PlaylistPart, QuotaCosts, VideoPart,
+};
 pub use error::FetchError;
+pub use observer::{FetchEvent, FetchObserver};
+pub use plan::RequestPlan;
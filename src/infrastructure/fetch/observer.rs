@@ -0,0 +1,110 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use super::Endpoint;
+use crate::id::PlaylistId;
+
+/// 取得処理の進捗を受け取る
+///
+/// `ApiClient::with_observer`で登録すると, 取得中に`FetchEvent`が通知される
+///
+/// 複数のリクエストを並行して送信するので, 別スレッドから呼ばれることがある
+pub trait FetchObserver: Send + Sync {
+    fn on_event(&self, event: &FetchEvent);
+}
+
+/// 取得処理の進捗
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchEvent {
+    /// 取得を開始した, 取得する動画と再生リストの`url`の数
+    Started { videos: usize, playlists: usize },
+    /// リクエストを送信した
+    RequestStarted { endpoint: Endpoint },
+    /// レスポンスを受け取った, もしくは送信に失敗した
+    RequestFinished { endpoint: Endpoint, success: bool },
+    /// 再生リストの`page`ページ目(1始まり)を取得した
+    ///
+    /// `total_pages`は最初のページを取得するまで分からないので`None`のときもある
+    PlaylistPage {
+        id: PlaylistId,
+        page: usize,
+        total_pages: Option<usize>,
+    },
+    /// まとめてリクエストした`urls`個の動画の取得が終わった
+    VideoChunkDone { urls: usize },
+    /// 再生リストの`url`の全ページの取得が終わった, あるいは再生リストが存在しなかった
+    PlaylistDone { id: PlaylistId },
+    /// 入力された`url`のうち`urls`個の結果が確定した
+    UrlsResolved { urls: usize },
+    /// quotaを消費した, `total`はこれまでに消費した合計
+    QuotaSpent { cost: usize, total: usize },
+    /// レスポンスに想定していない値が含まれていた, APIの仕様変更の可能性がある
+    SchemaDrift { detail: String },
+    /// 失敗したリクエストを`delay`後に再送する, `attempt`は1始まり
+    RetryScheduled {
+        endpoint: Endpoint,
+        attempt: usize,
+        delay: Duration,
+        reason: String,
+    },
+}
+
+/// 何もしない`FetchObserver`
+struct NoopObserver;
+
+impl FetchObserver for NoopObserver {
+    fn on_event(&self, _event: &FetchEvent) {}
+}
+
+/// 各クライアント間で共有する`FetchObserver`
+#[derive(Clone)]
+pub(super) struct SharedObserver(Arc<dyn FetchObserver>);
+
+impl SharedObserver {
+    pub(super) fn new(observer: Arc<dyn FetchObserver>) -> Self {
+        Self(observer)
+    }
+
+    pub(super) fn notify(&self, event: FetchEvent) {
+        self.0.on_event(&event);
+    }
+}
+
+impl Default for SharedObserver {
+    fn default() -> Self {
+        Self(Arc::new(NoopObserver))
+    }
+}
+
+impl Debug for SharedObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedObserver")
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// 受け取った`FetchEvent`を記録する
+    #[derive(Default)]
+    pub(in super::super) struct RecordObserver(pub Mutex<Vec<FetchEvent>>);
+
+    impl FetchObserver for RecordObserver {
+        fn on_event(&self, event: &FetchEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_shared_observer_notify() {
+        let record = Arc::new(RecordObserver::default());
+        let observer = SharedObserver::new(record.clone());
+        observer.notify(FetchEvent::VideoChunkDone { urls: 3 });
+        assert_eq!(
+            *record.0.lock().unwrap(),
+            vec![FetchEvent::VideoChunkDone { urls: 3 }]
+        );
+    }
+}
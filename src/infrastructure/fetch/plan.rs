@@ -0,0 +1,201 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+};
+
+use super::{Endpoint, MaxIdNum, Method, QuotaCosts};
+use crate::{
+    id::{PlaylistId, VideoId},
+    url::Url,
+};
+
+/// 作成した再生リストに含められる動画の最大数
+///
+/// アップロード動画の再生リストなど自動で作られるものはこれを超えることがある
+///
+/// https://support.google.com/youtube/answer/57792
+const MAX_PLAYLIST_ITEMS: usize = 5000;
+
+/// APIを呼び出さずに見積もった, 取得時に送信するリクエストと消費するquota
+///
+/// 再生リストのページ数は取得するまで分からないので, quotaは目安の範囲で表す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestPlan {
+    /// 動画と再生リストのidで重複を除いた`url`, 入力された順
+    pub urls: VecDeque<Url>,
+    /// 直接指定された動画の取得に必要な`videos.list`の回数
+    pub video_chunks: usize,
+    /// 各再生リストの最初のページの`playlistItems.list`の回数
+    pub playlist_first_pages: usize,
+    /// ミックスリストなどAPIで取得できないため, リクエストしない再生リストの数
+    pub unsupported_playlists: usize,
+    /// 消費するquotaの最小値, 再生リストが全て1ページで新たな動画を含まないとき
+    pub min_quota: usize,
+    /// 消費するquotaの目安, 再生リストが全て`MAX_PLAYLIST_ITEMS`個の動画を含むとき
+    ///
+    /// それより多くの動画を含む再生リストがあると超えるので上限ではない
+    pub high_quota: usize,
+}
+
+impl RequestPlan {
+    pub fn new(urls: VecDeque<Url>, costs: &QuotaCosts) -> Self {
+        // 再生を始める位置などが異なっても同じ動画, 再生リストとみなす
+        let mut seen: HashSet<Result<VideoId, PlaylistId>> = HashSet::new();
+        let urls: VecDeque<Url> = urls
+            .into_iter()
+            .filter(|url| {
+                let key = match url {
+                    Url::Video(v) => Ok(<&VideoId>::from(v).clone()),
+                    Url::Playlist(pl) => Err(pl.get_playlist_id().clone()),
+                };
+                seen.insert(key)
+            })
+            .collect();
+
+        let per_request: usize = MaxIdNum::default().into();
+        let videos = urls.iter().filter(|url| matches!(url, Url::Video(_))).count();
+        let unsupported_playlists = urls
+            .iter()
+            .filter(|url| {
+                matches!(url, Url::Playlist(pl) if pl.unsupported_reason().is_some())
+            })
+            .count();
+        let playlists = urls.len() - videos - unsupported_playlists;
+        let video_chunks = videos.div_ceil(per_request);
+
+        let video_cost = costs.cost(Endpoint::Videos, Method::List);
+        let playlist_cost = costs.cost(Endpoint::PlaylistItems, Method::List);
+        let min_quota = video_chunks * video_cost + playlists * playlist_cost;
+        let max_pages = MAX_PLAYLIST_ITEMS.div_ceil(per_request);
+        let max_video_chunks =
+            (videos + playlists * MAX_PLAYLIST_ITEMS).div_ceil(per_request);
+        let high_quota =
+            max_video_chunks * video_cost + playlists * max_pages * playlist_cost;
+
+        Self {
+            urls,
+            video_chunks,
+            playlist_first_pages: playlists,
+            unsupported_playlists,
+            min_quota,
+            high_quota,
+        }
+    }
+}
+
+impl Display for RequestPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "urls after removing duplicates: {}", self.urls.len())?;
+        writeln!(
+            f,
+            "`videos.list` requests for videos: {}",
+            self.video_chunks
+        )?;
+        writeln!(
+            f,
+            "`playlistItems.list` requests for the first page of playlists: {}",
+            self.playlist_first_pages
+        )?;
+        if self.unsupported_playlists != 0 {
+            writeln!(
+                f,
+                "playlists skipped because the api cannot fetch them (mixes, etc.): {}",
+                self.unsupported_playlists
+            )?;
+        }
+        write!(f, "estimated quota: {}", self.min_quota)?;
+        if self.high_quota != self.min_quota {
+            write!(
+                f,
+                " - {} (if each playlist has up to {} videos, larger ones such as uploads use more)",
+                self.high_quota, MAX_PLAYLIST_ITEMS
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Operation;
+    use super::*;
+    use crate::{
+        id::{PlaylistId, VideoId},
+        url::{UrlPlaylist, UrlVideo},
+    };
+
+    #[test]
+    fn test_request_plan_videos_only() {
+        let mut urls: VecDeque<Url> = (0..120)
+            .map(|i| {
+                let id = VideoId::new(format!("{:0>11}", i)).unwrap();
+                UrlVideo::from(id).into()
+            })
+            .collect();
+        urls.push_back(urls[0].clone());
+
+        let plan = RequestPlan::new(urls, &QuotaCosts::default());
+        assert_eq!(plan.urls.len(), 120);
+        assert_eq!(plan.video_chunks, 3);
+        assert_eq!(plan.playlist_first_pages, 0);
+        assert_eq!((plan.min_quota, plan.high_quota), (3, 3));
+        assert_eq!(plan.to_string().lines().last(), Some("estimated quota: 3"));
+    }
+
+    #[test]
+    fn test_request_plan_with_playlist() {
+        let urls: VecDeque<Url> = vec![
+            Url::v_inc_from_1(),
+            Url::pl_inc_from_1(),
+            UrlPlaylist::new(Some(VideoId::inc_from_1()), PlaylistId::all_1()).into(),
+            Url::v_inc_from_1(),
+        ]
+        .into();
+        let costs = QuotaCosts::default().with_overrides([(
+            Operation::new(Endpoint::PlaylistItems, Method::List),
+            2,
+        )]);
+
+        let plan = RequestPlan::new(urls, &costs);
+        assert_eq!(plan.urls.len(), 3);
+        assert_eq!((plan.video_chunks, plan.playlist_first_pages), (1, 2));
+        assert_eq!(plan.min_quota, 1 + 2 * 2);
+        // 動画: (1 + 5000 * 2) / 50 回, 再生リスト: 100ページ * 2
+        assert_eq!(plan.high_quota, 201 + 2 * 100 * 2);
+    }
+
+    #[test]
+    fn test_request_plan_skips_mixes() {
+        let mix = PlaylistId::new("RD12345678901".into()).unwrap();
+        let urls: VecDeque<Url> = vec![
+            Url::v_inc_from_1(),
+            UrlPlaylist::new(Some(VideoId::inc_from_1()), mix).into(),
+        ]
+        .into();
+        let plan = RequestPlan::new(urls, &QuotaCosts::default());
+        assert_eq!(plan.playlist_first_pages, 0);
+        assert_eq!(plan.unsupported_playlists, 1);
+        assert_eq!((plan.min_quota, plan.high_quota), (1, 1));
+    }
+
+    #[test]
+    fn test_request_plan_dedup_by_id() {
+        use crate::url::Position;
+
+        let position = Position::new(Some(30), None);
+        let urls: VecDeque<Url> = vec![
+            Url::v_pl_inc_from_1(),
+            Url::pl_inc_from_1(),
+            Url::pl_inc_from_1().with_position(position),
+            Url::v_inc_from_1(),
+            Url::v_inc_from_1().with_position(position),
+        ]
+        .into();
+        let plan = RequestPlan::new(urls, &QuotaCosts::default());
+        assert_eq!(
+            plan.urls,
+            VecDeque::from(vec![Url::v_pl_inc_from_1(), Url::v_inc_from_1()])
+        );
+        assert_eq!((plan.video_chunks, plan.playlist_first_pages), (1, 1));
+    }
+}
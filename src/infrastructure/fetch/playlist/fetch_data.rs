@@ -1,92 +1,206 @@
-use futures::{stream::FuturesOrdered, StreamExt};
-use serde::Deserialize;
-use std::{
-    collections::VecDeque,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
-    },
-};
+use std::sync::{atomic::AtomicUsize, Arc};
+use tokio_util::sync::CancellationToken;
 use tracing::Level;
 
 use super::super::{
-    api_request::ApiRequest, collections::quota, response::ApiResponse,
-    response::PageToken, ApiKey, ApiOptionsPart, FetchError, MaxIdNum,
+    api_request::ApiRequest,
+    observer::{FetchEvent, SharedObserver},
+    response::{ApiResponse, IdOnlyItem, IdOnlyResponse, PageToken},
+    ApiFields, ApiKey, ApiOptionsPart, Endpoint, FetchError, MaxIdNum,
+    PlaylistItemPart, QuotaCosts,
 };
-use super::response::PlaylistApiResponse;
-use crate::{metadata::BasicPlaylistData, url::UrlPlaylist};
+use super::response::{PlaylistApiResponse, PlaylistEntry};
+use crate::{url::UrlPlaylist, MalformedEntry};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PlaylistApiClient {
     max_id: MaxIdNum,
-    api_options: ApiOptionsPart,
+    api_options: ApiOptionsPart<PlaylistItemPart>,
+    fields: ApiFields,
+    /// レスポンスのそのままの`item`を結果に含めるかどうか
+    keep_raw: bool,
     api_key: ApiKey,
     used_quota: Arc<AtomicUsize>,
+    /// レスポンスに含まれていた想定していない値の数
+    schema_drift: Arc<AtomicUsize>,
+    quota_costs: QuotaCosts,
+    observer: SharedObserver,
 }
 
-impl ApiRequest for PlaylistApiClient {}
+impl ApiRequest for PlaylistApiClient {
+    fn observer(&self) -> &SharedObserver {
+        &self.observer
+    }
+
+    fn used_quota(&self) -> &AtomicUsize {
+        &self.used_quota
+    }
+
+    fn schema_drift(&self) -> &AtomicUsize {
+        &self.schema_drift
+    }
+
+    fn quota_costs(&self) -> &QuotaCosts {
+        &self.quota_costs
+    }
+}
 
 impl PlaylistApiClient {
-    pub fn new_default(api_key: ApiKey, used_quota: Arc<AtomicUsize>) -> Self {
+    pub(in super::super) fn new_default(
+        api_key: ApiKey,
+        used_quota: Arc<AtomicUsize>,
+        observer: SharedObserver,
+    ) -> Self {
         Self {
             max_id: MaxIdNum::default(),
             api_options: ApiOptionsPart::default(),
+            fields: ApiFields::default(),
+            keep_raw: false,
             api_key,
             used_quota,
+            schema_drift: Arc::default(),
+            quota_costs: QuotaCosts::default(),
+            observer,
         }
     }
 
-    // pub async fn fetch_video_ids(
-    //     &self,
-    //     urls_pl: VecDeque<UrlPlaylist>,
-    // ) -> Result<VecDeque<Result<VideosInPlaylist, UrlPlaylist>>, FetchError> {
-    //     let mut tasks = FuturesOrdered::new();
-    //     for url_pl in urls_pl {
-    //         tasks.push_back(self.process_single_pl(url_pl));
-    //     }
-    //     let mut fetched_data: VecDeque<Result<VideosInPlaylist, UrlPlaylist>> =
-    //         VecDeque::new();
-    //     while let Some(res) = tasks.next().await {
-    //         fetched_data.push_back(res?.map(Into::into));
-    //     }
-    //     Ok(fetched_data)
-    // }
-
-    pub async fn fetch_all_playlist_data(
+    /// 消費するquotaの計算に使う表を指定する
+    pub(in super::super) fn with_quota_costs(
+        mut self,
+        quota_costs: QuotaCosts,
+    ) -> Self {
+        self.quota_costs = quota_costs;
+        self
+    }
+
+    /// 想定していない値の数を数える先を指定する
+    pub(in super::super) fn with_schema_drift(
+        mut self,
+        schema_drift: Arc<AtomicUsize>,
+    ) -> Self {
+        self.schema_drift = schema_drift;
+        self
+    }
+
+    /// レスポンスに含めるフィールドを指定する
+    pub(in super::super) fn with_fields(mut self, fields: ApiFields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// リクエストする`part`を指定する
+    pub(in super::super) fn with_parts(
+        mut self,
+        api_options: ApiOptionsPart<PlaylistItemPart>,
+    ) -> Self {
+        self.api_options = api_options;
+        self
+    }
+
+    /// レスポンスのそのままの`item`を結果に含める
+    pub(in super::super) fn with_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+
+    /// 再生リスト内の動画の`VideoId`のみを全てのページから取得する
+    ///
+    /// 非公開や削除済みの動画は含まない, デシリアライズできなかった`item`は`Err`として含める
+    pub(in super::super) async fn fetch_video_ids(
         &self,
-        urls_pl: VecDeque<UrlPlaylist>,
-    ) -> Result<VecDeque<Result<BasicPlaylistData, UrlPlaylist>>, crate::Error> {
-        let mut tasks = FuturesOrdered::new();
-        for url_pl in urls_pl {
-            tasks.push_back(self.process_playlist(url_pl));
-        }
-        let mut fetched_data: VecDeque<Result<BasicPlaylistData, UrlPlaylist>> =
-            VecDeque::new();
-        while let Some(res) = tasks.next().await {
-            fetched_data.push_back(res?.map(Into::into));
-        }
-        Ok(fetched_data)
+        url_pl: UrlPlaylist,
+        cancel: &CancellationToken,
+    ) -> Result<
+        Result<Vec<Result<PlaylistEntry, MalformedEntry>>, UrlPlaylist>,
+        FetchError,
+    > {
+        Ok(self.process_playlist(url_pl, cancel).await?.map(Into::into))
     }
 
-    pub async fn fetch_playlist_data(
+    /// 再生リスト内の動画を`snippet`を含めずに全てのページから取得する
+    ///
+    /// 非公開や削除済みの動画も含む, 再生リストが存在しないときは`Err(UrlPlaylist)`
+    #[tracing::instrument(level = Level::DEBUG, skip(cancel))]
+    pub(in super::super) async fn fetch_item_statuses(
         &self,
         url_pl: UrlPlaylist,
-    ) -> Result<Result<BasicPlaylistData, UrlPlaylist>, crate::Error> {
-        Ok(self.process_playlist(url_pl).await?.map(Into::into))
+        cancel: &CancellationToken,
+    ) -> Result<Result<Vec<IdOnlyItem>, UrlPlaylist>, FetchError> {
+        let parts = ApiOptionsPart::exact(vec![
+            PlaylistItemPart::ContentDetails,
+            PlaylistItemPart::Status,
+        ]);
+        let base_url = format!(
+            "{}{}?key={}&part={}&playlistId={}&maxResults={}{}",
+            Self::BASE_API_URL,
+            Endpoint::PlaylistItems.path(),
+            self.api_key.as_string(),
+            parts.join(","),
+            url_pl.get_playlist_id(),
+            self.max_id,
+            ApiFields::existence().to_query(Endpoint::PlaylistItems, &parts.fields()),
+        );
+        let mut items: Vec<IdOnlyItem> = Vec::new();
+        let mut url_string = base_url.clone();
+        let mut page: usize = 0;
+        loop {
+            let response =
+                match self.api_call(Endpoint::PlaylistItems, &url_string, cancel).await
+                {
+                    Ok(response) => response,
+                    Err(e) if e.is_not_found() => {
+                        self.notify_done(&url_pl);
+                        return Ok(Err(url_pl));
+                    }
+                    Err(e) => return Err(e),
+                };
+            let resp: IdOnlyResponse = serde_json::from_value(response)
+                .map_err(|e| FetchError::Deserialize(e.to_string()))?;
+            page += 1;
+            self.observer.notify(FetchEvent::PlaylistPage {
+                id: url_pl.get_playlist_id().clone(),
+                page,
+                total_pages: resp.pageInfo.map(|info| {
+                    let per_page: usize = self.max_id.into();
+                    info.totalResults.div_ceil(per_page.max(1)).max(1)
+                }),
+            });
+            items.extend(resp.items);
+            match resp.nextPageToken {
+                Some(token) => {
+                    url_string = format!("{}&pageToken={}", base_url, *token)
+                }
+                None => break,
+            }
+        }
+        self.notify_done(&url_pl);
+        Ok(Ok(items))
     }
 
-    #[tracing::instrument(level = Level::DEBUG, ret)]
+    #[tracing::instrument(level = Level::DEBUG, ret, skip(cancel))]
     async fn process_playlist(
         &self,
         url_pl: UrlPlaylist,
+        cancel: &CancellationToken,
     ) -> Result<Result<PlaylistApiResponse, UrlPlaylist>, FetchError> {
         let mut fetched_pl_data: Option<PlaylistApiResponse> = None;
         let mut url_string = self.build_playlist_api_url(&url_pl);
+        let mut page: usize = 0;
         loop {
-            let pl_resp = match self.call_playlist_api(&url_pl, &url_string).await? {
-                Some(pl_resp) => pl_resp,
-                None => return Ok(Err(url_pl)),
-            };
+            let pl_resp =
+                match self.call_playlist_api(&url_pl, &url_string, cancel).await? {
+                    Some(pl_resp) => pl_resp,
+                    None => {
+                        self.notify_done(&url_pl);
+                        return Ok(Err(url_pl));
+                    }
+                };
+            page += 1;
+            self.observer.notify(FetchEvent::PlaylistPage {
+                id: url_pl.get_playlist_id().clone(),
+                page,
+                total_pages: pl_resp.get_total_pages(self.max_id.into()),
+            });
             let next_page_token = pl_resp.get_next_page_token();
             Self::merge_playlist_data(&mut fetched_pl_data, pl_resp)?;
             url_string = match self.build_next_page_url(&url_pl, next_page_token) {
@@ -94,51 +208,68 @@ impl PlaylistApiClient {
                 None => break,
             }
         }
+        self.notify_done(&url_pl);
         Ok(Ok(fetched_pl_data.unwrap()))
     }
 
+    fn notify_done(&self, url_pl: &UrlPlaylist) {
+        self.observer.notify(FetchEvent::PlaylistDone {
+            id: url_pl.get_playlist_id().clone(),
+        });
+    }
+
     fn build_playlist_api_url(&self, pl_url: &UrlPlaylist) -> String {
         format!(
-            "{}playlistItems?key={}&part={}&playlistId={}&maxResults={}",
+            "{}{}?key={}&part={}&playlistId={}&maxResults={}{}",
             Self::BASE_API_URL,
+            Endpoint::PlaylistItems.path(),
             self.api_key.as_string(),
             self.api_options.join(","),
             pl_url.get_playlist_id(),
-            self.max_id
+            self.max_id,
+            self.fields.to_query(Endpoint::PlaylistItems, &self.api_options.fields())
         )
     }
 
-    #[tracing::instrument(level = Level::TRACE, ret, skip(url_string))]
+    /// `item`ごとにデシリアライズし, できなかったものはログに残し結果に含める
+    ///
+    /// 再生リストが存在しないときは`None`
+    #[tracing::instrument(level = Level::TRACE, ret, skip(url_string, cancel))]
     async fn call_playlist_api(
         &self,
         url_pl: &UrlPlaylist,
         url_string: &str,
+        cancel: &CancellationToken,
     ) -> Result<Option<PlaylistApiResponse>, FetchError> {
-        self.used_quota.fetch_add(quota::VIDEO_INFO, Ordering::Relaxed);
-
-        // for debug
-        let response = self.api_call(url_string).await?;
-        let deserialized = ApiResponse::deserialize(response);
-        // for debug end
-
-        match deserialized {
-            Ok(resp) => Ok(Some(
-                // data_value.remove_unavailable_videos();
-                // `DataValue`=>`PlaylistDataValue`にparseできないのは異常なため
-                // FetchErrorを返す
-                PlaylistApiResponse::new(resp, url_pl).map_err(FetchError::Parse)?,
-            )),
-            // `Value`=>`DataValue`にパースできない
-            // eq `playlist_id`が無効なとき
-            // TODO 上本当か確かめる
-            Err(e) => {
-                tracing::trace!(
-                    "response(Value) cannot parse to `DataValue`, reason: {:?}",
-                    e
-                );
-                Ok(None)
-            }
+        let response =
+            match self.api_call(Endpoint::PlaylistItems, url_string, cancel).await {
+                Ok(response) => response,
+                Err(e) if e.is_not_found() => return Ok(None),
+                Err(e) => return Err(e),
+            };
+        // `items`以外の構造が異なるのは異常なため FetchErrorを返す
+        let (mut resp, malformed) = ApiResponse::deserialize_lenient(response)
+            .map_err(FetchError::Deserialize)?;
+        if !self.keep_raw {
+            resp.raw_items.clear();
+        }
+        for detail in resp.unknown_values() {
+            self.report_schema_drift(detail);
+        }
+        for item in &malformed {
+            tracing::warn!(
+                "skipped malformed item (video id: {}) in playlist `{}`: {}",
+                item.id.as_deref().unwrap_or("unknown"),
+                url_pl.get_playlist_id(),
+                item.reason
+            );
         }
+        // `DataValue`=>`PlaylistDataValue`にparseできないのは異常なため
+        // FetchErrorを返す
+        Ok(Some(
+            PlaylistApiResponse::new(resp, url_pl, malformed)
+                .map_err(FetchError::Parse)?,
+        ))
     }
 
     fn merge_playlist_data(
@@ -1,2 +1,4 @@
 pub(super) mod fetch_data;
 mod response;
+
+pub(super) use response::PlaylistEntry;
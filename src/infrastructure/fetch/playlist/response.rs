@@ -1,23 +1,39 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::VecDeque;
 
-use super::super::response::{ApiResponse, PageToken, SnippetPlaylist};
+use super::super::{
+    response::{ApiResponse, MalformedItem, PageToken, SnippetPlaylist},
+    Endpoint,
+};
 use crate::metadata::{BasicPlaylistData, BasicVideoData, PlaylistDataItself};
 use crate::{
     id::{PlaylistId, VideoId},
     url::UrlPlaylist,
+    MalformedEntry,
 };
 
+/// 取得した動画, 再生リスト自体の情報, デシリアライズできなかった`item`
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
-pub(super) struct PlaylistApiResponse(VecDeque<Item>, PlaylistData);
+pub(super) struct PlaylistApiResponse(
+    VecDeque<Item>,
+    PlaylistData,
+    #[serde(skip)] Vec<MalformedEntry>,
+);
 
 impl PlaylistApiResponse {
+    /// `data_value.raw_items`に格納されている`item`は各動画に引き継ぐ
+    ///
+    /// `malformed`は`data_value`からデシリアライズできなかった`item`
     pub fn new(
-        data_value: ApiResponse,
+        mut data_value: ApiResponse,
         url_playlist: &UrlPlaylist,
+        malformed: Vec<MalformedItem>,
     ) -> Result<Self, String> {
         let next_page_token = data_value.nextPageToken.clone();
+        let total_results = data_value.pageInfo.map(|info| info.totalResults);
+        let mut raw_items = std::mem::take(&mut data_value.raw_items);
 
         // generate self(0)
         let items: VecDeque<Item> = match data_value.get_as_playlist() {
@@ -25,7 +41,10 @@ impl PlaylistApiResponse {
                 .into_iter()
                 // 非公開動画を消す
                 .filter_map(|item| item.available())
-                .map(Into::into)
+                .map(|snippet| {
+                    let raw = raw_items.remove(&snippet.resourceId.videoId.to_string());
+                    Item::new(snippet, raw)
+                })
                 .collect(),
             None => return Err("".into()),
         };
@@ -37,14 +56,29 @@ impl PlaylistApiResponse {
             total,
             url_playlist.get_video_id().clone(),
             next_page_token,
+            total_results,
         );
-        Ok(Self(items, pl_data))
+        let malformed = malformed
+            .into_iter()
+            .map(|item| MalformedEntry {
+                video_id: item.id.and_then(|id| VideoId::new(id).ok()),
+                reason: item.reason,
+            })
+            .collect();
+        Ok(Self(items, pl_data, malformed))
     }
 
     pub fn get_next_page_token(&self) -> Option<PageToken> {
         self.1.next_page_token.clone()
     }
 
+    /// 1ページに`per_page`件ずつ取得するときの総ページ数
+    ///
+    /// レスポンスに総数が含まれていないときは`None`
+    pub fn get_total_pages(&self, per_page: usize) -> Option<usize> {
+        self.1.total_results.map(|total| total.div_ceil(per_page.max(1)).max(1))
+    }
+
     /// 別(next_page_tokenを使用して)でfetchしたものと結合
     ///
     /// `old.merge(new)`のように呼び出す
@@ -57,6 +91,7 @@ impl PlaylistApiResponse {
         self.1.total += new.1.total;
         self.1.next_page_token = new.1.next_page_token;
         self.0.extend(new.0);
+        self.2.extend(new.2);
         Ok(())
     }
 }
@@ -65,12 +100,26 @@ impl PlaylistApiResponse {
 // このとき、どのように値を返すか要件等。
 // また、二つ具体作ってもいい (eq: 処理時間を短くする時用)
 
-impl From<PlaylistApiResponse> for VecDeque<VideoId> {
+/// デシリアライズできなかった`item`は`Err`として末尾に含める
+impl From<PlaylistApiResponse> for Vec<Result<PlaylistEntry, MalformedEntry>> {
     fn from(value: PlaylistApiResponse) -> Self {
-        value.0.into_iter().map(|item| item.id).collect()
+        let entries = value.0.into_iter().map(|item| {
+            Ok(PlaylistEntry {
+                id: item.id,
+                raw: item.raw,
+            })
+        });
+        entries.chain(value.2.into_iter().map(Err)).collect()
     }
 }
 
+/// 再生リスト内の動画の`VideoId`と, 保持しているときはそのままの`item`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(in super::super) struct PlaylistEntry {
+    pub id: VideoId,
+    pub raw: Option<Value>,
+}
+
 impl From<&PlaylistApiResponse> for PlaylistDataItself {
     fn from(value: &PlaylistApiResponse) -> Self {
         Self::new(value.1.id.clone(), value.1.total)
@@ -81,21 +130,23 @@ impl From<PlaylistApiResponse> for BasicPlaylistData {
     fn from(value: PlaylistApiResponse) -> Self {
         let pl_id = value.1.id.clone();
         BasicPlaylistData::new(value.0.into_iter().map(Into::into).collect(), pl_id)
+            .with_malformed(value.2)
     }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 struct Item {
     id: VideoId,
-    published_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
     title: String,
     description: String,
     channel_id: String,
     channel_title: String,
+    raw: Option<Value>,
 }
 
-impl From<SnippetPlaylist> for Item {
-    fn from(value: SnippetPlaylist) -> Self {
+impl Item {
+    fn new(value: SnippetPlaylist, raw: Option<Value>) -> Self {
         let common = value.common_snippet;
         Self {
             id: value.resourceId.videoId,
@@ -104,20 +155,25 @@ impl From<SnippetPlaylist> for Item {
             description: common.description,
             channel_id: value.videoOwnerChannelId,
             channel_title: value.videoOwnerChannelTitle,
+            raw,
         }
     }
 }
 
 impl From<Item> for BasicVideoData {
     fn from(value: Item) -> Self {
-        BasicVideoData {
-            id: value.id,
-            upload_at: value.published_at,
-            title: value.title,
-            description: value.description,
-            channel_id: value.channel_id,
-            channel_title: value.channel_title,
+        let mut data = BasicVideoData::new(
+            value.id,
+            value.published_at,
+            value.title,
+            value.description,
+            value.channel_id,
+            value.channel_title,
+        );
+        if let Some(raw) = value.raw {
+            data.insert_raw(Endpoint::PlaylistItems.path(), raw);
         }
+        data
     }
 }
 
@@ -127,6 +183,8 @@ struct PlaylistData {
     total: usize,
     specified_directly: Option<VideoId>,
     next_page_token: Option<PageToken>,
+    /// 全ページを合わせた結果の総数, 非公開や削除済みの動画も含む
+    total_results: Option<usize>,
 }
 
 impl PlaylistData {
@@ -135,12 +193,14 @@ impl PlaylistData {
         total: usize,
         specified_directly: Option<VideoId>,
         next_page_token: Option<PageToken>,
+        total_results: Option<usize>,
     ) -> Self {
         Self {
             id,
             total,
             specified_directly,
             next_page_token,
+            total_results,
         }
     }
 }
@@ -159,10 +219,10 @@ mod tests {
         // 正常にパースできるか
 
         let playlist_data_value =
-            PlaylistApiResponse::new(data_value, &url_pl).unwrap();
+            PlaylistApiResponse::new(data_value, &url_pl, Vec::new()).unwrap();
 
         // 内部値の比較
-        let published_at = Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap();
+        let published_at = Some(Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap());
         assert_eq!(
             playlist_data_value,
             PlaylistApiResponse(
@@ -173,7 +233,8 @@ mod tests {
                         title: "foo_title_0".into(),
                         description: "foo_description_0".into(),
                         channel_id: "UC7_00000000000000000000".into(),
-                        channel_title: "foo_channel_title_made_this_video_0".into()
+                        channel_title: "foo_channel_title_made_this_video_0".into(),
+                        raw: None,
                     },
                     Item {
                         id: VideoId::all_1(),
@@ -181,7 +242,8 @@ mod tests {
                         title: "foo_title_1".into(),
                         description: "foo_description_1".into(),
                         channel_id: "UC7_11111111111111111111".into(),
-                        channel_title: "foo_channel_title_made_this_video_1".into()
+                        channel_title: "foo_channel_title_made_this_video_1".into(),
+                        raw: None,
                     }
                 ]
                 .into(),
@@ -191,9 +253,12 @@ mod tests {
                     specified_directly: Some(VideoId::all_1()),
                     next_page_token: Some(PageToken::new_for_test(
                         "next_page_token".into()
-                    ))
-                }
+                    )),
+                    total_results: Some(120),
+                },
+                Vec::new(),
             )
-        )
+        );
+        assert_eq!(playlist_data_value.get_total_pages(50), Some(3));
     }
 }
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::VecDeque, ops::Deref};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Deref,
+};
 
 use crate::{id::VideoId, metadata::Live};
 
@@ -19,23 +22,76 @@ pub(super) struct ApiResponse {
     pub nextPageToken: Option<PageToken>,
     /// 基本は`nextPageToken`を使用するが、前の値を取得したいときはこれ
     pub prevPageToken: Option<PageToken>,
+    /// 結果の総数など, ページ数の計算に使用
+    pub pageInfo: Option<PageInfo>,
     /// 個々の動画の情報
     pub items: VecDeque<Item>,
+    /// デシリアライズする前の`item`, キーは動画のid
+    ///
+    /// `deserialize_lenient`で作成したときのみ格納される
+    #[serde(skip)]
+    pub raw_items: HashMap<String, Value>,
 }
 
 impl ApiResponse {
+    /// `items`を1件ずつデシリアライズし, できなかったもの, 未知の`kind`のものは`MalformedItem`として返す
+    ///
+    /// `items`が無いときは空とみなす(`fields`パラメータを指定すると空の配列は省略される)
+    ///
+    /// `Err`: `items`以外の部分が想定と異なるとき
+    pub(super) fn deserialize_lenient(
+        mut value: Value,
+    ) -> Result<(Self, Vec<MalformedItem>), String> {
+        let raw_items = match value.get_mut("items").map(Value::take) {
+            Some(Value::Array(items)) => items,
+            Some(other) => return Err(format!("`items` is not an array: {}", other)),
+            None => Vec::new(),
+        };
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("items".into(), Value::Array(Vec::new()));
+        }
+        let mut resp = Self::deserialize(value).map_err(|e| e.to_string())?;
+
+        let mut malformed = Vec::new();
+        for raw in raw_items {
+            let id = read_video_id(&raw);
+            match Item::deserialize(raw.clone()) {
+                // 取り除いた上で`unknown_values`で報告するため`items`にも残す
+                Ok(Item::Unknown(kind)) => {
+                    malformed.push(MalformedItem {
+                        id,
+                        reason: format!("unknown item kind `{}`", kind),
+                    });
+                    resp.items.push_back(Item::Unknown(kind));
+                }
+                Ok(item) => {
+                    resp.items.push_back(item);
+                    if let Some(id) = id {
+                        resp.raw_items.entry(id).or_insert(raw);
+                    }
+                }
+                Err(e) => malformed.push(MalformedItem {
+                    id,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        Ok((resp, malformed))
+    }
+
     #[allow(unused)]
     pub(super) fn is_for_video(&self) -> bool {
         self.items.iter().all(|item| matches!(item, Item::Video { .. }))
     }
 
+    /// 未知の`kind`の`item`は取り除く
     pub(super) fn get_as_video(self) -> Option<VecDeque<ItemVideo>> {
         let mut res: VecDeque<ItemVideo> = VecDeque::new();
         for item in self.items {
-            if let Item::Video(item_video) = item {
-                res.push_back(item_video);
-            } else {
-                return None;
+            match item {
+                Item::Video(item_video) => res.push_back(item_video),
+                Item::Unknown(_) => {}
+                Item::Playlist(_) => return None,
             }
         }
         Some(res)
@@ -46,22 +102,72 @@ impl ApiResponse {
         self.items.iter().all(|item| matches!(item, Item::Playlist { .. }))
     }
 
+    /// 未知の`kind`の`item`は取り除く
     pub(super) fn get_as_playlist(self) -> Option<VecDeque<ItemPlaylist>> {
         let mut res: VecDeque<ItemPlaylist> = VecDeque::new();
         for item in self.items {
-            if let Item::Playlist(item_pl) = item {
-                res.push_back(item_pl);
-            } else {
-                return None;
+            match item {
+                Item::Playlist(item_pl) => res.push_back(item_pl),
+                Item::Unknown(_) => {}
+                Item::Video(_) => return None,
             }
         }
         Some(res)
     }
+
+    /// レスポンスに含まれる想定していない値の説明, APIの仕様変更の検知に使用
+    pub(super) fn unknown_values(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Unknown(kind) => Some(format!("item kind `{}`", kind)),
+                Item::Video(ItemVideo { id, snippet }) => {
+                    match &snippet.liveBroadcastContent {
+                        LiveBroadcast::Unknown(value) => Some(format!(
+                            "liveBroadcastContent `{}` of video `{}`",
+                            value, id
+                        )),
+                        _ => None,
+                    }
+                }
+                Item::Playlist(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// デシリアライズできなかった`item`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct MalformedItem {
+    /// `item`の動画のid, 読み取れなかったときは`None`
+    pub id: Option<String>,
+    pub reason: String,
+}
+
+/// `item`の動画のidを読み取る
+///
+/// `videos`では`id`, `playlistItems`では`snippet.resourceId.videoId`から読み取る
+fn read_video_id(raw: &Value) -> Option<String> {
+    match raw.get("kind").and_then(Value::as_str) {
+        Some("youtube#playlistItem") => raw.pointer("/snippet/resourceId/videoId"),
+        _ => raw.get("id"),
+    }
+    .and_then(Value::as_str)
+    .map(Into::into)
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub(super) struct PageToken(String);
 
+/// depth: **1**
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub(super) struct PageInfo {
+    /// 全ページを合わせた結果の総数, 非公開や削除済みの動画も含む
+    pub totalResults: usize,
+    pub resultsPerPage: usize,
+}
+
 impl Deref for PageToken {
     type Target = String;
     fn deref(&self) -> &Self::Target {
@@ -79,13 +185,40 @@ impl PageToken {
 /// depth: **1**
 ///
 /// https://developers.google.com/youtube/v3/docs/videos
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(tag = "kind")]
 pub(super) enum Item {
     #[serde(rename = "youtube#video")]
     Video(ItemVideo),
     #[serde(rename = "youtube#playlistItem")]
     Playlist(ItemPlaylist),
+    /// 上記以外の`kind`, APIに新しい種類が追加されたとき
+    #[serde(skip_serializing)]
+    Unknown(String),
+}
+
+// `kind`が未知の値でもデシリアライズできるようにカスタムデシリアライズを適用
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: Value = Value::deserialize(deserializer)?;
+        let kind = value
+            .get("kind")
+            .and_then(Value::as_str)
+            .ok_or_else(|| serde::de::Error::missing_field("kind"))?;
+        match kind {
+            "youtube#video" => Ok(Self::Video(
+                serde_json::from_value(value).map_err(serde::de::Error::custom)?,
+            )),
+            "youtube#playlistItem" => Ok(Self::Playlist(
+                serde_json::from_value(value).map_err(serde::de::Error::custom)?,
+            )),
+            other => Ok(Self::Unknown(other.into())),
+        }
+    }
 }
 
 /// depth: **1**
@@ -159,12 +292,19 @@ impl ItemPlaylist {
 }
 
 /// depth: **2**
+///
+/// `fields`パラメータで除外されたフィールドはデフォルト値になる
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub(super) struct CommonSnippet {
     /// 公開時刻; 配信, プレミア公開は枠立ての時刻
-    pub publishedAt: DateTime<Utc>,
+    ///
+    /// `fields`で除外したときなどレスポンスに含まれないときは`None`
+    #[serde(default)]
+    pub publishedAt: Option<DateTime<Utc>>,
+    #[serde(default)]
     pub title: String,
+    #[serde(default)]
     pub description: String,
 }
 
@@ -180,8 +320,10 @@ pub(super) struct SnippetVideo {
     pub common_snippet: CommonSnippet,
     /// 動画の作成者のid
     // TODO このidを`domain`で型定義してもいい
+    #[serde(default)]
     pub channelId: String,
     /// 動画の作成者
+    #[serde(default)]
     pub channelTitle: String,
     pub liveBroadcastContent: LiveBroadcast,
 }
@@ -191,7 +333,7 @@ pub(super) struct SnippetVideo {
 /// Only `Video`
 ///
 /// https://developers.google.com/youtube/v3/docs/videos?hl=ja#snippet.liveBroadcastContent
-#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub(super) enum LiveBroadcast {
     /// プレミア公開中, 配信中
@@ -200,6 +342,9 @@ pub(super) enum LiveBroadcast {
     upcoming,
     /// 公開された後
     none,
+    /// 上記以外, APIに新しい値が追加されたとき
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl From<LiveBroadcast> for Live {
@@ -208,6 +353,7 @@ impl From<LiveBroadcast> for Live {
             LiveBroadcast::live => Self::Live,
             LiveBroadcast::upcoming => Self::Upcoming,
             LiveBroadcast::none => Self::Published,
+            LiveBroadcast::Unknown(value) => Self::Unknown(value),
         }
     }
 }
@@ -224,8 +370,10 @@ pub(super) struct SnippetPlaylist {
     pub common_snippet: CommonSnippet,
     pub resourceId: ResourceId,
     /// 動画の作成者
+    #[serde(default)]
     pub videoOwnerChannelTitle: String,
     /// 動画の作成者のid
+    #[serde(default)]
     pub videoOwnerChannelId: String,
 }
 
@@ -240,6 +388,56 @@ pub(super) struct ResourceId {
     pub videoId: VideoId,
 }
 
+/// depth: **0**
+///
+/// 存在の確認のため`snippet`を含めずに取得したレスポンス
+///
+/// - `videos`: `part=id`
+/// - `playlistItems`: `part=contentDetails,status`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[allow(non_snake_case)]
+pub(super) struct IdOnlyResponse {
+    pub nextPageToken: Option<PageToken>,
+    pub pageInfo: Option<PageInfo>,
+    /// `fields`パラメータを指定すると空の配列は省略される
+    #[serde(default)]
+    pub items: Vec<IdOnlyItem>,
+}
+
+/// depth: **1**
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+pub(super) struct IdOnlyItem {
+    /// `videos`では`VideoId`
+    pub id: Option<String>,
+    /// `playlistItems`のみ
+    pub contentDetails: Option<IdOnlyContentDetails>,
+    /// `playlistItems`のみ
+    pub status: Option<IdOnlyStatus>,
+}
+
+/// depth: **2**
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+pub(super) struct IdOnlyContentDetails {
+    pub videoId: Option<String>,
+}
+
+/// depth: **2**
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+pub(super) struct IdOnlyStatus {
+    pub privacyStatus: Option<String>,
+}
+
+impl IdOnlyItem {
+    /// 再生リスト内の動画が非公開かどうか
+    pub(super) fn is_private(&self) -> bool {
+        self.status.as_ref().and_then(|status| status.privacyStatus.as_deref())
+            == Some("private")
+    }
+}
+
 #[cfg(test)]
 impl ApiResponse {
     pub(super) fn v_dummy() -> Self {
@@ -292,6 +490,10 @@ impl ApiResponse {
         let a = r##"{
             "kind": "youtube#playlistItemListResponse",
             "nextPageToken": "next_page_token",
+            "pageInfo": {
+                "totalResults": 120,
+                "resultsPerPage": 50
+            },
             "items": [
                 {
                     "kind": "youtube#playlistItem",
@@ -332,8 +534,8 @@ pub(super) const UNAVAILABLE_VIDEOS_TITLE: [&str; 2] =
     ["Private video", "Deleted video"];
 
 pub(super) fn is_unavailable_video(title: &str, desc: &str) -> bool {
-    UNAVAILABLE_VIDEOS_TITLE.iter().any(|a| *a == title)
-        && UNAVAILABLE_VIDEOS_DESCRIPTION.iter().any(|a| *a == desc)
+    UNAVAILABLE_VIDEOS_TITLE.contains(&title)
+        && UNAVAILABLE_VIDEOS_DESCRIPTION.contains(&desc)
 }
 
 #[cfg(test)]
@@ -352,6 +554,145 @@ mod tests {
         let _pl = ApiResponse::pl_dummy();
     }
 
+    /// `fields`パラメータで一部のフィールドのみ要求したとき
+    #[test]
+    fn test_deserialize_reduced_payload() {
+        let resp = r##"
+        {
+            "kind": "youtube#videoListResponse",
+            "items": [
+                {
+                    "kind": "youtube#video",
+                    "id": "11111111111",
+                    "snippet": {
+                        "title": "foo_title_1",
+                        "liveBroadcastContent": "none"
+                    }
+                }
+            ]
+        }"##;
+        let api_response: ApiResponse = serde_json::from_str(resp).unwrap();
+        let item = api_response.get_as_video().unwrap().pop_front().unwrap();
+        assert_eq!(item.snippet.common_snippet.title, "foo_title_1");
+        assert_eq!(item.snippet.common_snippet.description, "");
+
+        let resp = r##"
+        {
+            "kind": "youtube#playlistItemListResponse",
+            "items": [
+                {
+                    "kind": "youtube#playlistItem",
+                    "snippet": {
+                        "title": "foo_title_1",
+                        "description": "foo_description_1",
+                        "resourceId": {
+                            "videoId": "11111111111"
+                        }
+                    }
+                }
+            ]
+        }"##;
+        let api_response: ApiResponse = serde_json::from_str(resp).unwrap();
+        let item = api_response.get_as_playlist().unwrap().pop_front().unwrap();
+        assert_eq!(
+            item.available().unwrap().resourceId.videoId,
+            VideoId::all_1()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_lenient_skips_malformed_items() {
+        let resp = serde_json::json!({
+            "kind": "youtube#videoListResponse",
+            "items": [
+                {
+                    "kind": "youtube#video",
+                    "id": "00000000000",
+                    "snippet": { "liveBroadcastContent": 0 }
+                },
+                {
+                    "kind": "youtube#video",
+                    "id": "11111111111",
+                    "snippet": { "liveBroadcastContent": "none" }
+                },
+                { "kind": "youtube#video" }
+            ]
+        });
+        let (api_response, malformed) = ApiResponse::deserialize_lenient(resp).unwrap();
+        // デシリアライズできたもののみそのままの`item`を保持する
+        assert_eq!(
+            api_response.raw_items.keys().collect::<Vec<_>>(),
+            vec!["11111111111"]
+        );
+        let items = api_response.get_as_video().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, VideoId::all_1());
+
+        assert_eq!(malformed.len(), 2);
+        assert_eq!(malformed[0].id.as_deref(), Some("00000000000"));
+        assert!(!malformed[0].reason.is_empty());
+        assert_eq!(malformed[1].id, None);
+    }
+
+    #[test]
+    fn test_deserialize_unknown_values() {
+        let resp = serde_json::json!({
+            "kind": "youtube#videoListResponse",
+            "items": [
+                {
+                    "kind": "youtube#video",
+                    "id": "00000000000",
+                    "snippet": { "liveBroadcastContent": "archived" }
+                },
+                { "kind": "youtube#short", "id": "11111111111" }
+            ]
+        });
+        let (api_response, malformed) = ApiResponse::deserialize_lenient(resp).unwrap();
+        // 未知の`kind`は解釈できなかった`item`としても返す
+        assert_eq!(
+            malformed,
+            vec![MalformedItem {
+                id: Some("11111111111".into()),
+                reason: "unknown item kind `youtube#short`".into(),
+            }]
+        );
+        assert_eq!(
+            api_response.unknown_values(),
+            vec![
+                "liveBroadcastContent `archived` of video `00000000000`".to_string(),
+                "item kind `youtube#short`".to_string()
+            ]
+        );
+
+        // 未知の`kind`は取り除き, 未知の`liveBroadcastContent`は`Live::Unknown`へ
+        let items = api_response.get_as_video().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            Live::from(items[0].snippet.liveBroadcastContent.clone()),
+            Live::Unknown("archived".into())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_lenient_empty_and_mismatch() {
+        // 該当する動画が無いときは空の`items`
+        let resp =
+            serde_json::json!({ "kind": "youtube#videoListResponse", "items": [] });
+        let (api_response, malformed) = ApiResponse::deserialize_lenient(resp).unwrap();
+        assert!(api_response.items.is_empty());
+        assert!(malformed.is_empty());
+
+        let resp = serde_json::json!({ "kind": "youtube#videoListResponse" });
+        assert!(ApiResponse::deserialize_lenient(resp).unwrap().0.items.is_empty());
+
+        // 構造自体が異なるとき
+        let resp = serde_json::json!({ "items": [] });
+        assert!(ApiResponse::deserialize_lenient(resp).is_err());
+        let resp =
+            serde_json::json!({ "kind": "youtube#videoListResponse", "items": {} });
+        assert!(ApiResponse::deserialize_lenient(resp).is_err());
+    }
+
     #[test]
     fn test_deserialize_snippet_playlist() {
         let content = r##"
@@ -369,6 +710,15 @@ mod tests {
         let _a: SnippetPlaylist = serde_json::from_str(content).unwrap();
     }
 
+    #[test]
+    fn test_deserialize_common_snippet_without_published_at() {
+        // `fields`で除外したときは1970年ではなく`None`
+        let snippet: CommonSnippet =
+            serde_json::from_str(r#"{ "title": "title_only" }"#).unwrap();
+        assert_eq!(snippet.publishedAt, None);
+        assert_eq!(snippet.title, "title_only");
+    }
+
     #[test]
     fn test_deserialize_playlist_contains_unavailable_video() {
         let resp = r##"
@@ -414,7 +764,9 @@ mod tests {
             item_pl.next().unwrap(),
             ItemPlaylist::Available(SnippetPlaylist {
                 common_snippet: CommonSnippet {
-                    publishedAt: Utc.with_ymd_and_hms(2024, 1, 10, 15, 0, 0).unwrap(),
+                    publishedAt: Some(
+                        Utc.with_ymd_and_hms(2024, 1, 10, 15, 0, 0).unwrap()
+                    ),
                     title: "title_valid_video".into(),
                     description: "description_valid_video".into()
                 },
@@ -1,136 +1,260 @@
 // external
-use serde::Deserialize;
 use std::{
-    collections::VecDeque,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
-    },
+    collections::{HashSet, VecDeque},
+    sync::{atomic::AtomicUsize, Arc},
 };
+use tokio_util::sync::CancellationToken;
 use tracing::Level;
 
 // crate
 use super::super::{
-    api_request::ApiRequest, collections::quota, response::ApiResponse, ApiKey,
-    ApiOptionsPart, FetchError, MaxIdNum,
+    api_request::ApiRequest,
+    observer::{FetchEvent, SharedObserver},
+    response::{ApiResponse, IdOnlyResponse, MalformedItem},
+    ApiFields, ApiKey, ApiOptionsPart, Endpoint, FetchError, MaxIdNum, QuotaCosts,
+    VideoPart,
 };
 use super::response::VideoApiResponse;
 use crate::{id::VideoId, metadata::FullVideoData, url::UrlVideo};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VideoApiClient {
     max_id: MaxIdNum,
     api_options: ApiOptionsPart,
+    fields: ApiFields,
+    /// レスポンスのそのままの`item`を結果に含めるかどうか
+    keep_raw: bool,
     api_key: ApiKey,
     used_quota: Arc<AtomicUsize>,
+    /// レスポンスに含まれていた想定していない値の数
+    schema_drift: Arc<AtomicUsize>,
+    quota_costs: QuotaCosts,
+    observer: SharedObserver,
 }
 
-impl ApiRequest for VideoApiClient {}
+impl ApiRequest for VideoApiClient {
+    fn observer(&self) -> &SharedObserver {
+        &self.observer
+    }
+
+    fn used_quota(&self) -> &AtomicUsize {
+        &self.used_quota
+    }
+
+    fn schema_drift(&self) -> &AtomicUsize {
+        &self.schema_drift
+    }
+
+    fn quota_costs(&self) -> &QuotaCosts {
+        &self.quota_costs
+    }
+}
 
 impl VideoApiClient {
-    pub fn new_default(api_key: ApiKey, used_quota: Arc<AtomicUsize>) -> Self {
+    pub(in super::super) fn new_default(
+        api_key: ApiKey,
+        used_quota: Arc<AtomicUsize>,
+        observer: SharedObserver,
+    ) -> Self {
         Self {
             max_id: MaxIdNum::default(),
             api_options: ApiOptionsPart::default(),
+            fields: ApiFields::default(),
+            keep_raw: false,
             api_key,
             used_quota,
+            schema_drift: Arc::default(),
+            quota_costs: QuotaCosts::default(),
+            observer,
         }
     }
 
-    pub async fn fetch_all_video_data(
+    /// 消費するquotaの計算に使う表を指定する
+    pub(in super::super) fn with_quota_costs(
+        mut self,
+        quota_costs: QuotaCosts,
+    ) -> Self {
+        self.quota_costs = quota_costs;
+        self
+    }
+
+    /// 想定していない値の数を数える先を指定する
+    pub(in super::super) fn with_schema_drift(
+        mut self,
+        schema_drift: Arc<AtomicUsize>,
+    ) -> Self {
+        self.schema_drift = schema_drift;
+        self
+    }
+
+    /// レスポンスに含めるフィールドを指定する
+    pub(in super::super) fn with_fields(mut self, fields: ApiFields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// レスポンスのそのままの`item`を結果に含める
+    pub(in super::super) fn with_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+
+    /// リクエストする`part`を指定する
+    pub(in super::super) fn with_parts(mut self, api_options: ApiOptionsPart) -> Self {
+        self.api_options = api_options;
+        self
+    }
+
+    /// 一度のリクエストで取得できる`VideoId`の最大数
+    pub(in super::super) fn max_id(&self) -> usize {
+        self.max_id.into()
+    }
+
+    /// `ids`をまとめて一度のリクエストでfetchし, 存在した動画の情報と
+    /// デシリアライズできなかった`item`を返す
+    ///
+    /// - 必須: `ids`は`max_id`の値以下
+    pub(in super::super) async fn fetch_chunk(
         &self,
-        mut urls: VecDeque<UrlVideo>,
-    ) -> Result<VecDeque<Result<FullVideoData, UrlVideo>>, FetchError> {
-        let mut fetched_data: VecDeque<Result<FullVideoData, UrlVideo>> =
-            VecDeque::new();
-
-        while !urls.is_empty() {
-            // `max_id`の数までしか一度にリクエストを送れないので,送れる数を抽出
-            let part_urls: VecDeque<UrlVideo> =
-                urls.drain(..usize::from(self.max_id).min(urls.len())).collect();
-            let video_data = self.process_video(&part_urls).await?;
-
-            fetched_data.extend(self.map_fetched_data_to_urls(&video_data, &part_urls));
-        }
-        Ok(fetched_data)
+        ids: &VecDeque<VideoId>,
+        cancel: &CancellationToken,
+    ) -> Result<(VecDeque<FullVideoData>, Vec<MalformedItem>), FetchError> {
+        let part_urls: VecDeque<UrlVideo> =
+            ids.iter().cloned().map(Into::into).collect();
+        let (video_data, malformed) = self.process_video(&part_urls, cancel).await?;
+        self.observer.notify(FetchEvent::VideoChunkDone { urls: ids.len() });
+        Ok((video_data.into(), malformed))
+    }
+
+    /// `ids`のうち存在する`VideoId`を`part=id`のみで確認する
+    ///
+    /// 非公開や削除済みの動画は含まない
+    ///
+    /// - 必須: `ids`は`max_id`の値以下
+    pub(in super::super) async fn check_chunk(
+        &self,
+        ids: &VecDeque<VideoId>,
+        cancel: &CancellationToken,
+    ) -> Result<HashSet<VideoId>, FetchError> {
+        let parts = ApiOptionsPart::exact(vec![VideoPart::Id]);
+        let url = format!(
+            "{}{}?key={}&part={}&id={}{}",
+            Self::BASE_API_URL,
+            Endpoint::Videos.path(),
+            self.api_key.as_string(),
+            parts.join(","),
+            ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(","),
+            ApiFields::existence().to_query(Endpoint::Videos, &parts.fields()),
+        );
+        let response = self.api_call(Endpoint::Videos, &url, cancel).await?;
+        let resp: IdOnlyResponse = serde_json::from_value(response)
+            .map_err(|e| FetchError::Deserialize(e.to_string()))?;
+        self.observer.notify(FetchEvent::VideoChunkDone { urls: ids.len() });
+        Ok(resp
+            .items
+            .into_iter()
+            .filter_map(|item| item.id)
+            .filter_map(|id| VideoId::new(id).ok())
+            .collect())
     }
 
+    /// `Err`: `url`の`item`をデシリアライズできなかったとき`FetchError::Deserialize`
     pub async fn fetch_video_data(
         &self,
         url: UrlVideo,
+        cancel: &CancellationToken,
     ) -> Result<Result<FullVideoData, UrlVideo>, FetchError> {
-        let video_data = self.process_video(&vec![url.clone()].into()).await?;
-        match video_data {
-            Some(v) => Ok(Ok(v.get_item_by_id(&url.into()).unwrap())),
+        let (video_data, malformed) =
+            self.process_video(&vec![url.clone()].into(), cancel).await?;
+        if let Some(item) = malformed.into_iter().next() {
+            return Err(FetchError::Deserialize(item.reason));
+        }
+        match video_data.get_item_by_id((&url).into()) {
+            Some(v) => Ok(Ok(v)),
             None => Ok(Err(url)),
         }
     }
 
     /// part_urlsをまとめてデータをfetchする
     ///
+    /// `item`ごとにデシリアライズし, できなかったものはログに残し別に返す
+    ///
     /// - 必須: part_urlsは`max_id`の値以下
-    #[tracing::instrument(level = Level::TRACE)]
+    #[tracing::instrument(level = Level::TRACE, skip(cancel))]
     async fn process_video(
         &self,
         part_urls: &VecDeque<UrlVideo>,
-    ) -> Result<Option<VideoApiResponse>, FetchError> {
+        cancel: &CancellationToken,
+    ) -> Result<(VideoApiResponse, Vec<MalformedItem>), FetchError> {
         let url = self.build_video_api_url(part_urls);
-        self.used_quota.fetch_add(quota::VIDEO_INFO, Ordering::Relaxed);
-        let response = self.api_call(&url).await?;
-        let video_resp = ApiResponse::deserialize(response).map_err(|e| e.to_string());
-
-        match video_resp {
-            Ok(v) => {
-                tracing::trace!("fetched content: {:?}", v);
-                // `DataValue`=>`VideoDataValue`にパースできないのは異常なため
-                // FetchErrorを返す
-                Ok(Some(
-                    VideoApiResponse::try_from(v).map_err(FetchError::Parse)?,
-                ))
-            }
-            // `Value`=>`DataValue`にパースできない
-            // eq 全動画の`video_id`が無効なとき
-            // TODO 上本当か確かめる
-            Err(_) => Ok(None),
+        let response = self.api_call(Endpoint::Videos, &url, cancel).await?;
+        // `items`以外の構造が異なるのは異常なため FetchErrorを返す
+        // 該当する動画が無いときは空の`items`が返るので`Ok`
+        let (mut resp, malformed) = ApiResponse::deserialize_lenient(response)
+            .map_err(FetchError::Deserialize)?;
+        if !self.keep_raw {
+            resp.raw_items.clear();
         }
+        tracing::trace!("fetched content: {:?}", resp);
+        for detail in resp.unknown_values() {
+            self.report_schema_drift(detail);
+        }
+        for item in &malformed {
+            tracing::warn!(
+                "skipped malformed video item (id: {}): {}",
+                item.id.as_deref().unwrap_or("unknown"),
+                item.reason
+            );
+        }
+        // `DataValue`=>`VideoDataValue`にパースできないのは異常なため
+        // FetchErrorを返す
+        let video_resp = VideoApiResponse::try_from(resp).map_err(FetchError::Parse)?;
+        Ok((video_resp, malformed))
     }
 
     fn build_video_api_url(&self, video_urls: &VecDeque<UrlVideo>) -> String {
         let id: Vec<&VideoId> = video_urls.iter().map(Into::into).collect();
         format!(
-            "{}videos?key={}&part={}&id={}",
+            "{}{}?key={}&part={}&id={}{}",
             Self::BASE_API_URL,
+            Endpoint::Videos.path(),
             self.api_key.as_string(),
             self.api_options.join(","),
-            id.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",")
+            id.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(","),
+            self.fields.to_query(Endpoint::Videos, &self.api_options.fields())
         )
     }
+}
 
-    /// 引数の`urls`とfetchした情報を照らし合わせfetchに成功したかどうかを判断
-    ///
-    /// urls(id)は`VecDequeue`に格納されている
-    /// - `url(id)`が無効: `Err(Url)`が格納
-    /// - `url(id)`が有効: `Ok(Video)`が格納
-    fn map_fetched_data_to_urls(
-        &self,
-        video_data: &Option<VideoApiResponse>,
-        urls: &VecDeque<UrlVideo>,
-    ) -> VecDeque<Result<FullVideoData, UrlVideo>> {
-        let mut res: VecDeque<Result<FullVideoData, UrlVideo>> = VecDeque::new();
-        if let Some(video_data) = video_data {
-            for url in urls {
-                let part_res = match video_data.get_item_by_id(&url.clone().into()) {
-                    // `video_id`が有効で(存在しており)情報が取得できたとき
-                    Some(data) => Ok(data),
-                    // `video_id`が無効で情報が取得できなかったとき
-                    None => Err(url.clone()),
-                };
-                res.push_back(part_res);
-            }
-        // 全動画の`video_id`が無効で動画情報が取得できなかったとき
-        } else {
-            res = urls.iter().map(|url| Err(url.clone())).collect();
-        }
-        res
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::super::observer::tests::RecordObserver;
+    use super::*;
+
+    #[test]
+    fn test_video_api_client_spend_quota() {
+        let record = Arc::new(RecordObserver::default());
+        let client = VideoApiClient::new_default(
+            ApiKey::new("dummy".into()),
+            Arc::new(AtomicUsize::new(0)),
+            SharedObserver::new(record.clone()),
+        );
+        client.spend_quota(2);
+        client.spend_quota(2);
+        assert_eq!(
+            *record.0.lock().unwrap(),
+            vec![
+                FetchEvent::QuotaSpent { cost: 2, total: 2 },
+                FetchEvent::QuotaSpent { cost: 2, total: 4 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_video_api_client_retry_delay() {
+        assert_eq!(VideoApiClient::retry_delay(1), Duration::from_secs(1));
+        assert_eq!(VideoApiClient::retry_delay(3), Duration::from_secs(4));
     }
 }
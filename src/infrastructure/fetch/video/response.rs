@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use serde_json::Value;
 use std::collections::VecDeque;
 
 use crate::{
@@ -6,18 +7,26 @@ use crate::{
     metadata::{FullVideoData, Live},
 };
 
-use super::super::response::{ApiResponse, SnippetVideo};
+use super::super::{
+    response::{ApiResponse, SnippetVideo},
+    Endpoint,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct VideoApiResponse(VecDeque<Item>);
 
 impl VideoApiResponse {
-    pub fn new(data_value: ApiResponse) -> Result<Self, String> {
+    /// `data_value.raw_items`に格納されている`item`は`FullVideoData`に引き継ぐ
+    pub fn new(mut data_value: ApiResponse) -> Result<Self, String> {
+        let mut raw_items = std::mem::take(&mut data_value.raw_items);
         match data_value.get_as_video() {
             Some(items) => Ok(Self(
                 items
                     .into_iter()
-                    .map(|item| Item::new_from_id_and_snippet(item.id, item.snippet))
+                    .map(|item| {
+                        let raw = raw_items.remove(&item.id.to_string());
+                        Item::new_from_id_and_snippet(item.id, item.snippet, raw)
+                    })
                     .collect(),
             )),
             None => Err(
@@ -51,16 +60,21 @@ impl From<VideoApiResponse> for VecDeque<FullVideoData> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Item {
     id: VideoId,
-    published_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
     title: String,
     description: String,
     channel_id: String,
     channel_title: String,
     live: Live,
+    raw: Option<Value>,
 }
 
 impl Item {
-    fn new_from_id_and_snippet(id: VideoId, snippet: SnippetVideo) -> Self {
+    fn new_from_id_and_snippet(
+        id: VideoId,
+        snippet: SnippetVideo,
+        raw: Option<Value>,
+    ) -> Self {
         let common = snippet.common_snippet;
         Self {
             id,
@@ -70,13 +84,14 @@ impl Item {
             channel_id: snippet.channelId,
             channel_title: snippet.channelTitle,
             live: snippet.liveBroadcastContent.into(),
+            raw,
         }
     }
 }
 
 impl From<Item> for FullVideoData {
     fn from(value: Item) -> Self {
-        Self::new(
+        let mut data = Self::new(
             value.id,
             value.published_at,
             value.title,
@@ -84,21 +99,17 @@ impl From<Item> for FullVideoData {
             value.channel_id,
             value.channel_title,
             value.live,
-        )
+        );
+        if let Some(raw) = value.raw {
+            data.basic_v_data.insert_raw(Endpoint::Videos.path(), raw);
+        }
+        data
     }
 }
 
 impl From<&Item> for FullVideoData {
     fn from(value: &Item) -> Self {
-        Self::new(
-            value.id.clone(),
-            value.published_at,
-            value.title.clone(),
-            value.description.clone(),
-            value.channel_id.clone(),
-            value.channel_title.clone(),
-            value.live,
-        )
+        value.clone().into()
     }
 }
 
@@ -127,11 +138,14 @@ mod tests {
         let full_video_data_to_be_found = FullVideoData {
             basic_v_data: BasicVideoData {
                 id: VideoId::all_1(),
-                upload_at: Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap(),
+                upload_at: Some(Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap()),
                 title: "foo_title_1".into(),
                 description: "foo_description_1".into(),
                 channel_id: "UC7_11111111111111111111".into(),
                 channel_title: "foo_channel_title_made_this_video_1".into(),
+                start: None,
+                selected: false,
+                raw: None,
             },
             live: Live::Live,
         };
@@ -145,7 +159,7 @@ mod tests {
     #[test]
     fn test_video_data_value() {
         let video_api_response = video_api_response();
-        let published_at = Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap();
+        let published_at = Some(Utc.with_ymd_and_hms(2024, 6, 25, 18, 0, 0).unwrap());
 
         // 内部値の比較
         assert_eq!(
@@ -160,6 +174,7 @@ mod tests {
                         channel_id: "UC7_00000000000000000000".into(),
                         channel_title: "foo_channel_title_made_this_video_0".into(),
                         live: Live::Published,
+                        raw: None,
                     },
                     Item {
                         id: VideoId::all_1(),
@@ -169,6 +184,7 @@ mod tests {
                         channel_id: "UC7_11111111111111111111".into(),
                         channel_title: "foo_channel_title_made_this_video_1".into(),
                         live: Live::Live,
+                        raw: None,
                     },
                     Item {
                         id: VideoId::all_2(),
@@ -177,7 +193,8 @@ mod tests {
                         description: "foo_description_2".into(),
                         channel_id: "UC7_22222222222222222222".into(),
                         channel_title: "foo_channel_title_made_this_video_2".into(),
-                        live: Live::Upcoming
+                        live: Live::Upcoming,
+                        raw: None,
                     }
                 ]
                 .into()
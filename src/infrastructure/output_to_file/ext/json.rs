@@ -1,4 +1,8 @@
-use super::super::serialize_safely::SerializeSafely;
+use serde_json::{Map, Value};
+
+use super::super::{
+    serialize_incrementally::SerializeIncrementally, serialize_safely::SerializeSafely,
+};
 use crate::util::tracing::ExpectLog;
 
 #[derive(Debug)]
@@ -10,3 +14,58 @@ impl SerializeSafely for Json {
             .expect_log("failed to serialize with json fmt")
     }
 }
+
+impl SerializeIncrementally for Json {
+    fn begin(key: &str) -> String {
+        format!("{{\n  {}: [", Self::serialize_safely(&key.into()))
+    }
+
+    fn item(value: &Value, idx: usize) -> String {
+        let sep = if idx == 0 { "\n" } else { ",\n" };
+        // 配列の要素は2段階インデントする
+        let indented: Vec<String> = Self::serialize_safely(value)
+            .lines()
+            .map(|line| format!("    {}", line))
+            .collect();
+        format!("{}{}", sep, indented.join("\n"))
+    }
+
+    fn end(len: usize, trailer: &Map<String, Value>) -> String {
+        let close = if len == 0 { "]" } else { "\n  ]" };
+        if trailer.is_empty() {
+            format!("{}\n}}", close)
+        } else {
+            // `{\n  "key": value\n}`の先頭の`{`を除いて続ける
+            let trailer = Self::serialize_safely(&Value::Object(trailer.clone()));
+            format!("{},{}", close, &trailer[1..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_serialize_incrementally() {
+        let items = [serde_json::json!({"a": [1, 2]}), serde_json::json!("b")];
+        let mut trailer = Map::new();
+        trailer.insert("c".into(), true.into());
+
+        let mut content = Json::begin("data");
+        for (idx, item) in items.iter().enumerate() {
+            content.push_str(&Json::item(item, idx));
+        }
+        content.push_str(&Json::end(items.len(), &trailer));
+
+        let expected = serde_json::json!({"data": items, "c": true});
+        assert_eq!(serde_json::from_str::<Value>(&content).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_json_serialize_incrementally_no_item() {
+        let content = format!("{}{}", Json::begin("data"), Json::end(0, &Map::new()));
+        let expected = serde_json::json!({"data": []});
+        assert_eq!(serde_json::from_str::<Value>(&content).unwrap(), expected);
+    }
+}
@@ -1,4 +1,8 @@
-use super::super::serialize_safely::SerializeSafely;
+use serde_json::{Map, Value};
+
+use super::super::{
+    serialize_incrementally::SerializeIncrementally, serialize_safely::SerializeSafely,
+};
 use crate::util::tracing::ExpectLog;
 
 #[derive(Debug)]
@@ -9,3 +13,57 @@ impl SerializeSafely for Yaml {
         serde_yaml::to_string(value).expect_log("failed to serialize with yaml fmt")
     }
 }
+
+impl SerializeIncrementally for Yaml {
+    fn begin(key: &str) -> String {
+        format!("{}:", Self::serialize_safely(&key.into()).trim_end())
+    }
+
+    fn item(value: &Value, _idx: usize) -> String {
+        // 2行目以降は`- `の分だけインデントする
+        let serialized = Self::serialize_safely(value);
+        let lines: Vec<&str> = serialized.lines().collect();
+        format!("\n- {}", lines.join("\n  "))
+    }
+
+    fn end(len: usize, trailer: &Map<String, Value>) -> String {
+        let close = if len == 0 { " []\n" } else { "\n" };
+        if trailer.is_empty() {
+            close.into()
+        } else {
+            let trailer = Self::serialize_safely(&Value::Object(trailer.clone()));
+            format!("{}{}", close, trailer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_serialize_incrementally() {
+        let items = [
+            serde_json::json!({"a": [1, 2], "b": {"c": "multi\nline"}}),
+            serde_json::json!("d"),
+        ];
+        let mut trailer = Map::new();
+        trailer.insert("e".into(), true.into());
+
+        let mut content = Yaml::begin("data");
+        for (idx, item) in items.iter().enumerate() {
+            content.push_str(&Yaml::item(item, idx));
+        }
+        content.push_str(&Yaml::end(items.len(), &trailer));
+
+        let expected = serde_json::json!({"data": items, "e": true});
+        assert_eq!(serde_yaml::from_str::<Value>(&content).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_yaml_serialize_incrementally_no_item() {
+        let content = format!("{}{}", Yaml::begin("data"), Yaml::end(0, &Map::new()));
+        let expected = serde_json::json!({"data": []});
+        assert_eq!(serde_yaml::from_str::<Value>(&content).unwrap(), expected);
+    }
+}
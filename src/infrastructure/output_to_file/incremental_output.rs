@@ -0,0 +1,85 @@
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::Level;
+
+use super::{
+    ext::{json::Json, yaml::Yaml},
+    output::{file_path_with_ext, OutputError},
+    output_file_ext::OutputFileExt,
+    serialize_incrementally::SerializeIncrementally,
+};
+
+/// 配列の要素を1件ずつファイルへ書き込む
+///
+/// 出力は`{ <key>: [<item>...], <trailer>... }`の形式
+///
+/// `finish`を呼ぶまでファイルの内容は文書として不完全
+#[derive(Debug)]
+pub struct IncrementalOutput {
+    file: BufWriter<fs::File>,
+    fmt: OutputFileExt,
+    len: usize,
+}
+
+impl IncrementalOutput {
+    /// ファイルを作成し`key`の配列を開始する
+    #[tracing::instrument(level = Level::TRACE)]
+    pub fn create(
+        file_path_without_ext: PathBuf,
+        fmt: Option<OutputFileExt>,
+        key: &str,
+    ) -> Result<Self, OutputError> {
+        let fmt = fmt.unwrap_or_default();
+        let file_path = file_path_with_ext(file_path_without_ext, fmt);
+        let file = fs::File::create(file_path).map_err(OutputError::Create)?;
+        let mut output = Self {
+            file: BufWriter::new(file),
+            fmt,
+            len: 0,
+        };
+        let begin = match fmt {
+            OutputFileExt::Json => Json::begin(key),
+            OutputFileExt::Yaml => Yaml::begin(key),
+        };
+        output.write_flush(&begin)?;
+        Ok(output)
+    }
+
+    /// 配列に要素を追加する, 書き込んだ内容はすぐにファイルへ反映する
+    pub fn write_item(&mut self, item: &Value) -> Result<(), OutputError> {
+        let item = match self.fmt {
+            OutputFileExt::Json => Json::item(item, self.len),
+            OutputFileExt::Yaml => Yaml::item(item, self.len),
+        };
+        self.write_flush(&item)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// 配列を閉じ, `trailer`の各フィールドを書き込んで終了する
+    pub fn finish(mut self, trailer: Map<String, Value>) -> Result<(), OutputError> {
+        let end = match self.fmt {
+            OutputFileExt::Json => Json::end(self.len, &trailer),
+            OutputFileExt::Yaml => Yaml::end(self.len, &trailer),
+        };
+        self.write_flush(&end)
+    }
+
+    /// これまでに書き込んだ要素の数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn write_flush(&mut self, content: &str) -> Result<(), OutputError> {
+        self.file.write_all(content.as_bytes()).map_err(OutputError::Write)?;
+        self.file.flush().map_err(OutputError::Write)
+    }
+}
@@ -1,7 +1,10 @@
 mod ext;
+mod incremental_output;
 mod output;
 mod output_file_ext;
+mod serialize_incrementally;
 mod serialize_safely;
 
+pub use incremental_output::IncrementalOutput;
 pub use output::{output_to_file, OutputError};
 pub use output_file_ext::{deserialize_option_ext_mode, OutputFileExt};
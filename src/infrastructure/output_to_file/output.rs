@@ -43,20 +43,25 @@ struct SerializedContent {
 #[tracing::instrument(level = Level::TRACE)]
 fn serialize_content(
     content: &Value,
-    mut file_path_without_ext: PathBuf,
+    file_path_without_ext: PathBuf,
     fmt: OutputFileExt,
 ) -> SerializedContent {
-    // `get_ext()`がセパレート文字を必ず含まないのでパニックさせる
-    if !file_path_without_ext.set_extension(fmt.get_ext()) {
-        panic!("failed set ext to file: {:?}", dbg!(file_path_without_ext));
-    }
+    let file_path = file_path_with_ext(file_path_without_ext, fmt);
     // ターボフィッシュを使わないで実装したいのでこのようにしている
     let content = match fmt {
         OutputFileExt::Json => Json::serialize_safely(content),
         OutputFileExt::Yaml => Yaml::serialize_safely(content),
     };
-    SerializedContent {
-        content,
-        file_path: file_path_without_ext,
+    SerializedContent { content, file_path }
+}
+
+pub(super) fn file_path_with_ext(
+    mut file_path_without_ext: PathBuf,
+    fmt: OutputFileExt,
+) -> PathBuf {
+    // `get_ext()`がセパレート文字を必ず含まないのでパニックさせる
+    if !file_path_without_ext.set_extension(fmt.get_ext()) {
+        panic!("failed set ext to file: {:?}", dbg!(file_path_without_ext));
     }
+    file_path_without_ext
 }
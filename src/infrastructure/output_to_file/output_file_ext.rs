@@ -2,18 +2,13 @@ use std::str::FromStr;
 
 use serde::{de::Error, Deserialize, Deserializer};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum OutputFileExt {
+    #[default]
     Json,
     Yaml,
 }
 
-impl Default for OutputFileExt {
-    fn default() -> Self {
-        Self::Json
-    }
-}
-
 impl OutputFileExt {
     /// enum値に対応する拡張子を返す
     ///
@@ -0,0 +1,14 @@
+use serde_json::{Map, Value};
+
+/// 配列の要素を1件ずつ書き込めるように, 出力を分割してシリアライズする
+///
+/// `begin`, `item`(0回以上), `end`の順に結合すると
+/// `{ <key>: [<item>...], <trailer>... }`を表す一つの文書になる
+pub(super) trait SerializeIncrementally {
+    /// 出力の先頭, `key`をキーとする配列を開始する
+    fn begin(key: &str) -> String;
+    /// 配列の要素, `idx`は0始まり
+    fn item(value: &Value, idx: usize) -> String;
+    /// 配列を閉じて`trailer`の各フィールドを続ける, `len`は書き込んだ要素の数
+    fn end(len: usize, trailer: &Map<String, Value>) -> String;
+}
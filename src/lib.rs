@@ -5,7 +5,11 @@ pub mod infrastructure;
 pub mod util;
 
 // re-import
-pub use domain::{id, metadata, url, Error, Handle};
+pub use domain::{
+    id, metadata, url, Annotated, Annotator, Error, Existence, FetchedItem,
+    FetchedResults, Handle, ItemExistence, MalformedEntries, MalformedEntry,
+    ParseError, ParseErrorKind, UrlExistence,
+};
 
 // auxiliary
 pub mod auxiliary;
@@ -1,14 +1,40 @@
 use fetch_yt_data_tools::{
     application::YouTubeService,
-    auxiliary::{input_urls, settings_cfg::Settings},
-    infrastructure::{fetch::ApiClient, output_to_file},
-    util::{convert::separate_vec_deque_result, tracing::apply_tracing_settings},
+    auxiliary::{
+        input_urls::{self, TakeoutContext},
+        progress::ProgressLine,
+        settings_cfg::Settings,
+    },
+    infrastructure::{
+        fetch::{ApiClient, RequestPlan},
+        output_to_file::{IncrementalOutput, OutputError},
+    },
+    metadata::VideoProjection,
+    url::Url,
+    util::tracing::apply_tracing_settings,
+    Annotated, Annotator, Existence, FetchedItem, MalformedEntries,
 };
+use futures::StreamExt;
+use std::{collections::VecDeque, sync::Arc};
+use tokio_util::sync::CancellationToken;
+
+/// Ctrl-Cで中断されたときの終了コード (128 + SIGINT)
+const EXIT_CODE_INTERRUPTED: i32 = 130;
+/// 入力を求めずに実行するとき, 必須の設定が足りなかったときの終了コード
+const EXIT_CODE_MISSING_SETTINGS: i32 = 2;
+/// 指定された`url`の入力元を読み込めなかったときの終了コード
+const EXIT_CODE_INVALID_INPUT: i32 = 3;
 
 // このクレート単体で実行することないので多少汚くて大丈夫
 #[tokio::main]
 async fn main() {
-    let settings = Settings::load();
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(EXIT_CODE_MISSING_SETTINGS);
+        }
+    };
     let _tracing_settings = apply_tracing_settings(
         settings.get_stdout_log_level(),
         settings.get_file_log_level(),
@@ -16,42 +42,374 @@ async fn main() {
 
     println!("Finish input settings!");
 
-    let api_impl = ApiClient::new(settings.get_api_key().as_string().into());
-    let service = YouTubeService::new(api_impl);
-    let urls = input_urls::input();
+    // Takeoutのときは各項目の文脈も保持し, 取得は重複を除いた`url`で行う
+    let takeout_paths = settings.get_takeout_paths();
+    let takeout = (!takeout_paths.is_empty())
+        .then(|| Annotator::new(input_urls::load_takeout(&takeout_paths)));
+    let urls = match &takeout {
+        Some(annotator) => annotator.urls(),
+        None => {
+            let sources = settings.get_url_sources();
+            let loaded = input_urls::load(
+                &sources,
+                &settings.get_input_options(),
+                !settings.get_no_prompt(),
+            );
+            if loaded.ignored > 0 {
+                println!(
+                    "{} entries are ignored due to invalid format.",
+                    loaded.ignored
+                );
+            }
+            // 明示された入力元を読み込めなかったときは, 取得せずに失敗として終了する
+            if !sources.is_empty()
+                && (!loaded.failed.is_empty() || loaded.urls.is_empty())
+            {
+                eprintln!(
+                    "Failed to load urls from the given sources ({} failed, {} url(s) loaded).",
+                    loaded.failed.len(),
+                    loaded.urls.len()
+                );
+                std::process::exit(EXIT_CODE_INVALID_INPUT);
+            }
+            loaded.urls
+        }
+    };
 
     if urls.is_empty() {
         println!("No urls inputted, exit this process.");
         return;
     }
 
-    println!("Start fetching data");
-    let res = service.using_urls(urls).await;
-    let res = match res {
-        Ok(v) => v,
+    if settings.get_dry_run() {
+        let plan = RequestPlan::new(urls, &settings.get_quota_costs());
+        println!("Dry run, no request is sent.");
+        for url in &plan.urls {
+            println!("  > {}", url);
+        }
+        println!("{}", plan);
+        return;
+    }
+
+    let api_key =
+        settings.get_api_key().expect("api key is always loaded unless dry run");
+    let output_path = settings
+        .get_output_path_without_ext()
+        .expect("output path is always loaded unless dry run");
+    let progress = Arc::new(ProgressLine::new());
+    let api_impl = ApiClient::new(api_key.as_string().into())
+        .with_observer(progress.clone())
+        .with_fields(settings.get_api_fields())
+        .with_raw(settings.get_keep_raw())
+        .with_video_parts(settings.get_video_parts())
+        .with_playlist_items_parts(settings.get_playlist_items_parts())
+        .with_quota_costs(settings.get_quota_costs());
+    let projection = settings.get_output_projection();
+    let service = YouTubeService::new(api_impl);
+
+    // 1回目のCtrl-Cは取得済みのデータを書き込んでから終了し, 2回目は待たずに終了する
+    let cancel = CancellationToken::new();
+    let on_signal = cancel.clone();
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if on_signal.is_cancelled() {
+                println!("Interrupted again, exit without waiting.");
+                std::process::exit(EXIT_CODE_INTERRUPTED);
+            }
+            println!(
+                "Interrupted, writing the data fetched so far... (press Ctrl-C again to exit immediately)"
+            );
+            on_signal.cancel();
+        }
+    });
+
+    // 取得できたものから順にファイルへ書き込む
+    let mut output = match IncrementalOutput::create(
+        output_path.into(),
+        Some(settings.get_output_file_ext()),
+        "data",
+    ) {
+        Ok(output) => output,
         Err(e) => {
-            println!("{}, so exit the program.", e);
+            println!("Failed to create output file: `{}`", e);
             return;
         }
     };
-    let (basic_data, invalid_urls) = separate_vec_deque_result(res);
-    println!("Finish fetching data");
 
-    let output = output_to_file::output_to_file(
-        &serde_json::to_value(basic_data).unwrap(),
-        settings.get_output_path_without_ext().into(),
-        Some(settings.get_output_file_ext()),
-    );
+    if settings.get_check_existence() {
+        check_existence(&service, urls, cancel, output, &progress).await;
+        return;
+    }
+
+    if let Some(annotator) = takeout {
+        fetch_takeout(&service, annotator, cancel, output, &projection, &progress)
+            .await;
+        return;
+    }
+
+    println!("Start fetching data");
+    let mut summary = Summary::default();
+    let mut stream = service.using_urls_stream(urls, cancel);
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(item) => {
+                summary.record(&item);
+                if let FetchedItem::Found(data) = item {
+                    let data = projection.apply(&data);
+                    if let Err(e) = output.write_item(&data) {
+                        println!("Failed to write result in file: `{}`", e);
+                        return;
+                    }
+                }
+            }
+            Err(e) => summary.error = Some(e),
+        }
+    }
+    if summary.finish_output(output, &progress, service.api(), "fetching data") {
+        summary.report("fetching data");
+    }
+}
+
+/// 動画の情報を取得せずに存在のみを確認し, 結果を出力ファイルへ書き込む
+async fn check_existence(
+    service: &YouTubeService<ApiClient>,
+    urls: VecDeque<Url>,
+    cancel: CancellationToken,
+    mut output: IncrementalOutput,
+    progress: &ProgressLine,
+) {
+    println!("Start checking existence");
+    let mut summary = Summary::default();
+    let mut counts: Vec<(Existence, usize)> = Vec::new();
+    let mut stream = service.check_existence_stream(urls, cancel);
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(item) => {
+                summary.record(&item);
+                let FetchedItem::Found(report) = item else {
+                    continue;
+                };
+                match counts.iter_mut().find(|(e, _)| *e == report.existence) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((report.existence, 1)),
+                }
+                let report = serde_json::to_value(&report)
+                    .expect("`UrlExistence` is always serializable");
+                if let Err(e) = output.write_item(&report) {
+                    println!("Failed to write result in file: `{}`", e);
+                    return;
+                }
+            }
+            Err(e) => summary.error = Some(e),
+        }
+    }
+    if !summary.finish_output(output, progress, service.api(), "checking existence") {
+        return;
+    }
+    for (existence, count) in counts {
+        println!("  {:?}: {}", existence, count);
+    }
+    summary.report("checking existence");
+}
 
-    if let Err(e) = output {
+/// Takeoutの各項目に取得した情報を対応させ, 記述された順に出力ファイルへ書き込む
+///
+/// 取得できたものから順に, 先頭から結果が確定した項目を書き込む
+///
+/// 削除された動画など取得できなかった項目は`data`を`null`として残す
+async fn fetch_takeout(
+    service: &YouTubeService<ApiClient>,
+    mut annotator: Annotator<TakeoutContext>,
+    cancel: CancellationToken,
+    mut output: IncrementalOutput,
+    projection: &VideoProjection,
+    progress: &ProgressLine,
+) {
+    println!("Start fetching data");
+    let mut summary = Summary::default();
+    let mut missing = 0;
+    let mut stream = service.using_urls_stream(annotator.urls(), cancel);
+    loop {
+        let ready = match stream.next().await {
+            Some(Ok(item)) => {
+                summary.record(&item);
+                annotator.record(item);
+                annotator.take_ready()
+            }
+            Some(Err(e)) => {
+                summary.error = Some(e);
+                continue;
+            }
+            // 失敗して結果が確定しなかった項目も`data`を`null`として書き込む
+            None => break,
+        };
+        if let Err(e) = write_annotated(&mut output, ready, projection, &mut missing) {
+            println!("Failed to write result in file: `{}`", e);
+            return;
+        }
+    }
+    let rest = annotator.take_rest();
+    if let Err(e) = write_annotated(&mut output, rest, projection, &mut missing) {
         println!("Failed to write result in file: `{}`", e);
         return;
     }
 
-    if !invalid_urls.is_empty() {
-        println!("Url that could not be found is following:");
-        for url in invalid_urls {
-            println!("  > {}", url.build_url());
+    let total = output.len();
+    if !summary.finish_output(output, progress, service.api(), "fetching data") {
+        return;
+    }
+    if missing != 0 {
+        println!(
+            "{} of {} entries could not be fetched (deleted or private videos), their `data` is null.",
+            missing, total
+        );
+    }
+    summary.report("fetching data");
+}
+
+/// 文脈を付けた結果を書き込む, `data`が無いものは`null`として`missing`に数える
+fn write_annotated(
+    output: &mut IncrementalOutput,
+    entries: Vec<Annotated<TakeoutContext>>,
+    projection: &VideoProjection,
+    missing: &mut usize,
+) -> Result<(), OutputError> {
+    for entry in entries {
+        let mut item = serde_json::Map::new();
+        item.insert("url".into(), serde_json::json!(entry.url));
+        if let serde_json::Value::Object(context) = serde_json::to_value(&entry.context)
+            .expect("`TakeoutContext` is always serializable")
+        {
+            item.extend(context);
         }
+        let data = match &entry.data {
+            Some(data) => projection.apply(data),
+            None => {
+                *missing += 1;
+                serde_json::Value::Null
+            }
+        };
+        item.insert("data".into(), data);
+        output.write_item(&item.into())?;
     }
+    Ok(())
+}
+
+/// 取得できなかった`url`とその理由のまとめ
+#[derive(Default)]
+struct Summary {
+    not_found: Vec<Url>,
+    unattempted: Vec<Url>,
+    malformed: Vec<(Url, String)>,
+    unsupported: Vec<(Url, String)>,
+    error: Option<fetch_yt_data_tools::Error>,
+}
+
+impl Summary {
+    /// `Found`以外の結果と, `Found`に含まれる再生リスト内の解釈できなかった項目を記録する
+    fn record<T: MalformedEntries>(&mut self, item: &FetchedItem<T, Url>) {
+        match item {
+            FetchedItem::Found(data) => self.malformed.extend(data.malformed_entries()),
+            FetchedItem::NotFound(url) => self.not_found.push(url.clone()),
+            FetchedItem::Unattempted(url) => self.unattempted.push(url.clone()),
+            FetchedItem::Malformed(url, reason) => {
+                self.malformed.push((url.clone(), reason.clone()))
+            }
+            FetchedItem::Unsupported(url, reason) => {
+                self.unsupported.push((url.clone(), reason.clone()))
+            }
+        }
+    }
+
+    /// `Ctrl-C`で中断されたかどうか
+    fn interrupted(&self) -> bool {
+        self.error.is_none() && !self.unattempted.is_empty()
+    }
+
+    /// 進捗の表示を終え, 出力ファイルの末尾に取得できなかった`url`を書き込む
+    ///
+    /// 書き込めなかったときは`false`
+    fn finish_output(
+        &self,
+        output: IncrementalOutput,
+        progress: &ProgressLine,
+        api: &ApiClient,
+        task: &str,
+    ) -> bool {
+        progress.finish();
+        println!("Finish {} (used quota: {})", task, progress.used_quota());
+
+        // 中断, あるいは失敗したときは取得を試みなかったurlも併せて出力する
+        let mut trailer = serde_json::Map::new();
+        trailer.insert("interrupted".into(), self.interrupted().into());
+        trailer.insert(
+            "unattempted_urls".into(),
+            serde_json::json!(self.unattempted),
+        );
+        trailer.insert("schema_drift".into(), api.schema_drift().into());
+        trailer.insert("malformed_urls".into(), reasons_to_json(&self.malformed));
+        trailer.insert(
+            "unsupported_urls".into(),
+            reasons_to_json(&self.unsupported),
+        );
+        if let Err(e) = output.finish(trailer) {
+            println!("Failed to write result in file: `{}`", e);
+            return false;
+        }
+        if api.schema_drift() != 0 {
+            println!(
+                "The responses contained {} unknown values, the API may have changed. See the log for details.",
+                api.schema_drift()
+            );
+        }
+        true
+    }
+
+    /// 取得できなかった`url`を表示し, 失敗, あるいは中断されたときはその終了コードで終了する
+    fn report(self, task: &str) {
+        let interrupted = self.interrupted();
+        if !self.not_found.is_empty() {
+            println!("Url that could not be found is following:");
+            for url in self.not_found {
+                println!("  > {}", url);
+            }
+        }
+
+        if !self.malformed.is_empty() {
+            println!("Url whose response could not be parsed is following:");
+            for (url, reason) in self.malformed {
+                println!("  > {} ({})", url, reason);
+            }
+        }
+
+        if !self.unsupported.is_empty() {
+            println!("Url that cannot be fetched with the api is following:");
+            for (url, reason) in self.unsupported {
+                println!("  > {}\n    {}", url, reason);
+            }
+        }
+
+        if !self.unattempted.is_empty() {
+            println!("Url that were not attempted is following:");
+            for url in self.unattempted {
+                println!("  > {}", url);
+            }
+        }
+
+        if let Some(e) = self.error {
+            println!("{}, so stopped {}.", e, task);
+            std::process::exit(1);
+        }
+
+        if interrupted {
+            std::process::exit(EXIT_CODE_INTERRUPTED);
+        }
+    }
+}
+
+/// `[{url, reason}]`の形にする
+fn reasons_to_json(urls: &[(Url, String)]) -> serde_json::Value {
+    urls.iter()
+        .map(|(url, reason)| serde_json::json!({ "url": url, "reason": reason }))
+        .collect()
 }